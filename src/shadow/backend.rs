@@ -0,0 +1,266 @@
+//! Pluggable raw byte storage behind a [`ShadowTable`](crate::shadow::table::ShadowTable).
+
+use crate::shadow::error::ShadowError;
+
+/// Abstracts the raw byte store backing a
+/// [`ShadowTable`](crate::shadow::table::ShadowTable), so the table's
+/// per-block dirty tracking can sit on top of something other than a dense
+/// `[u8; TS]` array.
+///
+/// `offset`/`end` are byte offsets into the logical `TS`-byte address space,
+/// already bounds-checked by the caller.
+pub trait TableBackend<const TS: usize> {
+    /// Zero-copy read access to `self[offset..end]`.
+    fn with_bytes<F, R>(&self, offset: usize, end: usize, f: F) -> Result<R, ShadowError>
+    where
+        F: FnOnce(&[u8]) -> Result<R, ShadowError>;
+
+    /// Zero-copy read-write access to `self[offset..end]`.
+    fn with_bytes_mut<F, R>(&mut self, offset: usize, end: usize, f: F) -> Result<R, ShadowError>
+    where
+        F: FnOnce(&mut [u8]) -> Result<R, ShadowError>;
+}
+
+/// Dense backend: a plain `[u8; TS]` array held inline.
+///
+/// The default [`TableBackend`] for [`ShadowTable`](crate::shadow::table::ShadowTable),
+/// matching its behavior prior to the introduction of pluggable backends.
+#[derive(Debug, Clone)]
+pub struct DenseBackend<const TS: usize> {
+    bytes: [u8; TS],
+}
+
+impl<const TS: usize> DenseBackend<TS> {
+    /// `const fn` counterpart to [`Default::default`] — `[u8; TS]` isn't
+    /// `Default` for a generic `TS`, so the derive can't be used.
+    pub(crate) const fn new_const() -> Self {
+        Self { bytes: [0u8; TS] }
+    }
+}
+
+impl<const TS: usize> Default for DenseBackend<TS> {
+    fn default() -> Self {
+        Self::new_const()
+    }
+}
+
+impl<const TS: usize> TableBackend<TS> for DenseBackend<TS> {
+    fn with_bytes<F, R>(&self, offset: usize, end: usize, f: F) -> Result<R, ShadowError>
+    where
+        F: FnOnce(&[u8]) -> Result<R, ShadowError>,
+    {
+        f(&self.bytes[offset..end])
+    }
+
+    fn with_bytes_mut<F, R>(&mut self, offset: usize, end: usize, f: F) -> Result<R, ShadowError>
+    where
+        F: FnOnce(&mut [u8]) -> Result<R, ShadowError>,
+    {
+        f(&mut self.bytes[offset..end])
+    }
+}
+
+/// Sparse, page-backed [`TableBackend`] for large, mostly-untouched address
+/// spaces.
+///
+/// Pages are `PAGE_SIZE` bytes — pair this with the owning table's block
+/// size `BS` so every access the table performs (which is always
+/// block-granular or smaller) fits within one page. Pages are allocated
+/// lazily, from a fixed pool of `PAGES` slots, the first time they're
+/// written; a page that has never been written reads back as `fill`
+/// without consuming a slot. This lets a device mirror a large register
+/// map or external EEPROM while only paying RAM for the pages actually
+/// touched.
+///
+/// Because allocated pages aren't laid out contiguously in address order,
+/// [`Self::with_bytes`]/[`Self::with_bytes_mut`] only support a range that
+/// fits within a single page; a range spanning a page boundary returns
+/// [`ShadowError::OutOfBounds`]. Writing a page beyond the pool's `PAGES`
+/// capacity returns [`ShadowError::BackendFull`].
+pub struct SparseBackend<const PAGE_SIZE: usize, const PAGES: usize> {
+    pages: [Option<[u8; PAGE_SIZE]>; PAGES],
+    slot_page: [Option<usize>; PAGES],
+    fill: u8,
+}
+
+impl<const PAGE_SIZE: usize, const PAGES: usize> SparseBackend<PAGE_SIZE, PAGES> {
+    /// Creates an empty backend whose unwritten pages read back as `0x00`.
+    pub fn new() -> Self {
+        Self::with_fill(0)
+    }
+
+    /// Creates an empty backend whose unwritten pages read back as `fill`.
+    pub fn with_fill(fill: u8) -> Self {
+        Self {
+            pages: [None; PAGES],
+            slot_page: [None; PAGES],
+            fill,
+        }
+    }
+
+    fn find_slot(&self, page: usize) -> Option<usize> {
+        self.slot_page.iter().position(|p| *p == Some(page))
+    }
+
+    fn alloc_slot(&mut self, page: usize) -> Result<usize, ShadowError> {
+        if let Some(slot) = self.find_slot(page) {
+            return Ok(slot);
+        }
+        let slot = self
+            .slot_page
+            .iter()
+            .position(|p| p.is_none())
+            .ok_or(ShadowError::BackendFull)?;
+        self.slot_page[slot] = Some(page);
+        self.pages[slot] = Some([self.fill; PAGE_SIZE]);
+        Ok(slot)
+    }
+}
+
+impl<const PAGE_SIZE: usize, const PAGES: usize> Default for SparseBackend<PAGE_SIZE, PAGES> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const TS: usize, const PAGE_SIZE: usize, const PAGES: usize> TableBackend<TS>
+    for SparseBackend<PAGE_SIZE, PAGES>
+{
+    fn with_bytes<F, R>(&self, offset: usize, end: usize, f: F) -> Result<R, ShadowError>
+    where
+        F: FnOnce(&[u8]) -> Result<R, ShadowError>,
+    {
+        if offset == end {
+            return Err(ShadowError::ZeroLength);
+        }
+        let page = offset / PAGE_SIZE;
+        if (end - 1) / PAGE_SIZE != page {
+            return Err(ShadowError::OutOfBounds);
+        }
+        let page_off = offset % PAGE_SIZE;
+        let page_end = page_off + (end - offset);
+
+        match self.find_slot(page) {
+            Some(slot) => f(&self.pages[slot].as_ref().unwrap()[page_off..page_end]),
+            None => f(&[self.fill; PAGE_SIZE][page_off..page_end]),
+        }
+    }
+
+    fn with_bytes_mut<F, R>(&mut self, offset: usize, end: usize, f: F) -> Result<R, ShadowError>
+    where
+        F: FnOnce(&mut [u8]) -> Result<R, ShadowError>,
+    {
+        if offset == end {
+            return Err(ShadowError::ZeroLength);
+        }
+        let page = offset / PAGE_SIZE;
+        if (end - 1) / PAGE_SIZE != page {
+            return Err(ShadowError::OutOfBounds);
+        }
+        let page_off = offset % PAGE_SIZE;
+        let page_end = page_off + (end - offset);
+
+        let slot = self.alloc_slot(page)?;
+        f(&mut self.pages[slot].as_mut().unwrap()[page_off..page_end])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dense_backend_round_trips_bytes() {
+        let mut backend: DenseBackend<16> = DenseBackend::default();
+        backend
+            .with_bytes_mut(4, 8, |buf| {
+                buf.copy_from_slice(&[1, 2, 3, 4]);
+                Ok(())
+            })
+            .unwrap();
+        backend
+            .with_bytes(4, 8, |buf| {
+                assert_eq!(buf, &[1, 2, 3, 4]);
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn sparse_backend_reads_fill_for_unwritten_page() {
+        let backend: SparseBackend<8, 2> = SparseBackend::with_fill(0xFF);
+        TableBackend::<16>::with_bytes(&backend, 0, 8, |buf| {
+            assert_eq!(buf, &[0xFF; 8]);
+            Ok(())
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn sparse_backend_lazily_allocates_on_first_write() {
+        let mut backend: SparseBackend<8, 2> = SparseBackend::new();
+
+        TableBackend::<16>::with_bytes_mut(&mut backend, 0, 4, |buf| {
+            buf.copy_from_slice(&[1, 2, 3, 4]);
+            Ok(())
+        })
+        .unwrap();
+
+        TableBackend::<16>::with_bytes(&backend, 0, 8, |buf| {
+            assert_eq!(buf, &[1, 2, 3, 4, 0, 0, 0, 0]);
+            Ok(())
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn sparse_backend_rejects_zero_length_range() {
+        let mut backend: SparseBackend<8, 2> = SparseBackend::new();
+        assert_eq!(
+            TableBackend::<16>::with_bytes(&backend, 4, 4, |_| Ok(())),
+            Err(ShadowError::ZeroLength)
+        );
+        assert_eq!(
+            TableBackend::<16>::with_bytes_mut(&mut backend, 4, 4, |_| Ok(())),
+            Err(ShadowError::ZeroLength)
+        );
+    }
+
+    #[test]
+    fn sparse_backend_rejects_cross_page_span() {
+        let backend: SparseBackend<8, 2> = SparseBackend::new();
+        let result = TableBackend::<16>::with_bytes(&backend, 4, 12, |_| Ok(()));
+        assert_eq!(result, Err(ShadowError::OutOfBounds));
+    }
+
+    #[test]
+    fn sparse_backend_returns_backend_full_past_pool_capacity() {
+        let mut backend: SparseBackend<8, 2> = SparseBackend::new();
+        TableBackend::<24>::with_bytes_mut(&mut backend, 0, 1, |_| Ok(())).unwrap();
+        TableBackend::<24>::with_bytes_mut(&mut backend, 8, 9, |_| Ok(())).unwrap();
+
+        let result = TableBackend::<24>::with_bytes_mut(&mut backend, 16, 17, |_| Ok(()));
+        assert_eq!(result, Err(ShadowError::BackendFull));
+    }
+
+    #[test]
+    fn sparse_backend_reuses_slot_on_repeat_write_to_same_page() {
+        let mut backend: SparseBackend<8, 1> = SparseBackend::new();
+        TableBackend::<8>::with_bytes_mut(&mut backend, 0, 1, |buf| {
+            buf.copy_from_slice(&[1]);
+            Ok(())
+        })
+        .unwrap();
+        TableBackend::<8>::with_bytes_mut(&mut backend, 2, 3, |buf| {
+            buf.copy_from_slice(&[2]);
+            Ok(())
+        })
+        .unwrap();
+
+        TableBackend::<8>::with_bytes(&backend, 0, 4, |buf| {
+            assert_eq!(buf, &[1, 0, 2, 0]);
+            Ok(())
+        })
+        .unwrap();
+    }
+}
@@ -0,0 +1,66 @@
+//! Read-through fill for shadow addresses that have never been written.
+
+use crate::shadow::ShadowError;
+
+/// Fills never-written shadow bytes from a larger, slower store the shadow
+/// table only mirrors a hot working set of.
+///
+/// Implementations wrap whatever holds the cold image — a QSPI/NOR flash
+/// device, an EEPROM, a file behind a host-side backend — and fill `out`
+/// with the bytes at `addr..addr+out.len()`.
+/// [`HostView::with_ro_slice`](crate::shadow::HostView::with_ro_slice) and
+/// [`HostView::with_rw_slice`](crate::shadow::HostView::with_rw_slice) call
+/// this on a miss (a read touching a block
+/// [`ShadowTable`](crate::shadow::table::ShadowTable) has never marked
+/// populated) and warm the table with the result, so the address reads as
+/// populated from then on without a second round trip.
+pub trait BackingStore {
+    /// Reads `out.len()` bytes starting at `addr` from the backing store
+    /// into `out`.
+    fn load(&self, addr: u16, out: &mut [u8]) -> Result<(), ShadowError>;
+}
+
+/// Default backing store: every address is treated as populated from the
+/// start, so a miss is never possible and this is never called. Correct
+/// whenever the whole image already lives in RAM — the common case, and
+/// why this is the default `BK` type parameter.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NoBackingStore;
+
+impl BackingStore for NoBackingStore {
+    fn load(&self, _addr: u16, _out: &mut [u8]) -> Result<(), ShadowError> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct ConstantBackingStore {
+        fill: u8,
+    }
+
+    impl BackingStore for ConstantBackingStore {
+        fn load(&self, _addr: u16, out: &mut [u8]) -> Result<(), ShadowError> {
+            out.fill(self.fill);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn no_backing_store_leaves_the_buffer_untouched() {
+        let store = NoBackingStore;
+        let mut buf = [0xAAu8; 4];
+        store.load(0, &mut buf).unwrap();
+        assert_eq!(buf, [0xAA; 4]);
+    }
+
+    #[test]
+    fn constant_backing_store_fills_the_buffer() {
+        let store = ConstantBackingStore { fill: 0x55 };
+        let mut buf = [0u8; 4];
+        store.load(16, &mut buf).unwrap();
+        assert_eq!(buf, [0x55; 4]);
+    }
+}
@@ -0,0 +1,349 @@
+//! Typestate builder for [`ShadowStorage`](crate::shadow::ShadowStorage).
+//!
+//! Each stage only exposes the methods valid for that point in configuration,
+//! so a `ShadowStorage` cannot be built without a total size, block layout,
+//! access policy and persistence strategy.
+
+use core::marker::PhantomData;
+
+use crate::shadow::{
+    backend::{DenseBackend, TableBackend},
+    backing::{BackingStore, NoBackingStore},
+    cache::{CacheMaintenance, NoCache},
+    codec::{Codec, NoCodec},
+    fault::{AccessFaultHandler, NoFaultHandler},
+    persist::{NoPersist, PersistTrigger},
+    policy::{AccessPolicy, AllowAllPolicy, NoPersistPolicy, PersistPolicy},
+    storage::ShadowStorage,
+};
+
+/// Initial stage: no configuration supplied yet.
+pub struct NeedTotalSize;
+
+/// Total size chosen; block size still required.
+pub struct NeedBlockSize<const TS: usize>;
+
+/// Block size chosen; block count still required.
+pub struct NeedBlockCount<const TS: usize, const BS: usize>;
+
+/// Layout complete; an access policy is still required.
+pub struct NeedAccessPolicy<const TS: usize, const BS: usize, const BC: usize>;
+
+/// Access policy chosen; a persistence strategy is still required.
+pub struct NeedPersistPolicy<const TS: usize, const BS: usize, const BC: usize, AP> {
+    access_policy: AP,
+}
+
+/// Persist policy chosen; a persist trigger is still required.
+pub struct NeedPersistTrigger<const TS: usize, const BS: usize, const BC: usize, AP, PP, PK> {
+    access_policy: AP,
+    persist_policy: PP,
+    _pk: PhantomData<PK>,
+}
+
+/// All required configuration supplied; ready to optionally set a
+/// [`Codec`], [`AccessFaultHandler`], [`CacheMaintenance`], [`TableBackend`]
+/// or [`BackingStore`] and [`build`](ShadowStorageBuilder::build).
+pub struct Ready<
+    const TS: usize,
+    const BS: usize,
+    const BC: usize,
+    AP,
+    PP,
+    PT,
+    PK,
+    CC,
+    FH,
+    CM,
+    TB = DenseBackend<TS>,
+    BK = NoBackingStore,
+> {
+    access_policy: AP,
+    persist_policy: PP,
+    persist_trigger: PT,
+    compression: CC,
+    fault_handler: FH,
+    cache: CM,
+    backend: TB,
+    backing_store: BK,
+    _pk: PhantomData<PK>,
+}
+
+/// Typestate builder that walks through storage configuration step by step.
+pub struct ShadowStorageBuilder<State> {
+    state: State,
+}
+
+impl ShadowStorageBuilder<NeedTotalSize> {
+    /// Starts a new builder.
+    pub fn new() -> Self {
+        Self {
+            state: NeedTotalSize,
+        }
+    }
+
+    /// Sets the total size of the shadow table in bytes.
+    pub fn total_size<const TS: usize>(self) -> ShadowStorageBuilder<NeedBlockSize<TS>> {
+        ShadowStorageBuilder {
+            state: NeedBlockSize,
+        }
+    }
+}
+
+impl Default for ShadowStorageBuilder<NeedTotalSize> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const TS: usize> ShadowStorageBuilder<NeedBlockSize<TS>> {
+    /// Sets the dirty-tracking block size in bytes.
+    pub fn block_size<const BS: usize>(self) -> ShadowStorageBuilder<NeedBlockCount<TS, BS>> {
+        ShadowStorageBuilder {
+            state: NeedBlockCount,
+        }
+    }
+}
+
+impl<const TS: usize, const BS: usize> ShadowStorageBuilder<NeedBlockCount<TS, BS>> {
+    /// Sets the number of blocks (`TS` must equal `BS * BC`).
+    pub fn block_count<const BC: usize>(self) -> ShadowStorageBuilder<NeedAccessPolicy<TS, BS, BC>>
+    where
+        bitmaps::BitsImpl<BC>: bitmaps::Bits,
+    {
+        ShadowStorageBuilder {
+            state: NeedAccessPolicy,
+        }
+    }
+}
+
+impl<const TS: usize, const BS: usize, const BC: usize>
+    ShadowStorageBuilder<NeedAccessPolicy<TS, BS, BC>>
+where
+    bitmaps::BitsImpl<BC>: bitmaps::Bits,
+{
+    /// Supplies a custom [`AccessPolicy`].
+    pub fn access_policy<AP: AccessPolicy>(
+        self,
+        access_policy: AP,
+    ) -> ShadowStorageBuilder<NeedPersistPolicy<TS, BS, BC, AP>> {
+        ShadowStorageBuilder {
+            state: NeedPersistPolicy { access_policy },
+        }
+    }
+
+    /// Uses [`AllowAllPolicy`], permitting all reads and writes.
+    pub fn default_access(
+        self,
+    ) -> ShadowStorageBuilder<NeedPersistPolicy<TS, BS, BC, AllowAllPolicy>> {
+        self.access_policy(AllowAllPolicy::default())
+    }
+}
+
+impl<const TS: usize, const BS: usize, const BC: usize, AP>
+    ShadowStorageBuilder<NeedPersistPolicy<TS, BS, BC, AP>>
+where
+    AP: AccessPolicy,
+    bitmaps::BitsImpl<BC>: bitmaps::Bits,
+{
+    /// Supplies a custom [`PersistPolicy`]; a [`PersistTrigger`] is required next.
+    pub fn persist_policy<PK, PP: PersistPolicy<PK>>(
+        self,
+        persist_policy: PP,
+    ) -> ShadowStorageBuilder<NeedPersistTrigger<TS, BS, BC, AP, PP, PK>> {
+        ShadowStorageBuilder {
+            state: NeedPersistTrigger {
+                access_policy: self.state.access_policy,
+                persist_policy,
+                _pk: PhantomData,
+            },
+        }
+    }
+
+    /// Disables persistence entirely.
+    pub fn no_persist(
+        self,
+    ) -> ShadowStorageBuilder<
+        Ready<TS, BS, BC, AP, NoPersistPolicy, NoPersist, (), NoCodec, NoFaultHandler, NoCache>,
+    > {
+        ShadowStorageBuilder {
+            state: Ready {
+                access_policy: self.state.access_policy,
+                persist_policy: NoPersistPolicy::default(),
+                persist_trigger: NoPersist,
+                compression: NoCodec,
+                fault_handler: NoFaultHandler,
+                cache: NoCache,
+                backend: DenseBackend::default(),
+                backing_store: NoBackingStore,
+                _pk: PhantomData,
+            },
+        }
+    }
+}
+
+impl<const TS: usize, const BS: usize, const BC: usize, AP, PP, PK>
+    ShadowStorageBuilder<NeedPersistTrigger<TS, BS, BC, AP, PP, PK>>
+where
+    AP: AccessPolicy,
+    PP: PersistPolicy<PK>,
+    bitmaps::BitsImpl<BC>: bitmaps::Bits,
+{
+    /// Supplies the [`PersistTrigger`] that receives persistence requests.
+    pub fn persist_trigger<PT: PersistTrigger<PK>>(
+        self,
+        persist_trigger: PT,
+    ) -> ShadowStorageBuilder<Ready<TS, BS, BC, AP, PP, PT, PK, NoCodec, NoFaultHandler, NoCache>>
+    {
+        ShadowStorageBuilder {
+            state: Ready {
+                access_policy: self.state.access_policy,
+                persist_policy: self.state.persist_policy,
+                persist_trigger,
+                compression: NoCodec,
+                fault_handler: NoFaultHandler,
+                cache: NoCache,
+                backend: DenseBackend::default(),
+                backing_store: NoBackingStore,
+                _pk: PhantomData,
+            },
+        }
+    }
+}
+
+impl<const TS: usize, const BS: usize, const BC: usize, AP, PP, PT, PK, CC, FH, CM, TB, BK>
+    ShadowStorageBuilder<Ready<TS, BS, BC, AP, PP, PT, PK, CC, FH, CM, TB, BK>>
+where
+    AP: AccessPolicy,
+    PP: PersistPolicy<PK>,
+    PT: PersistTrigger<PK>,
+    CC: Codec,
+    FH: AccessFaultHandler,
+    CM: CacheMaintenance,
+    TB: TableBackend<TS>,
+    BK: BackingStore,
+    bitmaps::BitsImpl<BC>: bitmaps::Bits,
+{
+    /// Routes persisted block payloads through `codec` at serialization time
+    /// and inverts it on restore. Defaults to [`NoCodec`] if never called.
+    pub fn compression<NewCC: Codec>(
+        self,
+        codec: NewCC,
+    ) -> ShadowStorageBuilder<Ready<TS, BS, BC, AP, PP, PT, PK, NewCC, FH, CM, TB, BK>> {
+        ShadowStorageBuilder {
+            state: Ready {
+                access_policy: self.state.access_policy,
+                persist_policy: self.state.persist_policy,
+                persist_trigger: self.state.persist_trigger,
+                compression: codec,
+                fault_handler: self.state.fault_handler,
+                cache: self.state.cache,
+                backend: self.state.backend,
+                backing_store: self.state.backing_store,
+                _pk: PhantomData,
+            },
+        }
+    }
+
+    /// Notifies `handler` whenever the access policy denies a read or write.
+    /// Defaults to [`NoFaultHandler`] if never called.
+    pub fn fault_handler<NewFH: AccessFaultHandler>(
+        self,
+        handler: NewFH,
+    ) -> ShadowStorageBuilder<Ready<TS, BS, BC, AP, PP, PT, PK, CC, NewFH, CM, TB, BK>> {
+        ShadowStorageBuilder {
+            state: Ready {
+                access_policy: self.state.access_policy,
+                persist_policy: self.state.persist_policy,
+                persist_trigger: self.state.persist_trigger,
+                compression: self.state.compression,
+                fault_handler: handler,
+                cache: self.state.cache,
+                backend: self.state.backend,
+                backing_store: self.state.backing_store,
+                _pk: PhantomData,
+            },
+        }
+    }
+
+    /// Wires in `cache`, invoked to keep a CPU data cache coherent with
+    /// shadow bytes a DMA engine reads or writes directly. Defaults to
+    /// [`NoCache`] if never called.
+    pub fn cache_maintenance<NewCM: CacheMaintenance>(
+        self,
+        cache: NewCM,
+    ) -> ShadowStorageBuilder<Ready<TS, BS, BC, AP, PP, PT, PK, CC, FH, NewCM, TB, BK>> {
+        ShadowStorageBuilder {
+            state: Ready {
+                access_policy: self.state.access_policy,
+                persist_policy: self.state.persist_policy,
+                persist_trigger: self.state.persist_trigger,
+                compression: self.state.compression,
+                fault_handler: self.state.fault_handler,
+                cache,
+                backend: self.state.backend,
+                backing_store: self.state.backing_store,
+                _pk: PhantomData,
+            },
+        }
+    }
+
+    /// Swaps in a different [`TableBackend`] for the shadow table's raw
+    /// byte store, e.g. a [`SparseBackend`](crate::shadow::backend::SparseBackend)
+    /// for a large, sparsely-populated address space. Defaults to
+    /// [`DenseBackend`] if never called.
+    pub fn backend<NewTB: TableBackend<TS>>(
+        self,
+        backend: NewTB,
+    ) -> ShadowStorageBuilder<Ready<TS, BS, BC, AP, PP, PT, PK, CC, FH, CM, NewTB, BK>> {
+        ShadowStorageBuilder {
+            state: Ready {
+                access_policy: self.state.access_policy,
+                persist_policy: self.state.persist_policy,
+                persist_trigger: self.state.persist_trigger,
+                compression: self.state.compression,
+                fault_handler: self.state.fault_handler,
+                cache: self.state.cache,
+                backend,
+                backing_store: self.state.backing_store,
+                _pk: PhantomData,
+            },
+        }
+    }
+
+    /// Swaps in a different [`BackingStore`], consulted to fill addresses
+    /// the shadow table has never been written to. Defaults to
+    /// [`NoBackingStore`] if never called.
+    pub fn backing_store<NewBK: BackingStore>(
+        self,
+        backing_store: NewBK,
+    ) -> ShadowStorageBuilder<Ready<TS, BS, BC, AP, PP, PT, PK, CC, FH, CM, TB, NewBK>> {
+        ShadowStorageBuilder {
+            state: Ready {
+                access_policy: self.state.access_policy,
+                persist_policy: self.state.persist_policy,
+                persist_trigger: self.state.persist_trigger,
+                compression: self.state.compression,
+                fault_handler: self.state.fault_handler,
+                cache: self.state.cache,
+                backend: self.state.backend,
+                backing_store,
+                _pk: PhantomData,
+            },
+        }
+    }
+
+    /// Builds the configured [`ShadowStorage`].
+    pub fn build(self) -> ShadowStorage<TS, BS, BC, AP, PP, PT, PK, CC, FH, CM, TB, BK> {
+        ShadowStorage::<TS, BS, BC, AP, PP, PT, PK, NoCodec, NoFaultHandler, NoCache>::new(
+            self.state.access_policy,
+            self.state.persist_policy,
+            self.state.persist_trigger,
+        )
+        .with_compression(self.state.compression)
+        .with_fault_handler(self.state.fault_handler)
+        .with_cache_maintenance(self.state.cache)
+        .with_backend(self.state.backend)
+        .with_backing_store(self.state.backing_store)
+    }
+}
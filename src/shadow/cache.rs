@@ -0,0 +1,73 @@
+//! Hooks for keeping a CPU data cache coherent with DMA-backed shadow bytes.
+
+/// Cleans (flushes) and invalidates address ranges in a CPU data cache.
+///
+/// On cache-coherent MCUs this is a no-op (see [`NoCache`]). On a
+/// Cortex-A/Zynq-class SoC with an L2 cache, a DMA engine reading shadow
+/// bytes the host just wrote needs them cleaned from cache first, and a
+/// host reading bytes a DMA engine just wrote needs the range invalidated
+/// first so it doesn't see stale cached data.
+pub trait CacheMaintenance {
+    /// Writes back (flushes) `addr..addr+len` from cache to memory, so a
+    /// DMA engine reading memory directly observes the CPU's writes.
+    fn clean_range(&mut self, addr: u16, len: usize);
+    /// Discards any cached copy of `addr..addr+len`, so the next CPU read
+    /// fetches the bytes a DMA engine wrote to memory directly.
+    fn invalidate_range(&mut self, addr: u16, len: usize);
+}
+
+/// Default cache maintenance: both hooks are no-ops.
+///
+/// Correct whenever the shadow table lives in cache-coherent memory, or
+/// DMA isn't in play at all — the common case, and why this is the
+/// default `CM` type parameter.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NoCache;
+
+impl CacheMaintenance for NoCache {
+    fn clean_range(&mut self, _addr: u16, _len: usize) {}
+    fn invalidate_range(&mut self, _addr: u16, _len: usize) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct RecordingCache {
+        cleaned: [(u16, usize); 4],
+        clean_count: usize,
+        invalidated: [(u16, usize); 4],
+        invalidate_count: usize,
+    }
+
+    impl CacheMaintenance for RecordingCache {
+        fn clean_range(&mut self, addr: u16, len: usize) {
+            self.cleaned[self.clean_count] = (addr, len);
+            self.clean_count += 1;
+        }
+        fn invalidate_range(&mut self, addr: u16, len: usize) {
+            self.invalidated[self.invalidate_count] = (addr, len);
+            self.invalidate_count += 1;
+        }
+    }
+
+    #[test]
+    fn no_cache_ignores_both_hooks() {
+        let mut cache = NoCache;
+        cache.clean_range(0, 4);
+        cache.invalidate_range(0, 4);
+    }
+
+    #[test]
+    fn recording_cache_tracks_both_hooks() {
+        let mut cache = RecordingCache::default();
+        cache.clean_range(0, 4);
+        cache.invalidate_range(16, 8);
+
+        assert_eq!(cache.clean_count, 1);
+        assert_eq!(cache.cleaned[0], (0, 4));
+        assert_eq!(cache.invalidate_count, 1);
+        assert_eq!(cache.invalidated[0], (16, 8));
+    }
+}
@@ -0,0 +1,78 @@
+//! Pluggable compression for persisted block payloads.
+
+use crate::shadow::ShadowError;
+
+/// Encodes and decodes shadow table block payloads for persistence.
+///
+/// Implementations trade CPU time for reduced flash writes. Blocks are
+/// encoded on the way out to persistence and decoded on restore, so
+/// [`Self::decode`] must invert [`Self::encode`] exactly.
+pub trait Codec {
+    /// Encodes `block` into `out`, returning the number of bytes written.
+    fn encode(&self, block: &[u8], out: &mut [u8]) -> Result<usize, ShadowError>;
+
+    /// Decodes `encoded` into `out`, returning the number of bytes written.
+    fn decode(&self, encoded: &[u8], out: &mut [u8]) -> Result<usize, ShadowError>;
+}
+
+/// Default codec: copies block payloads through unchanged.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NoCodec;
+
+impl Codec for NoCodec {
+    fn encode(&self, block: &[u8], out: &mut [u8]) -> Result<usize, ShadowError> {
+        if out.len() < block.len() {
+            return Err(ShadowError::OutOfBounds);
+        }
+        out[..block.len()].copy_from_slice(block);
+        Ok(block.len())
+    }
+
+    fn decode(&self, encoded: &[u8], out: &mut [u8]) -> Result<usize, ShadowError> {
+        self.encode(encoded, out)
+    }
+}
+
+/// LZ4 block-compressed codec, trading CPU time for flash writes.
+#[cfg(feature = "lz4")]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Lz4Codec;
+
+#[cfg(feature = "lz4")]
+impl Codec for Lz4Codec {
+    fn encode(&self, block: &[u8], out: &mut [u8]) -> Result<usize, ShadowError> {
+        lz4_flex::block::compress_into(block, out).map_err(|_| ShadowError::OutOfBounds)
+    }
+
+    fn decode(&self, encoded: &[u8], out: &mut [u8]) -> Result<usize, ShadowError> {
+        lz4_flex::block::decompress_into(encoded, out).map_err(|_| ShadowError::OutOfBounds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_codec_roundtrip() {
+        let data = [0xAA, 0xBB, 0xCC, 0xDD];
+
+        let mut encoded = [0u8; 4];
+        let len = NoCodec.encode(&data, &mut encoded).unwrap();
+        assert_eq!(&encoded[..len], &data);
+
+        let mut decoded = [0u8; 4];
+        let len = NoCodec.decode(&encoded[..len], &mut decoded).unwrap();
+        assert_eq!(&decoded[..len], &data);
+    }
+
+    #[test]
+    fn no_codec_out_of_bounds() {
+        let data = [0u8; 4];
+        let mut out = [0u8; 2];
+        assert_eq!(
+            NoCodec.encode(&data, &mut out),
+            Err(ShadowError::OutOfBounds)
+        );
+    }
+}
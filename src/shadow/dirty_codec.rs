@@ -0,0 +1,390 @@
+//! Compact dirty-region framing for syncing a shadow across a transport —
+//! a second core, or a remote device over UART/SPI — without shipping the
+//! whole table on every round.
+
+use crate::shadow::{
+    backend::TableBackend,
+    cache::CacheMaintenance,
+    codec::Codec,
+    fault::AccessFaultHandler,
+    handle::{HostShadow, KernelShadow},
+    policy::PersistPolicy,
+    storage::NoStage,
+    AccessPolicy, PersistTrigger, ShadowError, WriteResult,
+};
+
+/// `base_addr(2) + len(2) + crc16(2)`.
+const FRAME_HEADER_LEN: usize = 6;
+
+/// CRC-16/MODBUS (poly 0xA001, init 0xFFFF), computed one byte at a time so
+/// callers don't need to assemble a contiguous header+payload buffer first.
+/// See [`crate::shadow::helpers`]'s `crc32` for the 32-bit sibling used by
+/// the journal persist backend.
+fn crc16(bytes: impl Iterator<Item = u8>) -> u16 {
+    let mut crc: u16 = 0xFFFF;
+    for byte in bytes {
+        crc ^= byte as u16;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xA001 & mask);
+        }
+    }
+    crc
+}
+
+/// Encodes/decodes a shadow's dirty blocks as a framed byte stream, so a
+/// peer shadow living in another domain can be kept in sync without
+/// exposing the whole table every time.
+///
+/// Each frame is `[base_addr: u16 LE, len: u16 LE, crc16: u16 LE, bytes...]`.
+/// [`Self::encode`] coalesces adjacent dirty blocks into a single frame;
+/// [`Self::decode`] applies each frame it validates to a peer
+/// [`HostShadow`] through the normal [`AccessPolicy`]/[`PersistPolicy`]
+/// path, the same as any other host-side write.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct DirtyCodec;
+
+impl DirtyCodec {
+    /// Walks every dirty block in `kernel`, coalesces adjacent blocks into
+    /// runs, and writes one frame per run into `out`. Synchronized with any
+    /// `HostShadow` access to the same storage when the `sync` feature is
+    /// enabled, the same as [`KernelShadow::with_view`].
+    ///
+    /// Returns the number of bytes written, or [`ShadowError::OutOfBounds`]
+    /// if `out` is too small for the frames produced so far.
+    pub fn encode<
+        const TS: usize,
+        const BS: usize,
+        const BC: usize,
+        AP,
+        PP,
+        PT,
+        PK,
+        SS,
+        CC,
+        FH,
+        CM,
+        TB,
+    >(
+        &self,
+        kernel: &KernelShadow<'_, TS, BS, BC, AP, PP, PT, PK, SS, CC, FH, CM, TB>,
+        out: &mut [u8],
+    ) -> Result<usize, ShadowError>
+    where
+        AP: AccessPolicy,
+        PP: PersistPolicy<PK>,
+        PT: PersistTrigger<PK>,
+        CC: Codec,
+        FH: AccessFaultHandler,
+        CM: CacheMaintenance,
+        TB: TableBackend<TS>,
+        bitmaps::BitsImpl<BC>: bitmaps::Bits,
+    {
+        kernel.with_view(|view| {
+            let mut addrs: [u16; BC] = [0; BC];
+            let mut count = 0;
+            view.iter_dirty(|addr, _data| {
+                if count < addrs.len() {
+                    addrs[count] = addr;
+                    count += 1;
+                }
+                Ok(())
+            })?;
+
+            let mut offset = 0;
+            let mut i = 0;
+            while i < count {
+                let run_addr = addrs[i];
+                let mut run_len = BS;
+                let mut j = i + 1;
+                while j < count && addrs[j] as usize == run_addr as usize + run_len {
+                    run_len += BS;
+                    j += 1;
+                }
+
+                if out.len() < offset + FRAME_HEADER_LEN + run_len {
+                    return Err(ShadowError::OutOfBounds);
+                }
+
+                let (header, body) = out[offset..].split_at_mut(FRAME_HEADER_LEN);
+                view.with_ro_slice(run_addr, run_len, |slice| {
+                    slice.copy_to_slice(&mut body[..run_len]);
+                })?;
+
+                header[0..2].copy_from_slice(&run_addr.to_le_bytes());
+                header[2..4].copy_from_slice(&(run_len as u16).to_le_bytes());
+                let crc = crc16(body[..run_len].iter().copied());
+                header[4..6].copy_from_slice(&crc.to_le_bytes());
+
+                offset += FRAME_HEADER_LEN + run_len;
+                i = j;
+            }
+
+            Ok(offset)
+        })
+    }
+
+    /// Validates and applies each frame in `frames` to `host`, through the
+    /// normal [`AccessPolicy`]/[`PersistPolicy`] path — a frame that touches
+    /// a read-only region is rejected with [`ShadowError::Denied`], the
+    /// same as any other denied write.
+    ///
+    /// Stops at the first incomplete trailing frame (fewer than
+    /// `base_addr+len+crc16+len` bytes remaining) rather than erroring,
+    /// since a partial frame at the end of a buffer is the caller's cue to
+    /// wait for more data, not a corrupt stream. A frame whose CRC doesn't
+    /// match its bytes *is* treated as a corrupt stream and reported as
+    /// [`ShadowError::ChecksumMismatch`] immediately, since every frame
+    /// after it can no longer be located reliably.
+    ///
+    /// Returns the number of bytes consumed.
+    pub fn decode<
+        const TS: usize,
+        const BS: usize,
+        const BC: usize,
+        AP,
+        PP,
+        PT,
+        PK,
+        CC,
+        FH,
+        CM,
+        TB,
+    >(
+        &self,
+        host: &HostShadow<'_, TS, BS, BC, AP, PP, PT, PK, NoStage, CC, FH, CM, TB>,
+        frames: &[u8],
+    ) -> Result<usize, ShadowError>
+    where
+        AP: AccessPolicy,
+        PP: PersistPolicy<PK>,
+        PT: PersistTrigger<PK>,
+        CC: Codec,
+        FH: AccessFaultHandler,
+        CM: CacheMaintenance,
+        TB: TableBackend<TS>,
+        bitmaps::BitsImpl<BC>: bitmaps::Bits,
+    {
+        let mut offset = 0;
+        while offset + FRAME_HEADER_LEN <= frames.len() {
+            let addr = u16::from_le_bytes([frames[offset], frames[offset + 1]]);
+            let len = u16::from_le_bytes([frames[offset + 2], frames[offset + 3]]) as usize;
+            let stored_crc = u16::from_le_bytes([frames[offset + 4], frames[offset + 5]]);
+
+            if offset + FRAME_HEADER_LEN + len > frames.len() {
+                break;
+            }
+
+            let body = &frames[offset + FRAME_HEADER_LEN..offset + FRAME_HEADER_LEN + len];
+            if crc16(body.iter().copied()) != stored_crc {
+                return Err(ShadowError::ChecksumMismatch);
+            }
+
+            host.with_view(|view| {
+                view.with_wo_slice(addr, len, |mut slice| {
+                    slice.copy_from_slice(body);
+                    WriteResult::Dirty(())
+                })
+            })?;
+
+            offset += FRAME_HEADER_LEN + len;
+        }
+
+        Ok(offset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shadow::test_support::{test_storage, ReadOnlyBelow32};
+    use crate::shadow::{AllowAllPolicy, NoPersist, NoPersistPolicy, ShadowStorage};
+
+    #[test]
+    fn encode_coalesces_adjacent_dirty_blocks_into_one_frame() {
+        let storage = test_storage();
+        storage.host_shadow().with_view(|view| {
+            view.with_wo_slice(0, 16, |mut slice| {
+                slice.copy_from_slice(&[0xAA; 16]);
+                WriteResult::Dirty(())
+            })
+            .unwrap();
+            view.with_wo_slice(16, 16, |mut slice| {
+                slice.copy_from_slice(&[0xBB; 16]);
+                WriteResult::Dirty(())
+            })
+            .unwrap();
+        });
+
+        let mut out = [0u8; 64];
+        let len = DirtyCodec
+            .encode(&storage.kernel_shadow(), &mut out)
+            .unwrap();
+
+        // One coalesced frame covering both adjacent 16-byte blocks.
+        assert_eq!(len, FRAME_HEADER_LEN + 32);
+        assert_eq!(&out[0..2], &0u16.to_le_bytes());
+        assert_eq!(&out[2..4], &32u16.to_le_bytes());
+        assert_eq!(&out[6..22], &[0xAA; 16]);
+        assert_eq!(&out[22..38], &[0xBB; 16]);
+    }
+
+    #[test]
+    fn encode_emits_separate_frames_for_non_adjacent_dirty_blocks() {
+        let storage = test_storage();
+        storage.host_shadow().with_view(|view| {
+            view.with_wo_slice(0, 4, |mut slice| {
+                slice.copy_from_slice(&[1, 2, 3, 4]);
+                WriteResult::Dirty(())
+            })
+            .unwrap();
+            view.with_wo_slice(32, 4, |mut slice| {
+                slice.copy_from_slice(&[5, 6, 7, 8]);
+                WriteResult::Dirty(())
+            })
+            .unwrap();
+        });
+
+        let mut out = [0u8; 64];
+        let len = DirtyCodec
+            .encode(&storage.kernel_shadow(), &mut out)
+            .unwrap();
+
+        assert_eq!(len, 2 * (FRAME_HEADER_LEN + 16));
+        assert_eq!(&out[0..2], &0u16.to_le_bytes());
+        let second_frame = FRAME_HEADER_LEN + 16;
+        assert_eq!(&out[second_frame..second_frame + 2], &32u16.to_le_bytes());
+    }
+
+    #[test]
+    fn encode_reports_out_of_bounds_when_output_too_small() {
+        let storage = test_storage();
+        storage.host_shadow().with_view(|view| {
+            view.with_wo_slice(0, 16, |mut slice| {
+                slice.copy_from_slice(&[0xAA; 16]);
+                WriteResult::Dirty(())
+            })
+            .unwrap();
+        });
+
+        let mut out = [0u8; 4];
+        assert_eq!(
+            DirtyCodec.encode(&storage.kernel_shadow(), &mut out),
+            Err(ShadowError::OutOfBounds)
+        );
+    }
+
+    #[test]
+    fn roundtrip_through_encode_and_decode_applies_dirty_bytes_to_peer() {
+        let source = test_storage();
+        let peer = test_storage();
+
+        source.host_shadow().with_view(|view| {
+            view.with_wo_slice(8, 8, |mut slice| {
+                slice.copy_from_slice(&[0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88]);
+                WriteResult::Dirty(())
+            })
+            .unwrap();
+        });
+
+        let mut frames = [0u8; 64];
+        let len = DirtyCodec
+            .encode(&source.kernel_shadow(), &mut frames)
+            .unwrap();
+
+        let consumed = DirtyCodec
+            .decode(&peer.host_shadow(), &frames[..len])
+            .unwrap();
+        assert_eq!(consumed, len);
+
+        peer.kernel_shadow().with_view(|view| {
+            view.with_ro_slice(8, 8, |slice| {
+                let mut buf = [0u8; 8];
+                slice.copy_to_slice(&mut buf);
+                assert_eq!(buf, [0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88]);
+            })
+            .unwrap();
+            assert!(view.is_dirty(8, 8).unwrap());
+        });
+    }
+
+    #[test]
+    fn decode_rejects_corrupted_frame_with_checksum_mismatch() {
+        let source = test_storage();
+        let peer = test_storage();
+
+        source.host_shadow().with_view(|view| {
+            view.with_wo_slice(0, 4, |mut slice| {
+                slice.copy_from_slice(&[1, 2, 3, 4]);
+                WriteResult::Dirty(())
+            })
+            .unwrap();
+        });
+
+        let mut frames = [0u8; 32];
+        let len = DirtyCodec
+            .encode(&source.kernel_shadow(), &mut frames)
+            .unwrap();
+        frames[FRAME_HEADER_LEN] ^= 0xFF; // Flip a payload byte after the CRC was computed.
+
+        assert_eq!(
+            DirtyCodec.decode(&peer.host_shadow(), &frames[..len]),
+            Err(ShadowError::ChecksumMismatch)
+        );
+    }
+
+    #[test]
+    fn decode_stops_at_incomplete_trailing_frame() {
+        let source = test_storage();
+        let peer = test_storage();
+
+        source.host_shadow().with_view(|view| {
+            view.with_wo_slice(0, 4, |mut slice| {
+                slice.copy_from_slice(&[1, 2, 3, 4]);
+                WriteResult::Dirty(())
+            })
+            .unwrap();
+        });
+
+        let mut frames = [0u8; 32];
+        let len = DirtyCodec
+            .encode(&source.kernel_shadow(), &mut frames)
+            .unwrap();
+
+        // Truncate the last byte of the only frame.
+        let consumed = DirtyCodec
+            .decode(&peer.host_shadow(), &frames[..len - 1])
+            .unwrap();
+        assert_eq!(consumed, 0);
+    }
+
+    #[test]
+    fn decode_rejects_frame_touching_read_only_region() {
+        let source: ShadowStorage<64, 16, 4, AllowAllPolicy, NoPersistPolicy, NoPersist, ()> =
+            ShadowStorage::new(
+                AllowAllPolicy::default(),
+                NoPersistPolicy::default(),
+                NoPersist,
+            );
+        let peer: ShadowStorage<64, 16, 4, ReadOnlyBelow32, NoPersistPolicy, NoPersist, ()> =
+            ShadowStorage::new(ReadOnlyBelow32, NoPersistPolicy::default(), NoPersist);
+
+        source.host_shadow().with_view(|view| {
+            view.with_wo_slice(0, 4, |mut slice| {
+                slice.copy_from_slice(&[1, 2, 3, 4]);
+                WriteResult::Dirty(())
+            })
+            .unwrap();
+        });
+
+        let mut frames = [0u8; 32];
+        let len = DirtyCodec
+            .encode(&source.kernel_shadow(), &mut frames)
+            .unwrap();
+
+        assert_eq!(
+            DirtyCodec.decode(&peer.host_shadow(), &frames[..len]),
+            Err(ShadowError::Denied)
+        );
+    }
+}
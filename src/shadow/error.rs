@@ -9,6 +9,28 @@ pub enum ShadowError {
     Denied,
     /// Staging buffer capacity exceeded.
     StageFull,
+    /// Backend's fixed-size storage pool has no free capacity left.
+    BackendFull,
+    /// A transaction touched more distinct blocks than its scratch buffer
+    /// can snapshot.
+    TransactionFull,
+    /// A [`PersistBackend`](crate::shadow::persist::PersistBackend) failed to
+    /// commit a region to non-volatile storage.
+    PersistFailed,
+    /// A DMA region lease overlaps a block already pinned by another
+    /// in-flight DMA lease.
+    Pinned,
+    /// A decoded frame's CRC didn't match its bytes.
+    ChecksumMismatch,
+    /// A staged write overlapped an already-staged range under
+    /// [`ConflictPolicy::Reject`](crate::shadow::staged::ConflictPolicy::Reject).
+    StagingConflict,
+    /// A DMA region lease's address or length wasn't a multiple of the
+    /// block size.
+    Unaligned,
+    /// A bulk read or write ran past the end of the slice before filling
+    /// the requested destination/source.
+    UnexpectedEof,
 }
 
 impl core::fmt::Display for ShadowError {
@@ -18,6 +40,34 @@ impl core::fmt::Display for ShadowError {
             ShadowError::ZeroLength => write!(f, "operation attempted with zero length"),
             ShadowError::Denied => write!(f, "access denied by policy"),
             ShadowError::StageFull => write!(f, "staging buffer capacity exceeded"),
+            ShadowError::BackendFull => write!(f, "backend storage pool capacity exceeded"),
+            ShadowError::TransactionFull => {
+                write!(
+                    f,
+                    "transaction touched more blocks than its scratch buffer allows"
+                )
+            }
+            ShadowError::PersistFailed => {
+                write!(f, "persist backend failed to commit a region to storage")
+            }
+            ShadowError::Pinned => {
+                write!(
+                    f,
+                    "region overlaps a block already pinned by another DMA lease"
+                )
+            }
+            ShadowError::ChecksumMismatch => {
+                write!(f, "decoded frame's CRC didn't match its bytes")
+            }
+            ShadowError::StagingConflict => {
+                write!(f, "staged write overlapped an already-staged range")
+            }
+            ShadowError::Unaligned => {
+                write!(f, "DMA region address or length wasn't block-aligned")
+            }
+            ShadowError::UnexpectedEof => {
+                write!(f, "bulk read or write ran past the end of the slice")
+            }
         }
     }
 }
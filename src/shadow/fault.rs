@@ -0,0 +1,60 @@
+//! Hooks invoked when an [`AccessPolicy`](crate::shadow::AccessPolicy) denies a read or write.
+
+/// Receives notification whenever an access policy denies a read or write.
+///
+/// Implementations can maintain violation counters, latch a fault flag, or
+/// trigger a protective action. Invoked immediately before the denial site
+/// returns [`ShadowError::Denied`](crate::shadow::ShadowError::Denied).
+pub trait AccessFaultHandler {
+    /// Called when a read at `addr` for `len` bytes is denied.
+    fn on_read_denied(&mut self, addr: u16, len: usize);
+    /// Called when a write at `addr` for `len` bytes is denied.
+    fn on_write_denied(&mut self, addr: u16, len: usize);
+}
+
+/// Default fault handler: ignores every denial.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NoFaultHandler;
+
+impl AccessFaultHandler for NoFaultHandler {
+    fn on_read_denied(&mut self, _addr: u16, _len: usize) {}
+    fn on_write_denied(&mut self, _addr: u16, _len: usize) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct CountingFaultHandler {
+        reads_denied: usize,
+        writes_denied: usize,
+    }
+
+    impl AccessFaultHandler for CountingFaultHandler {
+        fn on_read_denied(&mut self, _addr: u16, _len: usize) {
+            self.reads_denied += 1;
+        }
+        fn on_write_denied(&mut self, _addr: u16, _len: usize) {
+            self.writes_denied += 1;
+        }
+    }
+
+    #[test]
+    fn no_fault_handler_ignores_denials() {
+        let mut handler = NoFaultHandler;
+        handler.on_read_denied(0, 4);
+        handler.on_write_denied(0, 4);
+    }
+
+    #[test]
+    fn counting_fault_handler_tracks_denials() {
+        let mut handler = CountingFaultHandler::default();
+        handler.on_read_denied(0, 4);
+        handler.on_write_denied(32, 8);
+        handler.on_write_denied(32, 8);
+
+        assert_eq!(handler.reads_denied, 1);
+        assert_eq!(handler.writes_denied, 2);
+    }
+}
@@ -0,0 +1,270 @@
+#![allow(unsafe_code)]
+
+use crate::shadow::{
+    backend::{DenseBackend, TableBackend},
+    backing::BackingStore,
+    cache::CacheMaintenance,
+    codec::Codec,
+    fault::AccessFaultHandler,
+    policy::PersistPolicy,
+    storage::{NoStage, ShadowStorageBase, WithStage},
+    types::StagingBuffer,
+    view::{DmaDirection, DmaRegion, HostView, HostViewStaged},
+    AccessPolicy, PersistTrigger, ShadowError,
+};
+
+pub struct HostShadow<
+    'a,
+    const TS: usize,
+    const BS: usize,
+    const BC: usize,
+    AP,
+    PP,
+    PT,
+    PK,
+    SS,
+    CC,
+    FH,
+    CM,
+    TB = DenseBackend<TS>,
+    BK = crate::shadow::backing::NoBackingStore,
+> where
+    AP: AccessPolicy,
+    PP: PersistPolicy<PK>,
+    PT: PersistTrigger<PK>,
+    CC: Codec,
+    FH: AccessFaultHandler,
+    CM: CacheMaintenance,
+    TB: TableBackend<TS>,
+    BK: BackingStore,
+    bitmaps::BitsImpl<BC>: bitmaps::Bits,
+{
+    storage: &'a ShadowStorageBase<TS, BS, BC, AP, PP, PT, PK, SS, CC, FH, CM, TB, BK>,
+}
+
+impl<'a, const TS: usize, const BS: usize, const BC: usize, AP, PP, PT, PK, SS, CC, FH, CM, TB, BK>
+    core::fmt::Debug for HostShadow<'a, TS, BS, BC, AP, PP, PT, PK, SS, CC, FH, CM, TB, BK>
+where
+    AP: AccessPolicy,
+    PP: PersistPolicy<PK>,
+    PT: PersistTrigger<PK>,
+    CC: Codec,
+    FH: AccessFaultHandler,
+    CM: CacheMaintenance,
+    TB: TableBackend<TS>,
+    BK: BackingStore,
+    bitmaps::BitsImpl<BC>: bitmaps::Bits,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("HostShadow").finish_non_exhaustive()
+    }
+}
+
+impl<'a, const TS: usize, const BS: usize, const BC: usize, AP, PP, PT, PK, SS, CC, FH, CM, TB, BK>
+    HostShadow<'a, TS, BS, BC, AP, PP, PT, PK, SS, CC, FH, CM, TB, BK>
+where
+    AP: AccessPolicy,
+    PP: PersistPolicy<PK>,
+    PT: PersistTrigger<PK>,
+    CC: Codec,
+    FH: AccessFaultHandler,
+    CM: CacheMaintenance,
+    TB: TableBackend<TS>,
+    BK: BackingStore,
+    bitmaps::BitsImpl<BC>: bitmaps::Bits,
+{
+    pub(crate) fn new(
+        storage: &'a ShadowStorageBase<TS, BS, BC, AP, PP, PT, PK, SS, CC, FH, CM, TB, BK>,
+    ) -> Self {
+        Self { storage }
+    }
+
+    /// Finishes a DMA transfer started by
+    /// [`HostView::with_dma_region`](crate::shadow::HostView::with_dma_region),
+    /// releasing its pin and updating dirty/persist state: marks it dirty
+    /// and triggers persistence as configured for
+    /// [`DmaDirection::DeviceToMemory`], exactly as a host write would, or
+    /// leaves it clean for [`DmaDirection::MemoryToDevice`].
+    ///
+    /// Synchronized with any `KernelShadow` access to the same storage
+    /// when the `sync` feature is enabled, the same as
+    /// [`Self::with_view`] on the unstaged handle.
+    #[cfg(feature = "sync")]
+    pub fn complete_dma(&self, region: DmaRegion) -> Result<(), ShadowError> {
+        critical_section::with(|_| unsafe { self.complete_dma_unchecked(region) })
+    }
+
+    /// Finishes a DMA transfer started by
+    /// [`HostView::with_dma_region`](crate::shadow::HostView::with_dma_region).
+    /// See [`Self::complete_dma`].
+    #[cfg(not(feature = "sync"))]
+    pub fn complete_dma(&self, region: DmaRegion) -> Result<(), ShadowError> {
+        unsafe { self.complete_dma_unchecked(region) }
+    }
+
+    /// # Safety
+    /// Same requirement as [`Self::with_view_unchecked`] on the unstaged
+    /// handle: the caller must ensure no other code accesses the
+    /// ShadowStorage concurrently.
+    pub unsafe fn complete_dma_unchecked(&self, region: DmaRegion) -> Result<(), ShadowError> {
+        let table = unsafe { &mut *self.storage.table.get() };
+        let addr = region.addr();
+        let len = region.len();
+        let dir = region.direction();
+
+        table.unpin_range(addr, len)?;
+
+        if dir == DmaDirection::DeviceToMemory {
+            table.mark_dirty(addr, len)?;
+            let persist_trigger = unsafe { &mut *self.storage.persist_trigger.get() };
+            let should_persist =
+                self.storage
+                    .persist_policy
+                    .push_persist_keys_for_range(addr, len, |key| persist_trigger.push_key(key));
+            if should_persist {
+                persist_trigger.request_persist();
+            }
+        }
+
+        #[cfg(feature = "async")]
+        self.storage.dirty_signal.signal(());
+
+        Ok(())
+    }
+}
+
+impl<'a, const TS: usize, const BS: usize, const BC: usize, AP, PP, PT, PK, CC, FH, CM, TB, BK>
+    HostShadow<'a, TS, BS, BC, AP, PP, PT, PK, NoStage, CC, FH, CM, TB, BK>
+where
+    AP: AccessPolicy,
+    PP: PersistPolicy<PK>,
+    PT: PersistTrigger<PK>,
+    CC: Codec,
+    FH: AccessFaultHandler,
+    CM: CacheMaintenance,
+    TB: TableBackend<TS>,
+    BK: BackingStore,
+    bitmaps::BitsImpl<BC>: bitmaps::Bits,
+{
+    /// Runs `f` against a [`HostView`], synchronized with any `KernelShadow`
+    /// access to the same storage when the `sync` feature is enabled.
+    ///
+    /// With `sync` on, this enters a `critical_section` for the duration of
+    /// the call, so an ISR-driven host write and a main-loop kernel scan
+    /// cannot tear the dirty bitmap. The callback `f` runs entirely inside
+    /// that critical section, so it must be short and non-blocking.
+    #[cfg(feature = "sync")]
+    pub fn with_view<R>(
+        &self,
+        f: impl FnOnce(&mut HostView<TS, BS, BC, AP, PP, PT, PK, TB>) -> R,
+    ) -> R {
+        critical_section::with(|_| unsafe { self.with_view_unchecked(f) })
+    }
+
+    /// Runs `f` against a [`HostView`].
+    ///
+    /// Without the `sync` feature, storage is assumed to be accessed from a
+    /// single execution context, so no critical section is taken.
+    #[cfg(not(feature = "sync"))]
+    pub fn with_view<R>(
+        &self,
+        f: impl FnOnce(&mut HostView<TS, BS, BC, AP, PP, PT, PK, TB>) -> R,
+    ) -> R {
+        unsafe { self.with_view_unchecked(f) }
+    }
+
+    /// # Safety
+    /// This function is unsafe because it requires exclusive access to the ShadowStorage.
+    /// You must ensure that no other code is accessing the ShadowStorage at the same time.
+    /// Generally, if your host is running with interrupts disabled, or you know the
+    /// kernel side cannot run concurrently, then it is safe to call this function.
+    ///
+    /// With the `async` feature enabled, this wakes every task parked in
+    /// [`KernelShadow::wait_dirty`](crate::shadow::KernelShadow::wait_dirty)
+    /// after `f` runs, whether or not `f` actually marked anything dirty —
+    /// the waiter re-checks its own range before resuming.
+    pub unsafe fn with_view_unchecked<R>(
+        &self,
+        f: impl FnOnce(&mut HostView<TS, BS, BC, AP, PP, PT, PK, TB>) -> R,
+    ) -> R {
+        let table = unsafe { &mut *self.storage.table.get() };
+        let persist_trigger = unsafe { &mut *self.storage.persist_trigger.get() };
+        let fault_handler = unsafe { &mut *self.storage.fault_handler.get() };
+        let mut view = HostView::new(
+            table,
+            &self.storage.access_policy,
+            &self.storage.persist_policy,
+            persist_trigger,
+            fault_handler,
+            &self.storage.backing_store,
+        );
+        let result = f(&mut view);
+        #[cfg(feature = "async")]
+        self.storage.dirty_signal.signal(());
+        result
+    }
+}
+
+impl<'a, const TS: usize, const BS: usize, const BC: usize, AP, PP, PT, PK, SB, CC, FH, CM, TB, BK>
+    HostShadow<'a, TS, BS, BC, AP, PP, PT, PK, WithStage<SB>, CC, FH, CM, TB, BK>
+where
+    AP: AccessPolicy,
+    PP: PersistPolicy<PK>,
+    PT: PersistTrigger<PK>,
+    CC: Codec,
+    FH: AccessFaultHandler,
+    CM: CacheMaintenance,
+    TB: TableBackend<TS>,
+    BK: BackingStore,
+    bitmaps::BitsImpl<BC>: bitmaps::Bits,
+    SB: StagingBuffer,
+{
+    /// Runs `f` against a [`HostViewStaged`], synchronized with any
+    /// `KernelShadow` access to the same storage when the `sync` feature is
+    /// enabled. See [`Self::with_view`] on the unstaged handle for the
+    /// short-callback invariant this relies on.
+    #[cfg(feature = "sync")]
+    pub fn with_view<R>(
+        &self,
+        f: impl FnOnce(&mut HostViewStaged<TS, BS, BC, AP, PP, PT, PK, SB, TB>) -> R,
+    ) -> R {
+        critical_section::with(|_| unsafe { self.with_view_unchecked(f) })
+    }
+
+    /// Runs `f` against a [`HostViewStaged`].
+    #[cfg(not(feature = "sync"))]
+    pub fn with_view<R>(
+        &self,
+        f: impl FnOnce(&mut HostViewStaged<TS, BS, BC, AP, PP, PT, PK, SB, TB>) -> R,
+    ) -> R {
+        unsafe { self.with_view_unchecked(f) }
+    }
+
+    /// # Safety
+    /// This function is unsafe for the same reasons as
+    /// [`Self::with_view_unchecked`] on the unstaged handle: the caller must
+    /// ensure no other code accesses the ShadowStorage concurrently.
+    pub unsafe fn with_view_unchecked<R>(
+        &self,
+        f: impl FnOnce(&mut HostViewStaged<TS, BS, BC, AP, PP, PT, PK, SB, TB>) -> R,
+    ) -> R {
+        let table = unsafe { &mut *self.storage.table.get() };
+        let persist_trigger = unsafe { &mut *self.storage.persist_trigger.get() };
+        let fault_handler = unsafe { &mut *self.storage.fault_handler.get() };
+        let cache = unsafe { &mut *self.storage.cache.get() };
+        let stage_state = unsafe { &mut *self.storage.stage_state.get() };
+        let base = HostView::new(
+            table,
+            &self.storage.access_policy,
+            &self.storage.persist_policy,
+            persist_trigger,
+            fault_handler,
+            &self.storage.backing_store,
+        );
+        let mut view = HostViewStaged::new(base, &mut stage_state.sb, cache);
+        let result = f(&mut view);
+        #[cfg(feature = "async")]
+        self.storage.dirty_signal.signal(());
+        result
+    }
+}
@@ -1,26 +1,61 @@
 #![allow(unsafe_code)]
 
 use crate::shadow::{
-    AccessPolicy, PersistTrigger, policy::PersistPolicy, storage::ShadowStorageBase,
-    view::KernelView,
+    backend::{DenseBackend, TableBackend},
+    backing::BackingStore,
+    cache::CacheMaintenance,
+    codec::Codec,
+    fault::AccessFaultHandler,
+    persist::{PersistBackend, Pollable},
+    policy::PersistPolicy,
+    storage::ShadowStorageBase,
+    view::{DmaDirection, DmaRegion, KernelView},
+    AccessPolicy, PersistTrigger, ShadowError,
 };
 
-pub struct KernelShadow<'a, const TS: usize, const BS: usize, const BC: usize, AP, PP, PT, PK, SS>
-where
+#[cfg(feature = "async")]
+use crate::shadow::persist::AsyncPersistBackend;
+
+pub struct KernelShadow<
+    'a,
+    const TS: usize,
+    const BS: usize,
+    const BC: usize,
+    AP,
+    PP,
+    PT,
+    PK,
+    SS,
+    CC,
+    FH,
+    CM,
+    TB = DenseBackend<TS>,
+    BK = crate::shadow::backing::NoBackingStore,
+> where
     AP: AccessPolicy,
     PP: PersistPolicy<PK>,
     PT: PersistTrigger<PK>,
+    CC: Codec,
+    FH: AccessFaultHandler,
+    CM: CacheMaintenance,
+    TB: TableBackend<TS>,
+    BK: BackingStore,
     bitmaps::BitsImpl<BC>: bitmaps::Bits,
 {
-    storage: &'a ShadowStorageBase<TS, BS, BC, AP, PP, PT, PK, SS>,
+    storage: &'a ShadowStorageBase<TS, BS, BC, AP, PP, PT, PK, SS, CC, FH, CM, TB, BK>,
 }
 
-impl<'a, const TS: usize, const BS: usize, const BC: usize, AP, PP, PT, PK, SS> core::fmt::Debug
-    for KernelShadow<'a, TS, BS, BC, AP, PP, PT, PK, SS>
+impl<'a, const TS: usize, const BS: usize, const BC: usize, AP, PP, PT, PK, SS, CC, FH, CM, TB, BK>
+    core::fmt::Debug for KernelShadow<'a, TS, BS, BC, AP, PP, PT, PK, SS, CC, FH, CM, TB, BK>
 where
     AP: AccessPolicy,
     PP: PersistPolicy<PK>,
     PT: PersistTrigger<PK>,
+    CC: Codec,
+    FH: AccessFaultHandler,
+    CM: CacheMaintenance,
+    TB: TableBackend<TS>,
+    BK: BackingStore,
     bitmaps::BitsImpl<BC>: bitmaps::Bits,
 {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
@@ -28,22 +63,46 @@ where
     }
 }
 
-impl<'a, const TS: usize, const BS: usize, const BC: usize, AP, PP, PT, PK, SS>
-    KernelShadow<'a, TS, BS, BC, AP, PP, PT, PK, SS>
+impl<'a, const TS: usize, const BS: usize, const BC: usize, AP, PP, PT, PK, SS, CC, FH, CM, TB, BK>
+    KernelShadow<'a, TS, BS, BC, AP, PP, PT, PK, SS, CC, FH, CM, TB, BK>
 where
     AP: AccessPolicy,
     PP: PersistPolicy<PK>,
     PT: PersistTrigger<PK>,
+    CC: Codec,
+    FH: AccessFaultHandler,
+    CM: CacheMaintenance,
+    TB: TableBackend<TS>,
+    BK: BackingStore,
     bitmaps::BitsImpl<BC>: bitmaps::Bits,
 {
-    pub(crate) fn new(storage: &'a ShadowStorageBase<TS, BS, BC, AP, PP, PT, PK, SS>) -> Self {
+    pub(crate) fn new(
+        storage: &'a ShadowStorageBase<TS, BS, BC, AP, PP, PT, PK, SS, CC, FH, CM, TB, BK>,
+    ) -> Self {
         Self { storage }
     }
 
-    pub fn with_view<R>(&self, f: impl FnOnce(&mut KernelView<TS, BS, BC>) -> R) -> R {
+    /// Runs `f` against a [`KernelView`], synchronized with any `HostShadow`
+    /// access to the same storage when the `sync` feature is enabled.
+    ///
+    /// With `sync` on, this enters a `critical_section` for the duration of
+    /// the call, so a main-loop dirty scan and an ISR-driven host write
+    /// cannot tear the dirty bitmap. The callback `f` runs entirely inside
+    /// that critical section, so it must be short and non-blocking.
+    #[cfg(feature = "sync")]
+    pub fn with_view<R>(&self, f: impl FnOnce(&mut KernelView<TS, BS, BC, TB>) -> R) -> R {
         critical_section::with(|_| unsafe { self.with_view_unchecked(f) })
     }
 
+    /// Runs `f` against a [`KernelView`].
+    ///
+    /// Without the `sync` feature, storage is assumed to be accessed from a
+    /// single execution context, so no critical section is taken.
+    #[cfg(not(feature = "sync"))]
+    pub fn with_view<R>(&self, f: impl FnOnce(&mut KernelView<TS, BS, BC, TB>) -> R) -> R {
+        unsafe { self.with_view_unchecked(f) }
+    }
+
     /// # Safety
     /// This function is unsafe because it requires exclusive access to the ShadowStorage.
     /// You must ensure that no other code is accessing the ShadowStorage at the same time.
@@ -51,10 +110,328 @@ where
     /// then it is safe to call this function.
     pub unsafe fn with_view_unchecked<R>(
         &self,
-        f: impl FnOnce(&mut KernelView<TS, BS, BC>) -> R,
+        f: impl FnOnce(&mut KernelView<TS, BS, BC, TB>) -> R,
     ) -> R {
         let table = unsafe { &mut *self.storage.table.get() };
         let mut view = KernelView::new(table);
         f(&mut view)
     }
+
+    /// Drives any deferred persistence the configured [`PersistTrigger`] is
+    /// coalescing, e.g. a
+    /// [`CoalescingPersistTrigger`](crate::shadow::persist::CoalescingPersistTrigger).
+    /// Call periodically (e.g. from a timer ISR) with the current tick.
+    ///
+    /// Synchronized with any `HostShadow` access to the same storage when
+    /// the `sync` feature is enabled, the same as [`Self::with_view`].
+    #[cfg(feature = "sync")]
+    pub fn poll_persist(&self, now: u32)
+    where
+        PT: Pollable,
+    {
+        critical_section::with(|_| unsafe { self.poll_persist_unchecked(now) })
+    }
+
+    /// Drives any deferred persistence the configured [`PersistTrigger`] is
+    /// coalescing. See [`Self::poll_persist`].
+    #[cfg(not(feature = "sync"))]
+    pub fn poll_persist(&self, now: u32)
+    where
+        PT: Pollable,
+    {
+        unsafe { self.poll_persist_unchecked(now) }
+    }
+
+    /// # Safety
+    /// Same requirement as [`Self::with_view_unchecked`]: the caller must
+    /// ensure no other code accesses the ShadowStorage concurrently.
+    pub unsafe fn poll_persist_unchecked(&self, now: u32)
+    where
+        PT: Pollable,
+    {
+        let persist_trigger = unsafe { &mut *self.storage.persist_trigger.get() };
+        persist_trigger.poll(now);
+    }
+
+    /// Leases every dirty block, maps it to persist keys via the configured
+    /// [`PersistPolicy`], and commits its bytes through `backend`.
+    ///
+    /// Before reading each leased block's bytes, this calls the configured
+    /// [`CacheMaintenance::clean_range`] over it, so a block a host write
+    /// just landed on is flushed from any CPU data cache before `backend`
+    /// (typically a DMA-driven persist path) reads it from memory.
+    ///
+    /// A block stays dirty if `backend.persist` fails for any key it maps
+    /// to, or if a host write lands on the block while it's leased (the
+    /// same generation check as [`KernelView::complete_lease`]), so the
+    /// next call retries it. Returns the first error encountered, after
+    /// attempting every dirty block.
+    ///
+    /// Synchronized with any `HostShadow` access to the same storage when
+    /// the `sync` feature is enabled, the same as [`Self::with_view`].
+    #[cfg(feature = "sync")]
+    pub fn flush_dirty<PB>(&self, backend: &mut PB) -> Result<(), ShadowError>
+    where
+        PB: PersistBackend<PK>,
+    {
+        critical_section::with(|_| unsafe { self.flush_dirty_unchecked(backend) })
+    }
+
+    /// Leases every dirty block, maps it to persist keys via the configured
+    /// [`PersistPolicy`], and commits its bytes through `backend`. See
+    /// [`Self::flush_dirty`].
+    #[cfg(not(feature = "sync"))]
+    pub fn flush_dirty<PB>(&self, backend: &mut PB) -> Result<(), ShadowError>
+    where
+        PB: PersistBackend<PK>,
+    {
+        unsafe { self.flush_dirty_unchecked(backend) }
+    }
+
+    /// # Safety
+    /// Same requirement as [`Self::with_view_unchecked`]: the caller must
+    /// ensure no other code accesses the ShadowStorage concurrently.
+    pub unsafe fn flush_dirty_unchecked<PB>(&self, backend: &mut PB) -> Result<(), ShadowError>
+    where
+        PB: PersistBackend<PK>,
+    {
+        let table = unsafe { &mut *self.storage.table.get() };
+        let cache = unsafe { &mut *self.storage.cache.get() };
+        let mut completions: [(u16, u32, bool); BC] = [(0, 0, false); BC];
+        let mut count = 0;
+        let mut first_err = None;
+
+        table.lease_dirty_blocks(|lease| {
+            let addr = lease.addr();
+            let generation = lease.generation();
+            cache.clean_range(addr, BS);
+            let mut buf = [0u8; BS];
+            lease.data().copy_to_slice(&mut buf);
+
+            let mut block_err = None;
+            self.storage
+                .persist_policy
+                .push_persist_keys_for_range(addr, BS, |key| {
+                    if block_err.is_none() {
+                        if let Err(err) = backend.persist(key, addr, &buf) {
+                            block_err = Some(err);
+                        }
+                    }
+                });
+
+            let ok = block_err.is_none();
+            if first_err.is_none() {
+                first_err = block_err;
+            }
+            if count < completions.len() {
+                completions[count] = (addr, generation, ok);
+                count += 1;
+            }
+        });
+
+        for &(addr, generation, ok) in &completions[..count] {
+            let _ = table.complete_lease(addr, generation, ok);
+        }
+
+        match first_err {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+
+    /// Leases every dirty block, maps it to persist keys via the configured
+    /// [`PersistPolicy`], and commits its bytes through `backend`'s async
+    /// [`AsyncPersistBackend::persist`] — for backends (e.g. NOR flash
+    /// erase/write on real hardware) whose I/O takes too long to run inside
+    /// a `critical_section`, unlike [`Self::flush_dirty`].
+    ///
+    /// Only collecting the leased blocks' bytes and completing their leases
+    /// runs under a `critical_section` (when the `sync` feature is
+    /// enabled) — brief, non-blocking steps, same as [`Self::with_view`].
+    /// Every `backend.persist` call is awaited entirely outside of it, so a
+    /// write-heavy ISR driving [`HostShadow::with_view`](crate::shadow::HostShadow::with_view)
+    /// is never stalled behind flash latency. Every key collected before
+    /// this call is guaranteed to have its `persist` call awaited by the
+    /// time the returned future resolves.
+    ///
+    /// `KC` bounds how many persist keys this call can collect across all
+    /// leased blocks in one pass; a key beyond that bound is dropped and
+    /// its block stays dirty, to be retried on the next call.
+    ///
+    /// Returns the first error encountered, after attempting every
+    /// collected key.
+    #[cfg(feature = "async")]
+    pub async fn flush_dirty_async<PB, const KC: usize>(
+        &self,
+        backend: &mut PB,
+    ) -> Result<(), ShadowError>
+    where
+        PB: AsyncPersistBackend<PK>,
+        PK: Copy,
+    {
+        let mut blocks: [(u16, u32, [u8; BS]); BC] = [(0, 0, [0u8; BS]); BC];
+        let mut block_count = 0;
+
+        #[cfg(feature = "sync")]
+        critical_section::with(|_| unsafe {
+            self.collect_dirty_blocks_unchecked(&mut blocks, &mut block_count)
+        });
+        #[cfg(not(feature = "sync"))]
+        unsafe {
+            self.collect_dirty_blocks_unchecked(&mut blocks, &mut block_count)
+        };
+
+        let mut block_ok = [true; BC];
+        let mut keys: [Option<(PK, usize)>; KC] = [None; KC];
+        let mut key_count = 0;
+        for (idx, &(addr, _, _)) in blocks[..block_count].iter().enumerate() {
+            self.storage
+                .persist_policy
+                .push_persist_keys_for_range(addr, BS, |key| {
+                    if key_count < KC {
+                        keys[key_count] = Some((key, idx));
+                        key_count += 1;
+                    } else {
+                        block_ok[idx] = false;
+                    }
+                });
+        }
+
+        let mut first_err = None;
+        for &(key, idx) in keys[..key_count].iter().flatten() {
+            let (addr, _, buf) = &blocks[idx];
+            if let Err(err) = backend.persist(key, *addr, buf).await {
+                block_ok[idx] = false;
+                if first_err.is_none() {
+                    first_err = Some(err);
+                }
+            }
+        }
+
+        #[cfg(feature = "sync")]
+        critical_section::with(|_| unsafe {
+            self.complete_leases_unchecked(&blocks, &block_ok, block_count)
+        });
+        #[cfg(not(feature = "sync"))]
+        unsafe {
+            self.complete_leases_unchecked(&blocks, &block_ok, block_count)
+        };
+
+        match first_err {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+
+    /// # Safety
+    /// Same requirement as [`Self::with_view_unchecked`]: the caller must
+    /// ensure no other code accesses the ShadowStorage concurrently.
+    #[cfg(feature = "async")]
+    unsafe fn collect_dirty_blocks_unchecked(
+        &self,
+        blocks: &mut [(u16, u32, [u8; BS]); BC],
+        block_count: &mut usize,
+    ) {
+        let table = unsafe { &mut *self.storage.table.get() };
+        let cache = unsafe { &mut *self.storage.cache.get() };
+        table.lease_dirty_blocks(|lease| {
+            if *block_count >= BC {
+                return;
+            }
+            let addr = lease.addr();
+            cache.clean_range(addr, BS);
+            let mut buf = [0u8; BS];
+            lease.data().copy_to_slice(&mut buf);
+            blocks[*block_count] = (addr, lease.generation(), buf);
+            *block_count += 1;
+        });
+    }
+
+    /// # Safety
+    /// Same requirement as [`Self::collect_dirty_blocks_unchecked`].
+    #[cfg(feature = "async")]
+    unsafe fn complete_leases_unchecked(
+        &self,
+        blocks: &[(u16, u32, [u8; BS]); BC],
+        block_ok: &[bool; BC],
+        block_count: usize,
+    ) {
+        let table = unsafe { &mut *self.storage.table.get() };
+        for (idx, &(addr, generation, _)) in blocks[..block_count].iter().enumerate() {
+            let _ = table.complete_lease(addr, generation, block_ok[idx]);
+        }
+    }
+
+    /// Finishes a DMA transfer started by [`KernelView::with_dma_region`],
+    /// releasing its pin and updating its dirty state: marks it dirty for
+    /// [`DmaDirection::DeviceToMemory`], re-running the configured
+    /// [`PersistPolicy`] exactly as a host write would, or clears it for
+    /// [`DmaDirection::MemoryToDevice`].
+    ///
+    /// Synchronized with any `HostShadow` access to the same storage when
+    /// the `sync` feature is enabled, the same as [`Self::with_view`].
+    #[cfg(feature = "sync")]
+    pub fn complete_dma(&self, region: DmaRegion) -> Result<(), ShadowError> {
+        critical_section::with(|_| unsafe { self.complete_dma_unchecked(region) })
+    }
+
+    /// Finishes a DMA transfer started by [`KernelView::with_dma_region`].
+    /// See [`Self::complete_dma`].
+    #[cfg(not(feature = "sync"))]
+    pub fn complete_dma(&self, region: DmaRegion) -> Result<(), ShadowError> {
+        unsafe { self.complete_dma_unchecked(region) }
+    }
+
+    /// # Safety
+    /// Same requirement as [`Self::with_view_unchecked`]: the caller must
+    /// ensure no other code accesses the ShadowStorage concurrently.
+    pub unsafe fn complete_dma_unchecked(&self, region: DmaRegion) -> Result<(), ShadowError> {
+        let table = unsafe { &mut *self.storage.table.get() };
+        table.unpin_range(region.addr(), region.len())?;
+
+        match region.direction() {
+            DmaDirection::DeviceToMemory => {
+                table.mark_dirty(region.addr(), region.len())?;
+
+                let persist_trigger = unsafe { &mut *self.storage.persist_trigger.get() };
+                let should_persist = self.storage.persist_policy.push_persist_keys_for_range(
+                    region.addr(),
+                    region.len(),
+                    |key| persist_trigger.push_key(key),
+                );
+                if should_persist {
+                    persist_trigger.request_persist();
+                }
+            }
+            DmaDirection::MemoryToDevice => {
+                table.clear_dirty(region.addr(), region.len())?;
+            }
+        }
+
+        #[cfg(feature = "async")]
+        self.storage.dirty_signal.signal(());
+
+        Ok(())
+    }
+
+    /// Waits until any block overlapping `addr..addr+len` is dirty,
+    /// following embassy's waker-driven model instead of busy-polling
+    /// [`KernelView::is_dirty`].
+    ///
+    /// Returns immediately if the range is already dirty. Otherwise parks
+    /// on the storage's dirty signal until a host write runs through
+    /// [`HostShadow::with_view`](crate::shadow::HostShadow::with_view) or a
+    /// [`DmaDirection::DeviceToMemory`] transfer completes through
+    /// [`Self::complete_dma`], re-checking the range each time it wakes,
+    /// since an unrelated write elsewhere in the table also wakes this
+    /// signal.
+    #[cfg(feature = "async")]
+    pub async fn wait_dirty(&self, addr: u16, len: usize) -> Result<(), ShadowError> {
+        loop {
+            if self.with_view(|view| view.is_dirty(addr, len))? {
+                return Ok(());
+            }
+            self.storage.dirty_signal.wait().await;
+        }
+    }
 }
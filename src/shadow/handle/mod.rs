@@ -0,0 +1,5 @@
+mod host;
+mod kernel;
+
+pub use host::HostShadow;
+pub use kernel::KernelShadow;
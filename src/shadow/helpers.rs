@@ -82,6 +82,73 @@ pub fn range_span<const TS: usize>(addr: u16, len: usize) -> Result<(usize, usiz
     Ok((offset, end))
 }
 
+/// Expands `addr..addr+len` out to `LINE`-byte cache-line granularity.
+///
+/// Returns `(line_addr, line_len)` such that `line_addr` is `addr` rounded
+/// down to a multiple of `LINE` and `line_addr + line_len` is `addr + len`
+/// rounded up to a multiple of `LINE`. Partial-line cache clean/invalidate
+/// is unsafe — it can discard or miss bytes a neighbor within the same
+/// line is using — so [`CacheMaintenance`](crate::shadow::CacheMaintenance)
+/// implementations should round every range through this before acting on
+/// it.
+///
+/// # Type Parameters
+/// * `LINE` - Cache line size in bytes
+///
+/// # Example
+/// ```
+/// use embedded_shadow::shadow::helpers::round_to_cache_lines;
+///
+/// // 32-byte cache lines: addr 40, len 10 spans bytes 40-49, which sits
+/// // entirely within the line starting at 32.
+/// assert_eq!(round_to_cache_lines::<32>(40, 10), (32, 32));
+///
+/// // A range straddling two lines expands to cover both in full.
+/// assert_eq!(round_to_cache_lines::<32>(20, 20), (0, 64));
+/// ```
+pub fn round_to_cache_lines<const LINE: usize>(addr: u16, len: usize) -> (u16, usize) {
+    let start = addr as usize;
+    let end = start + len;
+
+    let line_start = (start / LINE) * LINE;
+    let line_end = end.div_ceil(LINE) * LINE;
+
+    (line_start as u16, line_end - line_start)
+}
+
+/// Computes a CRC-32 (IEEE 802.3, polynomial `0xEDB8_8320`) checksum over a
+/// byte iterator.
+///
+/// Shared by the on-flash/on-disk frame formats (journal, log, staged patch
+/// buffer, and dirty-delta table records) so they all agree on one checksum
+/// implementation instead of drifting independently.
+pub(crate) fn crc32(bytes: impl Iterator<Item = u8>) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+#[test]
+fn round_to_cache_lines_rounds_outward_to_line_boundaries() {
+    // Entirely within one line.
+    assert_eq!(round_to_cache_lines::<32>(40, 10), (32, 32));
+
+    // Straddles two lines.
+    assert_eq!(round_to_cache_lines::<32>(20, 20), (0, 64));
+
+    // Already line-aligned on both ends.
+    assert_eq!(round_to_cache_lines::<16>(16, 16), (16, 16));
+
+    // Single byte at a line boundary still rounds to the full line.
+    assert_eq!(round_to_cache_lines::<16>(15, 1), (0, 16));
+}
+
 #[test]
 fn block_span_edge_cases() {
     // Zero length
@@ -0,0 +1,355 @@
+//! Log-structured, wear-leveling [`PersistBackend`] over a ring of NOR flash
+//! sectors.
+//!
+//! Unlike [`NorFlashPersistBackend`](crate::shadow::persist::NorFlashPersistBackend),
+//! which overwrites a fixed region per key (and so erases the same sector
+//! every time that key changes), [`JournaledPersistBackend`] always appends a
+//! new record to the next free offset in its active sector. This spreads
+//! erase cycles evenly across `SECTOR_COUNT` sectors and survives power loss
+//! mid-write: a half-written record is simply ignored on the next
+//! [`JournaledPersistBackend::restore`].
+
+#[cfg(feature = "norflash")]
+use crate::shadow::{helpers::crc32, persist::PersistBackend, ShadowError};
+
+/// Marks the start of a valid journal record. An erased flash byte (`0xFF`)
+/// or a leftover zero from a previous record never matches this, so a torn
+/// write is detected at the very first byte of its header.
+#[cfg(feature = "norflash")]
+const MAGIC: u8 = 0xA5;
+
+/// `magic(1) + key_id(2) + addr(2) + seq(4) + len(2)`.
+#[cfg(feature = "norflash")]
+const HEADER_LEN: usize = 1 + 2 + 2 + 4 + 2;
+
+#[cfg(feature = "norflash")]
+const CRC_LEN: usize = 4;
+
+/// Converts a [`PersistBackend`] key to/from the `u16` id stored in a
+/// journal record.
+#[cfg(feature = "norflash")]
+pub trait JournalKey: Copy {
+    /// Encodes `self` as the record's `key_id`.
+    fn to_id(self) -> u16;
+    /// Decodes a record's `key_id` back into a key.
+    fn from_id(id: u16) -> Self;
+}
+
+#[cfg(feature = "norflash")]
+impl JournalKey for u16 {
+    fn to_id(self) -> u16 {
+        self
+    }
+
+    fn from_id(id: u16) -> Self {
+        id
+    }
+}
+
+/// The most recently written, still-valid record seen for one key during a
+/// scan, along with a copy of its payload (so compaction can write it
+/// forward after the sector it lived in has been erased).
+#[cfg(feature = "norflash")]
+#[derive(Clone, Copy)]
+struct LatestRecord<const PAYLOAD_CAP: usize> {
+    key_id: u16,
+    addr: u16,
+    seq: u32,
+    len: u16,
+    payload: [u8; PAYLOAD_CAP],
+}
+
+/// [`PersistBackend`] that appends CRC-protected, sequence-numbered records
+/// to a ring of `SECTOR_COUNT` NOR flash sectors instead of overwriting a
+/// fixed region per key.
+///
+/// # Const Generics
+/// - `SECTOR_SIZE`: erase granularity of the underlying flash, in bytes.
+/// - `SECTOR_COUNT`: number of sectors in the ring. Must be at least 2 so
+///   there is always a destination sector to compact into.
+/// - `PAYLOAD_CAP`: largest payload a single record can hold (e.g. the
+///   storage's block size).
+/// - `MAX_KEYS`: largest number of distinct persist keys ever seen across
+///   the whole journal. [`Self::restore`] returns
+///   [`ShadowError::BackendFull`] if the journal holds more keys than that;
+///   [`Self::persist`] only counts keys when a full sector forces a
+///   compaction, and returns the same error there.
+#[cfg(feature = "norflash")]
+pub struct JournaledPersistBackend<
+    NF,
+    const SECTOR_SIZE: usize,
+    const SECTOR_COUNT: usize,
+    const PAYLOAD_CAP: usize,
+    const MAX_KEYS: usize,
+> {
+    flash: NF,
+    base_addr: u32,
+    active_sector: usize,
+    write_offset: u32,
+    next_seq: u32,
+}
+
+#[cfg(feature = "norflash")]
+impl<
+        NF,
+        const SECTOR_SIZE: usize,
+        const SECTOR_COUNT: usize,
+        const PAYLOAD_CAP: usize,
+        const MAX_KEYS: usize,
+    > JournaledPersistBackend<NF, SECTOR_SIZE, SECTOR_COUNT, PAYLOAD_CAP, MAX_KEYS>
+where
+    NF: embedded_storage::nor_flash::NorFlash,
+{
+    /// Wraps `flash`, placing the sector ring at `base_addr` and onward
+    /// within it. Call [`Self::restore`] before the first [`Self::persist`]
+    /// to recover any existing journal and rebuild the write cursor;
+    /// otherwise the journal behaves as if starting from a blank flash.
+    pub fn new(flash: NF, base_addr: u32) -> Self {
+        Self {
+            flash,
+            base_addr,
+            active_sector: 0,
+            write_offset: 0,
+            next_seq: 1,
+        }
+    }
+
+    fn sector_addr(&self, sector: usize) -> u32 {
+        self.base_addr + (sector * SECTOR_SIZE) as u32
+    }
+
+    /// Scans every sector for valid records, keeping the highest-`seq`
+    /// record per key, and rebuilds `active_sector`/`write_offset`/
+    /// `next_seq` from the globally freshest record found. A bad CRC or a
+    /// header/length that would run past the end of the sector ends that
+    /// sector's scan right there — the only way such a record can exist is
+    /// a write that was interrupted mid-append, which always happens at the
+    /// current tail, so anything after it (stale, pre-erase garbage) is
+    /// never mistaken for live data.
+    fn scan_latest(
+        &mut self,
+    ) -> Result<([Option<LatestRecord<PAYLOAD_CAP>>; MAX_KEYS], usize), ShadowError> {
+        let mut winners: [Option<LatestRecord<PAYLOAD_CAP>>; MAX_KEYS] = [None; MAX_KEYS];
+        let mut winner_count = 0;
+        let mut best: Option<(usize, u32, u32)> = None;
+
+        for sector in 0..SECTOR_COUNT {
+            let sector_addr = self.sector_addr(sector);
+            let mut offset = 0u32;
+
+            while (offset as usize) + HEADER_LEN + CRC_LEN <= SECTOR_SIZE {
+                let mut header = [0u8; HEADER_LEN];
+                self.flash
+                    .read(sector_addr + offset, &mut header)
+                    .map_err(|_| ShadowError::PersistFailed)?;
+
+                if header[0] != MAGIC {
+                    break;
+                }
+
+                let key_id = u16::from_le_bytes([header[1], header[2]]);
+                let addr = u16::from_le_bytes([header[3], header[4]]);
+                let seq = u32::from_le_bytes([header[5], header[6], header[7], header[8]]);
+                let len = u16::from_le_bytes([header[9], header[10]]) as usize;
+
+                if len > PAYLOAD_CAP || (offset as usize) + HEADER_LEN + len + CRC_LEN > SECTOR_SIZE
+                {
+                    break;
+                }
+
+                let mut payload = [0u8; PAYLOAD_CAP];
+                self.flash
+                    .read(
+                        sector_addr + offset + HEADER_LEN as u32,
+                        &mut payload[..len],
+                    )
+                    .map_err(|_| ShadowError::PersistFailed)?;
+
+                let mut stored_crc = [0u8; CRC_LEN];
+                self.flash
+                    .read(
+                        sector_addr + offset + (HEADER_LEN + len) as u32,
+                        &mut stored_crc,
+                    )
+                    .map_err(|_| ShadowError::PersistFailed)?;
+                let stored_crc = u32::from_le_bytes(stored_crc);
+
+                let computed = crc32(
+                    header[1..]
+                        .iter()
+                        .copied()
+                        .chain(payload[..len].iter().copied()),
+                );
+                if computed != stored_crc {
+                    break;
+                }
+
+                let record_len = HEADER_LEN + len + CRC_LEN;
+
+                let slot = winners
+                    .iter()
+                    .position(|w| matches!(w, Some(w) if w.key_id == key_id));
+                let slot = match slot {
+                    Some(slot) => slot,
+                    None => {
+                        if winner_count >= MAX_KEYS {
+                            return Err(ShadowError::BackendFull);
+                        }
+                        let slot = winner_count;
+                        winner_count += 1;
+                        slot
+                    }
+                };
+
+                let is_newer = winners[slot].map_or(true, |w| seq > w.seq);
+                if is_newer {
+                    winners[slot] = Some(LatestRecord {
+                        key_id,
+                        addr,
+                        seq,
+                        len: len as u16,
+                        payload,
+                    });
+                }
+
+                if best.map_or(true, |(_, _, best_seq)| seq > best_seq) {
+                    best = Some((sector, offset + record_len as u32, seq));
+                }
+
+                offset += record_len as u32;
+            }
+        }
+
+        if let Some((sector, tail, seq)) = best {
+            self.active_sector = sector;
+            self.write_offset = tail;
+            self.next_seq = seq.wrapping_add(1);
+        }
+
+        Ok((winners, winner_count))
+    }
+
+    /// Recovers the journal written by a previous run.
+    ///
+    /// Scans every sector, validates each record's CRC, and for every key
+    /// keeps only the payload with the highest valid `seq`. That payload is
+    /// handed to `load(key, addr, data)` so the caller can write it back
+    /// into the shadow table (typically from inside
+    /// [`ShadowStorageBase::load_defaults`](crate::shadow::ShadowStorage::load_defaults),
+    /// since restoring shouldn't mark the region dirty). Also rebuilds the
+    /// write cursor, so the next [`Self::persist`] appends after the
+    /// journal's current tail rather than overwriting it.
+    ///
+    /// Call this once at startup, before any [`Self::persist`] call.
+    pub fn restore<PK, F>(&mut self, mut load: F) -> Result<(), ShadowError>
+    where
+        PK: JournalKey,
+        F: FnMut(PK, u16, &[u8]) -> Result<(), ShadowError>,
+    {
+        let (winners, winner_count) = self.scan_latest()?;
+        for winner in winners.into_iter().take(winner_count).flatten() {
+            load(
+                PK::from_id(winner.key_id),
+                winner.addr,
+                &winner.payload[..winner.len as usize],
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Copies the latest record for every key forward into a freshly
+    /// erased sector, then makes that sector active, spreading erase
+    /// cycles evenly across the ring instead of wearing one sector.
+    fn compact_forward(&mut self) -> Result<(), ShadowError> {
+        let (winners, winner_count) = self.scan_latest()?;
+
+        let next_sector = (self.active_sector + 1) % SECTOR_COUNT;
+        let next_addr = self.sector_addr(next_sector);
+        self.flash
+            .erase(next_addr, next_addr + SECTOR_SIZE as u32)
+            .map_err(|_| ShadowError::PersistFailed)?;
+
+        self.active_sector = next_sector;
+        self.write_offset = 0;
+
+        for winner in winners.into_iter().take(winner_count).flatten() {
+            self.write_record(
+                winner.key_id,
+                winner.addr,
+                winner.seq,
+                &winner.payload[..winner.len as usize],
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Appends one record at the current write cursor. Caller guarantees it
+    /// fits in what's left of the active sector.
+    fn write_record(
+        &mut self,
+        key_id: u16,
+        addr: u16,
+        seq: u32,
+        data: &[u8],
+    ) -> Result<(), ShadowError> {
+        let mut header = [0u8; HEADER_LEN];
+        header[0] = MAGIC;
+        header[1..3].copy_from_slice(&key_id.to_le_bytes());
+        header[3..5].copy_from_slice(&addr.to_le_bytes());
+        header[5..9].copy_from_slice(&seq.to_le_bytes());
+        header[9..11].copy_from_slice(&(data.len() as u16).to_le_bytes());
+
+        let crc = crc32(header[1..].iter().copied().chain(data.iter().copied()));
+
+        let record_addr = self.sector_addr(self.active_sector) + self.write_offset;
+        self.flash
+            .write(record_addr, &header)
+            .map_err(|_| ShadowError::PersistFailed)?;
+        self.flash
+            .write(record_addr + HEADER_LEN as u32, data)
+            .map_err(|_| ShadowError::PersistFailed)?;
+        self.flash
+            .write(
+                record_addr + (HEADER_LEN + data.len()) as u32,
+                &crc.to_le_bytes(),
+            )
+            .map_err(|_| ShadowError::PersistFailed)?;
+
+        self.write_offset += (HEADER_LEN + data.len() + CRC_LEN) as u32;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "norflash")]
+impl<
+        PK,
+        NF,
+        const SECTOR_SIZE: usize,
+        const SECTOR_COUNT: usize,
+        const PAYLOAD_CAP: usize,
+        const MAX_KEYS: usize,
+    > PersistBackend<PK>
+    for JournaledPersistBackend<NF, SECTOR_SIZE, SECTOR_COUNT, PAYLOAD_CAP, MAX_KEYS>
+where
+    PK: JournalKey,
+    NF: embedded_storage::nor_flash::NorFlash,
+{
+    fn persist(&mut self, key: PK, addr: u16, data: &[u8]) -> Result<(), ShadowError> {
+        if data.len() > PAYLOAD_CAP {
+            return Err(ShadowError::OutOfBounds);
+        }
+
+        let record_len = HEADER_LEN + data.len() + CRC_LEN;
+        if self.write_offset as usize + record_len > SECTOR_SIZE {
+            self.compact_forward()?;
+        }
+        if self.write_offset as usize + record_len > SECTOR_SIZE {
+            return Err(ShadowError::OutOfBounds);
+        }
+
+        let seq = self.next_seq;
+        self.next_seq = self.next_seq.wrapping_add(1);
+        self.write_record(key.to_id(), addr, seq, data)
+    }
+}
@@ -0,0 +1,369 @@
+//! Append-only, CRC-protected frame log usable as a [`PersistBackend`] over
+//! any byte sink, regardless of backing medium.
+//!
+//! Unlike [`JournaledPersistBackend`](crate::shadow::journal::JournaledPersistBackend),
+//! which manages a ring of NOR flash sectors and per-key compaction itself,
+//! [`LogPersistBackend`] only tracks a linear write cursor and hands each
+//! encoded frame to a caller-supplied sink — the caller owns whatever
+//! storage medium (EEPROM, a flash page writer, a host file) actually backs
+//! it, and there is exactly one log, never compacted, so sequential replay
+//! alone gives last-writer-wins with no per-key dedup bookkeeping needed.
+
+use crate::shadow::{
+    helpers::crc32,
+    persist::PersistBackend,
+    slice::{ROSlice, WOSlice},
+    storage::WriteFn,
+    ShadowError,
+};
+
+/// Marks the start of a valid frame. Uninitialized storage (erased flash,
+/// zeroed EEPROM) never matches this, so a torn or missing frame is
+/// detected at the very first bytes of its header.
+const MAGIC: u16 = 0x5348;
+
+const VERSION: u8 = 1;
+
+/// `magic(2) + version(1) + key(2) + offset(2) + len(2)`.
+const HEADER_LEN: usize = 2 + 1 + 2 + 2 + 2;
+
+const CRC_LEN: usize = 4;
+
+/// Largest payload [`LogPersistBackend::persist`] can encode, regardless of
+/// the backend's `PAYLOAD_CAP`. Sizing the stack buffer off `PAYLOAD_CAP`
+/// itself would mean adding a generic parameter to an array length, which
+/// stable Rust doesn't support — so the buffer is sized off this fixed
+/// constant instead, and [`LogPersistBackend::new`] debug-asserts that
+/// `PAYLOAD_CAP` is not larger than it.
+const MAX_PERSIST_PAYLOAD: usize = 256;
+
+/// Converts a [`PersistBackend`] key to the `u16` id stored in a log frame.
+pub trait LogKey: Copy {
+    /// Encodes `self` as the frame's key id.
+    fn to_id(self) -> u16;
+}
+
+impl LogKey for u16 {
+    fn to_id(self) -> u16 {
+        self
+    }
+}
+
+/// A decoded log frame: the key id it was written under, the shadow
+/// address its payload starts at, and a borrowed view of the payload
+/// bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LogFrame<'a> {
+    pub key: u16,
+    pub offset: u16,
+    pub payload: &'a [u8],
+}
+
+/// Encodes one frame for `key`/`offset`/`payload` into `out`, returning the
+/// number of bytes written.
+///
+/// Frame layout: `[magic: u16 LE][version: u8][key: u16 LE][offset: u16 LE]
+/// [len: u16 LE][payload...][crc32: u32 LE]`, with the CRC computed over
+/// every byte preceding it. Exposed standalone, with its counterpart
+/// [`decode_frame`], so the format can be tested without a backing store.
+///
+/// Returns [`ShadowError::OutOfBounds`] if `payload` is too long to fit a
+/// `u16` length field or `out` is too small to hold the encoded frame.
+pub fn encode_frame(
+    key: u16,
+    offset: u16,
+    payload: &[u8],
+    out: &mut [u8],
+) -> Result<usize, ShadowError> {
+    if payload.len() > u16::MAX as usize {
+        return Err(ShadowError::OutOfBounds);
+    }
+
+    let frame_len = HEADER_LEN + payload.len() + CRC_LEN;
+    if out.len() < frame_len {
+        return Err(ShadowError::OutOfBounds);
+    }
+
+    let mut header = WOSlice::new(&mut out[..HEADER_LEN + payload.len()]);
+    header.write_u16_le_at(0, MAGIC);
+    header.write_u8_at(2, VERSION);
+    header.write_u16_le_at(3, key);
+    header.write_u16_le_at(5, offset);
+    header.write_u16_le_at(7, payload.len() as u16);
+    header.copy_from_slice_at(HEADER_LEN, payload);
+
+    let crc = crc32(out[..HEADER_LEN + payload.len()].iter().copied());
+    WOSlice::new(&mut out[HEADER_LEN + payload.len()..frame_len]).write_u32_le_at(0, crc);
+
+    Ok(frame_len)
+}
+
+/// Decodes one frame from the start of `buf`, returning the frame and the
+/// number of bytes it occupied.
+///
+/// Returns [`ShadowError::OutOfBounds`] if `buf` is too short to hold a
+/// header or the complete frame the header describes, and
+/// [`ShadowError::ChecksumMismatch`] if the magic, version, or CRC don't
+/// check out — the same outcome [`replay_log`] treats as "stop here", since
+/// a torn write at the log's tail looks identical to a validation failure.
+pub fn decode_frame(buf: &[u8]) -> Result<(LogFrame<'_>, usize), ShadowError> {
+    if buf.len() < HEADER_LEN {
+        return Err(ShadowError::OutOfBounds);
+    }
+
+    let header = ROSlice::new(&buf[..HEADER_LEN]);
+    let magic = header.read_u16_le_at(0);
+    let version = header.read_u8_at(2);
+    let key = header.read_u16_le_at(3);
+    let offset = header.read_u16_le_at(5);
+    let len = header.read_u16_le_at(7) as usize;
+
+    if magic != MAGIC || version != VERSION {
+        return Err(ShadowError::ChecksumMismatch);
+    }
+
+    let frame_len = HEADER_LEN + len + CRC_LEN;
+    if buf.len() < frame_len {
+        return Err(ShadowError::OutOfBounds);
+    }
+
+    let stored_crc = ROSlice::new(&buf[HEADER_LEN + len..frame_len]).read_u32_le_at(0);
+    let computed = crc32(buf[..HEADER_LEN + len].iter().copied());
+    if computed != stored_crc {
+        return Err(ShadowError::ChecksumMismatch);
+    }
+
+    Ok((
+        LogFrame {
+            key,
+            offset,
+            payload: &buf[HEADER_LEN..HEADER_LEN + len],
+        },
+        frame_len,
+    ))
+}
+
+/// [`PersistBackend`] that appends CRC-protected frames through a
+/// caller-supplied sink, tracking only a linear write cursor.
+///
+/// # Const Generics
+/// - `PAYLOAD_CAP`: largest payload a single frame can hold (e.g. the
+///   storage's block size), sized so [`Self::persist`] can encode into a
+///   stack buffer without allocating.
+pub struct LogPersistBackend<'a, const PAYLOAD_CAP: usize> {
+    sink: &'a mut WriteFn,
+    cursor: u16,
+}
+
+impl<'a, const PAYLOAD_CAP: usize> LogPersistBackend<'a, PAYLOAD_CAP> {
+    /// Wraps `sink`, appending frames starting at `cursor` — the offset one
+    /// past the end of whatever the log already holds on disk, or `0` for a
+    /// fresh log. `sink` receives `(offset, frame_bytes)` for every frame,
+    /// the same shape [`ShadowStorageBase::load_defaults`](crate::shadow::ShadowStorage::load_defaults)
+    /// uses for `(addr, data)`, reinterpreted here as a position in the log
+    /// rather than an address in the shadow table.
+    pub fn new(sink: &'a mut WriteFn, cursor: u16) -> Self {
+        debug_assert!(
+            PAYLOAD_CAP <= MAX_PERSIST_PAYLOAD,
+            "PAYLOAD_CAP exceeds the largest payload LogPersistBackend can encode",
+        );
+        Self { sink, cursor }
+    }
+
+    /// The offset one past the last byte appended so far.
+    pub fn cursor(&self) -> u16 {
+        self.cursor
+    }
+}
+
+impl<'a, PK, const PAYLOAD_CAP: usize> PersistBackend<PK> for LogPersistBackend<'a, PAYLOAD_CAP>
+where
+    PK: LogKey,
+{
+    fn persist(&mut self, key: PK, addr: u16, data: &[u8]) -> Result<(), ShadowError> {
+        if data.len() > PAYLOAD_CAP {
+            return Err(ShadowError::OutOfBounds);
+        }
+
+        let mut buf = [0u8; HEADER_LEN + MAX_PERSIST_PAYLOAD + CRC_LEN];
+        let frame_len = encode_frame(key.to_id(), addr, data, &mut buf)?;
+
+        (self.sink)(self.cursor, &buf[..frame_len])?;
+        self.cursor = self.cursor.wrapping_add(frame_len as u16);
+        Ok(())
+    }
+}
+
+/// Replays a log previously written by [`LogPersistBackend`], applying each
+/// valid frame's payload to `write` in order.
+///
+/// Frames are decoded sequentially from the start of `log`; since later
+/// frames for the same `(key, offset)` are applied after earlier ones,
+/// last-writer-wins falls out of the order alone with no per-key tracking.
+/// Decoding stops silently, without error, at the first frame that fails to
+/// validate — a torn write at the tail of the log from a reset mid-append —
+/// so everything written before it is still recovered. Call this once at
+/// startup, typically from inside
+/// [`ShadowStorageBase::load_defaults`](crate::shadow::ShadowStorage::load_defaults)
+/// so replay doesn't mark the shadow table dirty.
+pub fn replay_log(log: &[u8], write: &mut WriteFn) -> Result<(), ShadowError> {
+    let mut pos = 0usize;
+    while pos < log.len() {
+        let (frame, frame_len) = match decode_frame(&log[pos..]) {
+            Ok(decoded) => decoded,
+            Err(_) => break,
+        };
+        write(frame.offset, frame.payload)?;
+        pos += frame_len;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_then_decode_round_trips() {
+        let mut buf = [0u8; 32];
+        let len = encode_frame(7, 100, &[1, 2, 3, 4], &mut buf).unwrap();
+
+        let (frame, consumed) = decode_frame(&buf[..len]).unwrap();
+        assert_eq!(consumed, len);
+        assert_eq!(frame.key, 7);
+        assert_eq!(frame.offset, 100);
+        assert_eq!(frame.payload, &[1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn decode_rejects_short_buffer() {
+        let buf = [0u8; 4];
+        assert_eq!(decode_frame(&buf), Err(ShadowError::OutOfBounds));
+    }
+
+    #[test]
+    fn decode_rejects_bad_magic() {
+        let mut buf = [0u8; 32];
+        let len = encode_frame(7, 100, &[1, 2, 3, 4], &mut buf).unwrap();
+        buf[0] ^= 0xFF;
+
+        assert_eq!(
+            decode_frame(&buf[..len]),
+            Err(ShadowError::ChecksumMismatch)
+        );
+    }
+
+    #[test]
+    fn decode_rejects_corrupted_payload() {
+        let mut buf = [0u8; 32];
+        let len = encode_frame(7, 100, &[1, 2, 3, 4], &mut buf).unwrap();
+        buf[HEADER_LEN] ^= 0xFF;
+
+        assert_eq!(
+            decode_frame(&buf[..len]),
+            Err(ShadowError::ChecksumMismatch)
+        );
+    }
+
+    #[test]
+    fn encode_rejects_buffer_too_small() {
+        let mut buf = [0u8; 8];
+        assert_eq!(
+            encode_frame(7, 100, &[1, 2, 3, 4], &mut buf),
+            Err(ShadowError::OutOfBounds)
+        );
+    }
+
+    fn append_frame(log: &mut [u8; 128], pos: &mut usize, key: u16, offset: u16, payload: &[u8]) {
+        let len = encode_frame(key, offset, payload, &mut log[*pos..]).unwrap();
+        *pos += len;
+    }
+
+    #[test]
+    fn persist_appends_frames_through_the_sink() {
+        let mut log = [0u8; 128];
+        let mut log_len = 0usize;
+
+        {
+            let mut sink = |offset: u16, bytes: &[u8]| -> Result<(), ShadowError> {
+                let offset = offset as usize;
+                log[offset..offset + bytes.len()].copy_from_slice(bytes);
+                log_len = log_len.max(offset + bytes.len());
+                Ok(())
+            };
+            let mut backend: LogPersistBackend<'_, 8> = LogPersistBackend::new(&mut sink, 0);
+
+            PersistBackend::persist(&mut backend, 1u16, 10, &[1, 2]).unwrap();
+            PersistBackend::persist(&mut backend, 2u16, 20, &[3, 4, 5]).unwrap();
+            assert_eq!(backend.cursor() as usize, log_len);
+        }
+
+        let mut applied = [(0u16, [0u8; 8], 0usize); 2];
+        let mut applied_count = 0;
+        replay_log(&log[..log_len], &mut |addr, data| {
+            let mut buf = [0u8; 8];
+            buf[..data.len()].copy_from_slice(data);
+            applied[applied_count] = (addr, buf, data.len());
+            applied_count += 1;
+            Ok(())
+        })
+        .unwrap();
+
+        assert_eq!(applied_count, 2);
+        assert_eq!(
+            applied[0],
+            (
+                10,
+                {
+                    let mut buf = [0u8; 8];
+                    buf[..2].copy_from_slice(&[1, 2]);
+                    buf
+                },
+                2
+            )
+        );
+        assert_eq!(applied[1].0, 20);
+        assert_eq!(&applied[1].1[..applied[1].2], &[3, 4, 5]);
+    }
+
+    #[test]
+    fn replay_stops_silently_at_first_bad_frame() {
+        let mut log = [0u8; 128];
+        let mut pos = 0;
+        append_frame(&mut log, &mut pos, 1, 10, &[1, 2]);
+        let good_len = pos;
+        append_frame(&mut log, &mut pos, 2, 20, &[3, 4, 5]);
+        log[good_len] ^= 0xFF;
+
+        let mut applied = [0u16; 2];
+        let mut applied_count = 0;
+        replay_log(&log[..pos], &mut |addr, _data| {
+            applied[applied_count] = addr;
+            applied_count += 1;
+            Ok(())
+        })
+        .unwrap();
+
+        assert_eq!(&applied[..applied_count], [10]);
+    }
+
+    #[test]
+    fn replay_applies_last_writer_per_offset_in_order() {
+        let mut log = [0u8; 128];
+        let mut pos = 0;
+        append_frame(&mut log, &mut pos, 1, 10, &[1, 1]);
+        append_frame(&mut log, &mut pos, 1, 10, &[2, 2]);
+
+        let mut applied = [[0u8; 2]; 2];
+        let mut applied_count = 0;
+        replay_log(&log[..pos], &mut |_addr, data| {
+            applied[applied_count].copy_from_slice(data);
+            applied_count += 1;
+            Ok(())
+        })
+        .unwrap();
+
+        assert_eq!(applied_count, 2);
+        assert_eq!(applied[1], [2, 2]);
+    }
+}
@@ -1,38 +1,95 @@
+pub mod backend;
+pub mod backing;
 pub mod builder;
+pub mod cache;
+pub mod codec;
+pub mod dirty_codec;
 pub mod error;
+pub mod fault;
 pub mod handle;
 pub mod helpers;
+#[cfg(feature = "norflash")]
+pub mod journal;
+pub mod log;
+#[cfg(feature = "async")]
+pub(crate) mod notify;
 pub mod persist;
 pub mod policy;
+pub mod slice;
 pub mod staged;
 pub mod storage;
 pub(crate) mod table;
 pub mod types;
 pub mod view;
 
+pub use backend::{DenseBackend, SparseBackend, TableBackend};
+pub use backing::{BackingStore, NoBackingStore};
 pub use builder::ShadowStorageBuilder;
+pub use cache::{CacheMaintenance, NoCache};
+pub use codec::{Codec, NoCodec};
+pub use dirty_codec::DirtyCodec;
 pub use error::ShadowError;
+pub use fault::{AccessFaultHandler, NoFaultHandler};
 pub use handle::{HostShadow, KernelShadow};
-pub use persist::{NoPersist, PersistTrigger};
-pub use policy::{AccessPolicy, AllowAllPolicy, NoPersistPolicy, PersistPolicy};
-pub use staged::PatchStagingBuffer;
+#[cfg(feature = "norflash")]
+pub use journal::{JournalKey, JournaledPersistBackend};
+pub use log::{decode_frame, encode_frame, replay_log, LogFrame, LogKey, LogPersistBackend};
+#[cfg(feature = "async")]
+pub use persist::{AsyncPersistBackend, AsyncPersistTrigger, AsyncPersistTriggerAdapter};
+pub use persist::{
+    CoalescingPersistTrigger, CoalescingTrigger, NoPersist, PersistBackend, PersistTrigger,
+    Pollable, TickSource,
+};
+#[cfg(feature = "norflash")]
+pub use persist::{FixedFlashBase, KeyToFlash, NorFlashPersistBackend};
+pub use policy::{
+    AccessPolicy, AllowAllPolicy, NoPersistPolicy, Perm, PersistPolicy, RegionAccessPolicy,
+};
+pub use slice::{Endian, Field, FieldCursor, FieldPrimitive};
+pub use staged::{ConflictPolicy, PatchStagingBuffer, SpscStagingQueue};
 pub use storage::{ShadowStorage, WriteFn};
-pub use types::StagingBuffer;
-pub use view::{HostView, HostViewStaged, KernelView};
+pub use types::{DirtyLease, Savepoint, StagingBuffer, WriteResult};
+pub use view::{
+    DmaDirection, DmaRegion, DmaWindowGuard, HostView, HostViewStaged, KernelView, Transaction,
+};
 
 #[cfg(test)]
 mod test_support;
 
 pub mod prelude {
-    pub use crate::{
+    #[cfg(feature = "norflash")]
+    pub use crate::shadow::journal::{JournalKey, JournaledPersistBackend};
+    #[cfg(feature = "async")]
+    pub use crate::shadow::persist::{
+        AsyncPersistBackend, AsyncPersistTrigger, AsyncPersistTriggerAdapter,
+    };
+    #[cfg(feature = "norflash")]
+    pub use crate::shadow::persist::{FixedFlashBase, KeyToFlash, NorFlashPersistBackend};
+    pub use crate::shadow::{
+        backend::{DenseBackend, SparseBackend, TableBackend},
+        backing::{BackingStore, NoBackingStore},
         builder::ShadowStorageBuilder,
+        cache::{CacheMaintenance, NoCache},
+        codec::{Codec, NoCodec},
+        dirty_codec::DirtyCodec,
         error::ShadowError,
+        fault::{AccessFaultHandler, NoFaultHandler},
         handle::{HostShadow, KernelShadow},
-        persist::{NoPersist, PersistTrigger},
-        policy::{AccessPolicy, AllowAllPolicy, NoPersistPolicy, PersistPolicy},
-        staged::PatchStagingBuffer,
+        log::{decode_frame, encode_frame, replay_log, LogFrame, LogKey, LogPersistBackend},
+        persist::{
+            CoalescingPersistTrigger, CoalescingTrigger, NoPersist, PersistBackend, PersistTrigger,
+            Pollable, TickSource,
+        },
+        policy::{
+            AccessPolicy, AllowAllPolicy, NoPersistPolicy, Perm, PersistPolicy, RegionAccessPolicy,
+        },
+        slice::{Endian, Field, FieldCursor, FieldPrimitive},
+        staged::{ConflictPolicy, PatchStagingBuffer, SpscStagingQueue},
         storage::ShadowStorage,
-        types::StagingBuffer,
-        view::{HostView, HostViewStaged, KernelView},
+        types::{Savepoint, StagingBuffer},
+        view::{
+            DmaDirection, DmaRegion, DmaWindowGuard, HostView, HostViewStaged, KernelView,
+            Transaction,
+        },
     };
 }
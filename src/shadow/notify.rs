@@ -0,0 +1,19 @@
+//! Async dirty-region notification, gated behind the `async` feature.
+
+use embassy_sync::{blocking_mutex::raw::NoopRawMutex, signal::Signal};
+
+/// Wakes every outstanding [`KernelShadow::wait_dirty`](crate::shadow::KernelShadow::wait_dirty)
+/// call whenever a host write may have marked new blocks dirty.
+///
+/// A single [`Signal`] rather than a waiter list keyed by address range:
+/// every signal wakes every waiter, and `wait_dirty` re-checks its own range
+/// with [`KernelView::is_dirty`](crate::shadow::KernelView::is_dirty) before
+/// returning, so a spurious wake from an unrelated write just costs one
+/// extra dirty check instead of a missed one.
+///
+/// Uses [`NoopRawMutex`] rather than a `critical-section`-backed one: every
+/// access already runs inside `HostShadow`/`KernelShadow`'s own
+/// `critical_section` when the `sync` feature is enabled, the same manual
+/// synchronization discipline [`ShadowStorageBase`](crate::shadow::storage::ShadowStorageBase)'s
+/// `unsafe impl Sync` already relies on for its `UnsafeCell` fields.
+pub(crate) type DirtySignal = Signal<NoopRawMutex, ()>;
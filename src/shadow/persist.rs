@@ -1,9 +1,23 @@
+use crate::shadow::ShadowError;
+
 /// Receives persistence keys and triggers storage operations.
 pub trait PersistTrigger<PK> {
     /// Queues a key identifying data that needs to be persisted.
     fn push_key(&mut self, key: PK);
     /// Signals that queued keys should be persisted to storage.
     fn request_persist(&mut self);
+
+    /// Appends raw bytes to a write-ahead journal sink.
+    ///
+    /// Used by
+    /// [`HostViewStaged::commit_journaled`](crate::shadow::HostViewStaged::commit_journaled)
+    /// to make a whole staged patch set durable before it's applied to the
+    /// shadow table. Defaults to a no-op, so implementers that don't need
+    /// journaled commits can ignore it.
+    fn journal_append(&mut self, bytes: &[u8]) -> Result<(), ShadowError> {
+        let _ = bytes;
+        Ok(())
+    }
 }
 
 /// No-op trigger that discards all persistence requests.
@@ -14,3 +28,585 @@ impl<PK> PersistTrigger<PK> for NoPersist {
     fn push_key(&mut self, _key: PK) {}
     fn request_persist(&mut self) {}
 }
+
+/// Commits a persist key's current shadow bytes to non-volatile storage.
+///
+/// Driven by
+/// [`KernelShadow::flush_dirty`](crate::shadow::KernelShadow::flush_dirty),
+/// which leases each dirty block, maps it to persist keys via the
+/// configured [`PersistPolicy`](crate::shadow::PersistPolicy), and hands the
+/// block's bytes to [`Self::persist`] for every key produced.
+pub trait PersistBackend<PK> {
+    /// Writes `data` — the current bytes at `addr` — to storage under `key`.
+    fn persist(&mut self, key: PK, addr: u16, data: &[u8]) -> Result<(), ShadowError>;
+}
+
+/// Async counterpart of [`PersistBackend`], for storage whose I/O (e.g. NOR
+/// flash erase/write on real hardware) takes too long to run inside a
+/// `critical_section`.
+///
+/// Driven by
+/// [`KernelShadow::flush_dirty_async`](crate::shadow::KernelShadow::flush_dirty_async),
+/// which leases each dirty block, maps it to persist keys via the
+/// configured [`PersistPolicy`](crate::shadow::PersistPolicy), and awaits
+/// [`Self::persist`] for every key produced, entirely outside of any
+/// critical section.
+#[cfg(feature = "async")]
+pub trait AsyncPersistBackend<PK> {
+    /// Writes `data` — the current bytes at `addr` — to storage under `key`.
+    async fn persist(&mut self, key: PK, addr: u16, data: &[u8]) -> Result<(), ShadowError>;
+}
+
+/// Async counterpart of [`PersistTrigger`], for triggers that drive their
+/// own deferred persistence work rather than leaving it entirely to
+/// [`KernelShadow::flush_dirty_async`](crate::shadow::KernelShadow::flush_dirty_async).
+///
+/// `flush` resolves once every key queued via [`PersistTrigger::push_key`]
+/// before the call is durably persisted.
+#[cfg(feature = "async")]
+pub trait AsyncPersistTrigger<PK>: PersistTrigger<PK> {
+    /// Persists every key queued since the last flush.
+    async fn flush(&mut self);
+}
+
+/// Adapts an existing sync [`PersistTrigger`] into an [`AsyncPersistTrigger`],
+/// for code that's generic over the async trait but whose trigger doesn't
+/// own any I/O of its own — the actual storage work happens through
+/// [`KernelShadow::flush_dirty_async`](crate::shadow::KernelShadow::flush_dirty_async),
+/// so there's nothing queued here for [`AsyncPersistTrigger::flush`] to
+/// await; it only exists to satisfy the bound.
+#[cfg(feature = "async")]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct AsyncPersistTriggerAdapter<PT>(pub PT);
+
+#[cfg(feature = "async")]
+impl<PK, PT> PersistTrigger<PK> for AsyncPersistTriggerAdapter<PT>
+where
+    PT: PersistTrigger<PK>,
+{
+    fn push_key(&mut self, key: PK) {
+        self.0.push_key(key);
+    }
+
+    fn request_persist(&mut self) {
+        self.0.request_persist();
+    }
+
+    fn journal_append(&mut self, bytes: &[u8]) -> Result<(), ShadowError> {
+        self.0.journal_append(bytes)
+    }
+}
+
+#[cfg(feature = "async")]
+impl<PK, PT> AsyncPersistTrigger<PK> for AsyncPersistTriggerAdapter<PT>
+where
+    PT: PersistTrigger<PK>,
+{
+    async fn flush(&mut self) {}
+}
+
+/// Maps a persist key to the flash address its shadow bytes should be
+/// written at, letting [`NorFlashPersistBackend`] route different key
+/// ranges to different flash regions (e.g. boot config to a protected
+/// sector, application data to main storage) the same way
+/// [`PersistPolicy`](crate::shadow::PersistPolicy) maps shadow addresses to
+/// persist keys on the write side.
+#[cfg(feature = "norflash")]
+pub trait KeyToFlash<PK> {
+    /// Returns the flash base address that `key`'s shadow region starts at.
+    fn flash_base(&self, key: PK) -> u32;
+}
+
+/// [`KeyToFlash`] that routes every key to the same fixed flash address,
+/// for the common case of one contiguous persisted region.
+#[cfg(feature = "norflash")]
+#[derive(Debug, Clone, Copy)]
+pub struct FixedFlashBase(pub u32);
+
+#[cfg(feature = "norflash")]
+impl<PK> KeyToFlash<PK> for FixedFlashBase {
+    fn flash_base(&self, _key: PK) -> u32 {
+        self.0
+    }
+}
+
+/// [`PersistBackend`] backed by a NOR flash implementing
+/// [`embedded_storage::nor_flash::NorFlash`].
+///
+/// NOR flash requires erase-before-write at sector granularity, so each
+/// [`Self::persist`] call reads the full `SECTOR_SIZE`-byte sector
+/// containing `addr` into a scratch buffer, overlays the region's bytes,
+/// erases the sector, then programs the buffer back. `SECTOR_SIZE` must
+/// match the flash's actual erase granularity. The key's flash base address
+/// comes from `M`, a [`KeyToFlash`] mapping — use [`FixedFlashBase`] when
+/// every key persists into the same region.
+#[cfg(feature = "norflash")]
+pub struct NorFlashPersistBackend<NF, M, const SECTOR_SIZE: usize> {
+    flash: NF,
+    key_map: M,
+}
+
+#[cfg(feature = "norflash")]
+impl<NF, M, const SECTOR_SIZE: usize> NorFlashPersistBackend<NF, M, SECTOR_SIZE>
+where
+    NF: embedded_storage::nor_flash::NorFlash,
+{
+    /// Wraps `flash`, persisting each key's shadow region at the flash base
+    /// address `key_map` resolves it to.
+    pub fn new(flash: NF, key_map: M) -> Self {
+        Self { flash, key_map }
+    }
+}
+
+#[cfg(feature = "norflash")]
+impl<PK, NF, M, const SECTOR_SIZE: usize> PersistBackend<PK>
+    for NorFlashPersistBackend<NF, M, SECTOR_SIZE>
+where
+    NF: embedded_storage::nor_flash::NorFlash,
+    M: KeyToFlash<PK>,
+{
+    fn persist(&mut self, key: PK, addr: u16, data: &[u8]) -> Result<(), ShadowError> {
+        let abs_addr = self.key_map.flash_base(key) + addr as u32;
+        let sector_start = (abs_addr / SECTOR_SIZE as u32) * SECTOR_SIZE as u32;
+        let offset_in_sector = (abs_addr - sector_start) as usize;
+
+        if offset_in_sector + data.len() > SECTOR_SIZE {
+            return Err(ShadowError::OutOfBounds);
+        }
+
+        let mut sector = [0u8; SECTOR_SIZE];
+        self.flash
+            .read(sector_start, &mut sector)
+            .map_err(|_| ShadowError::PersistFailed)?;
+
+        sector[offset_in_sector..offset_in_sector + data.len()].copy_from_slice(data);
+
+        self.flash
+            .erase(sector_start, sector_start + SECTOR_SIZE as u32)
+            .map_err(|_| ShadowError::PersistFailed)?;
+
+        self.flash
+            .write(sector_start, &sector)
+            .map_err(|_| ShadowError::PersistFailed)
+    }
+}
+
+/// Monotonic, free-running tick source for [`CoalescingPersistTrigger`].
+///
+/// The counter may wrap; implementations only guarantee that it advances by
+/// one per tick, not that it never overflows. Callers must compare elapsed
+/// time with `now.wrapping_sub(start)`, never plain subtraction.
+pub trait TickSource {
+    /// Returns the current tick count.
+    fn now(&self) -> u32;
+}
+
+/// Decorates a [`PersistTrigger`], coalescing bursts of `request_persist`
+/// calls into one flush per `WINDOW` ticks.
+///
+/// Keys are forwarded to the inner trigger immediately via
+/// [`Self::push_key`], so nothing queued is lost while a flush is pending.
+/// The persist request itself is deferred: the first `request_persist`
+/// since the last flush records the current tick from `TS`, and the inner
+/// trigger's `request_persist` only fires once [`Self::poll`] observes
+/// `now.wrapping_sub(first_dirty_tick) >= WINDOW`. Call `poll` periodically
+/// (e.g. from a kernel timer ISR) to drive the deferred flush.
+pub struct CoalescingPersistTrigger<PK, PT, TS, const WINDOW: u32>
+where
+    PT: PersistTrigger<PK>,
+    TS: TickSource,
+{
+    inner: PT,
+    tick_source: TS,
+    first_dirty_tick: Option<u32>,
+    _pk: core::marker::PhantomData<PK>,
+}
+
+impl<PK, PT, TS, const WINDOW: u32> CoalescingPersistTrigger<PK, PT, TS, WINDOW>
+where
+    PT: PersistTrigger<PK>,
+    TS: TickSource,
+{
+    /// Wraps `inner`, reading elapsed time from `tick_source`.
+    pub fn new(inner: PT, tick_source: TS) -> Self {
+        Self {
+            inner,
+            tick_source,
+            first_dirty_tick: None,
+            _pk: core::marker::PhantomData,
+        }
+    }
+}
+
+impl<PK, PT, TS, const WINDOW: u32> PersistTrigger<PK>
+    for CoalescingPersistTrigger<PK, PT, TS, WINDOW>
+where
+    PT: PersistTrigger<PK>,
+    TS: TickSource,
+{
+    fn push_key(&mut self, key: PK) {
+        self.inner.push_key(key);
+    }
+
+    fn request_persist(&mut self) {
+        if self.first_dirty_tick.is_none() {
+            self.first_dirty_tick = Some(self.tick_source.now());
+        }
+    }
+}
+
+/// Entry point for driving a [`PersistTrigger`]'s deferred persistence work,
+/// e.g. from a kernel timer ISR via
+/// [`KernelShadow::poll_persist`](crate::shadow::KernelShadow::poll_persist).
+pub trait Pollable {
+    /// Advances the trigger's notion of the current tick to `now`, flushing
+    /// any deferred persistence whose coalescing window has elapsed.
+    fn poll(&mut self, now: u32);
+}
+
+impl<PK, PT, TS, const WINDOW: u32> Pollable for CoalescingPersistTrigger<PK, PT, TS, WINDOW>
+where
+    PT: PersistTrigger<PK>,
+    TS: TickSource,
+{
+    fn poll(&mut self, now: u32) {
+        if let Some(start) = self.first_dirty_tick {
+            if now.wrapping_sub(start) >= WINDOW {
+                self.inner.request_persist();
+                self.first_dirty_tick = None;
+            }
+        }
+    }
+}
+
+/// Decorates a [`PersistTrigger`], deduplicating pushed keys into a fixed
+/// `N`-bit set and suppressing the inner `request_persist()` until either
+/// `threshold` distinct keys are pending or the caller drives an explicit
+/// [`Self::tick`]/[`Self::flush`] from a periodic timer.
+///
+/// Unlike [`CoalescingPersistTrigger`], which forwards every key immediately
+/// and only defers the flush signal, `CoalescingTrigger` holds keys back too:
+/// repeated `push_key` calls for the same key under bursty, overlapping
+/// writes collapse into a single forwarded key once the merged set flushes.
+/// This requires `PK` to map onto a dense `0..N` index space, the same
+/// bounded key domain the shadow table's own dirty bitmap assumes — a
+/// `PK` outside `0..N` is silently dropped rather than panicking.
+pub struct CoalescingTrigger<PT, const N: usize>
+where
+    bitmaps::BitsImpl<N>: bitmaps::Bits,
+{
+    inner: PT,
+    pending: bitmaps::Bitmap<N>,
+    threshold: usize,
+}
+
+impl<PT, const N: usize> CoalescingTrigger<PT, N>
+where
+    bitmaps::BitsImpl<N>: bitmaps::Bits,
+{
+    /// Wraps `inner`, auto-flushing once `threshold` distinct keys are pending.
+    pub fn new(inner: PT, threshold: usize) -> Self {
+        Self {
+            inner,
+            pending: bitmaps::Bitmap::new(),
+            threshold,
+        }
+    }
+
+    /// Forwards every pending key to the inner trigger and requests
+    /// persistence once, then clears the pending set. A no-op if nothing is
+    /// pending.
+    pub fn flush<PK>(&mut self)
+    where
+        PT: PersistTrigger<PK>,
+        PK: TryFrom<usize>,
+    {
+        if self.pending.is_empty() {
+            return;
+        }
+
+        let mut idx = self.pending.first_index();
+        while let Some(i) = idx {
+            if let Ok(key) = PK::try_from(i) {
+                self.inner.push_key(key);
+            }
+            idx = self.pending.next_index(i);
+        }
+        self.inner.request_persist();
+        self.pending = bitmaps::Bitmap::new();
+    }
+
+    /// Alias for [`Self::flush`], named for use from a periodic timer ISR
+    /// alongside [`CoalescingPersistTrigger::poll`]/[`Pollable::poll`].
+    pub fn tick<PK>(&mut self)
+    where
+        PT: PersistTrigger<PK>,
+        PK: TryFrom<usize>,
+    {
+        self.flush::<PK>();
+    }
+}
+
+impl<PK, PT, const N: usize> PersistTrigger<PK> for CoalescingTrigger<PT, N>
+where
+    PT: PersistTrigger<PK>,
+    PK: Copy + Into<usize> + TryFrom<usize>,
+    bitmaps::BitsImpl<N>: bitmaps::Bits,
+{
+    fn push_key(&mut self, key: PK) {
+        let idx = key.into();
+        if idx >= N {
+            return;
+        }
+
+        self.pending.set(idx, true);
+        if self.pending.len() >= self.threshold {
+            self.flush::<PK>();
+        }
+    }
+
+    fn request_persist(&mut self) {
+        // Deliberately suppressed: the merged key set is only forwarded,
+        // together with a single `request_persist`, once the threshold or
+        // an explicit `tick`/`flush` fires. See the type-level docs.
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::cell::Cell;
+
+    #[derive(Default)]
+    struct RecordingBackend {
+        last: Option<(u16, [u8; 4])>,
+        fail_next: bool,
+    }
+
+    impl PersistBackend<u16> for RecordingBackend {
+        fn persist(&mut self, _key: u16, addr: u16, data: &[u8]) -> Result<(), ShadowError> {
+            if self.fail_next {
+                return Err(ShadowError::PersistFailed);
+            }
+            let mut buf = [0u8; 4];
+            buf.copy_from_slice(data);
+            self.last = Some((addr, buf));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn persist_backend_records_committed_bytes() {
+        let mut backend = RecordingBackend::default();
+        backend.persist(7, 32, &[1, 2, 3, 4]).unwrap();
+        assert_eq!(backend.last, Some((32, [1, 2, 3, 4])));
+    }
+
+    #[test]
+    fn persist_backend_propagates_failure() {
+        let mut backend = RecordingBackend {
+            fail_next: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            backend.persist(7, 32, &[1, 2, 3, 4]),
+            Err(ShadowError::PersistFailed)
+        );
+    }
+
+    #[cfg(feature = "async")]
+    fn noop_waker() -> core::task::Waker {
+        core::task::Waker::noop().clone()
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn async_trigger_adapter_forwards_sync_calls_and_flushes_immediately() {
+        use core::{future::Future, task::Context};
+
+        let mut adapter = AsyncPersistTriggerAdapter(CountingTrigger::default());
+        adapter.push_key(());
+        adapter.request_persist();
+        assert_eq!(adapter.0.flushes, 1);
+
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+        let mut fut = core::pin::pin!(adapter.flush());
+        assert_eq!(fut.as_mut().poll(&mut cx), core::task::Poll::Ready(()));
+    }
+
+    struct FakeTick(Cell<u32>);
+
+    impl TickSource for FakeTick {
+        fn now(&self) -> u32 {
+            self.0.get()
+        }
+    }
+
+    #[derive(Default)]
+    struct CountingTrigger {
+        flushes: usize,
+    }
+
+    impl PersistTrigger<()> for CountingTrigger {
+        fn push_key(&mut self, _key: ()) {}
+        fn request_persist(&mut self) {
+            self.flushes += 1;
+        }
+    }
+
+    #[test]
+    fn does_not_flush_before_window_elapses() {
+        let tick = FakeTick(Cell::new(0));
+        let mut trigger: CoalescingPersistTrigger<(), _, _, 10> =
+            CoalescingPersistTrigger::new(CountingTrigger::default(), tick);
+
+        trigger.request_persist();
+        trigger.poll(5);
+
+        assert_eq!(trigger.inner.flushes, 0);
+    }
+
+    #[test]
+    fn flushes_once_window_elapses() {
+        let tick = FakeTick(Cell::new(100));
+        let mut trigger: CoalescingPersistTrigger<(), _, _, 10> =
+            CoalescingPersistTrigger::new(CountingTrigger::default(), tick);
+
+        trigger.request_persist();
+        trigger.poll(109);
+        assert_eq!(trigger.inner.flushes, 0);
+
+        trigger.poll(110);
+        assert_eq!(trigger.inner.flushes, 1);
+    }
+
+    #[test]
+    fn repeated_requests_within_window_do_not_reset_it() {
+        let tick = FakeTick(Cell::new(0));
+        let mut trigger: CoalescingPersistTrigger<(), _, _, 10> =
+            CoalescingPersistTrigger::new(CountingTrigger::default(), tick);
+
+        trigger.request_persist();
+        trigger.tick_source.0.set(5);
+        trigger.request_persist();
+
+        trigger.poll(10);
+        assert_eq!(trigger.inner.flushes, 1);
+    }
+
+    #[test]
+    fn elapsed_comparison_is_wrap_safe() {
+        // Window opened near the top of the u32 range; "now" has wrapped
+        // around past zero. Plain subtraction would underflow/misbehave,
+        // but wrapping_sub yields the correct small elapsed value.
+        let tick = FakeTick(Cell::new(u32::MAX - 2));
+        let mut trigger: CoalescingPersistTrigger<(), _, _, 10> =
+            CoalescingPersistTrigger::new(CountingTrigger::default(), tick);
+
+        trigger.request_persist();
+        trigger.poll(10); // elapsed = 10.wrapping_sub(MAX - 2) = 13
+        assert_eq!(trigger.inner.flushes, 1);
+    }
+
+    #[test]
+    fn new_window_starts_after_a_flush() {
+        let tick = FakeTick(Cell::new(0));
+        let mut trigger: CoalescingPersistTrigger<(), _, _, 10> =
+            CoalescingPersistTrigger::new(CountingTrigger::default(), tick);
+
+        trigger.request_persist();
+        trigger.poll(10);
+        assert_eq!(trigger.inner.flushes, 1);
+
+        // No new request yet, so further polling does nothing.
+        trigger.poll(1000);
+        assert_eq!(trigger.inner.flushes, 1);
+
+        trigger.tick_source.0.set(1000);
+        trigger.request_persist();
+        trigger.poll(1009);
+        assert_eq!(trigger.inner.flushes, 1);
+        trigger.poll(1010);
+        assert_eq!(trigger.inner.flushes, 2);
+    }
+
+    #[derive(Default)]
+    struct RecordingKeyTrigger {
+        pushed: heapless::Vec<u16, 8>,
+        flushes: usize,
+    }
+
+    impl PersistTrigger<u16> for RecordingKeyTrigger {
+        fn push_key(&mut self, key: u16) {
+            self.pushed.push(key).unwrap();
+        }
+
+        fn request_persist(&mut self) {
+            self.flushes += 1;
+        }
+    }
+
+    #[test]
+    fn coalescing_trigger_suppresses_flush_below_threshold() {
+        let mut trigger: CoalescingTrigger<RecordingKeyTrigger, 8> =
+            CoalescingTrigger::new(RecordingKeyTrigger::default(), 3);
+
+        trigger.push_key(1);
+        trigger.push_key(2);
+
+        assert!(trigger.inner.pushed.is_empty());
+        assert_eq!(trigger.inner.flushes, 0);
+    }
+
+    #[test]
+    fn coalescing_trigger_flushes_deduped_keys_once_threshold_crossed() {
+        let mut trigger: CoalescingTrigger<RecordingKeyTrigger, 8> =
+            CoalescingTrigger::new(RecordingKeyTrigger::default(), 3);
+
+        trigger.push_key(1);
+        trigger.push_key(1); // repeated key, should not count twice
+        trigger.push_key(2);
+        trigger.push_key(3);
+
+        assert_eq!(trigger.inner.pushed.as_slice(), &[1, 2, 3]);
+        assert_eq!(trigger.inner.flushes, 1);
+    }
+
+    #[test]
+    fn coalescing_trigger_explicit_flush_forwards_pending_keys() {
+        let mut trigger: CoalescingTrigger<RecordingKeyTrigger, 8> =
+            CoalescingTrigger::new(RecordingKeyTrigger::default(), 100);
+
+        trigger.push_key(4);
+        trigger.push_key(5);
+        assert_eq!(trigger.inner.flushes, 0);
+
+        trigger.flush::<u16>();
+
+        assert_eq!(trigger.inner.pushed.as_slice(), &[4, 5]);
+        assert_eq!(trigger.inner.flushes, 1);
+    }
+
+    #[test]
+    fn coalescing_trigger_tick_is_a_no_op_when_nothing_is_pending() {
+        let mut trigger: CoalescingTrigger<RecordingKeyTrigger, 8> =
+            CoalescingTrigger::new(RecordingKeyTrigger::default(), 100);
+
+        trigger.tick::<u16>();
+
+        assert!(trigger.inner.pushed.is_empty());
+        assert_eq!(trigger.inner.flushes, 0);
+    }
+
+    #[test]
+    fn coalescing_trigger_drops_keys_outside_the_bounded_index_space() {
+        let mut trigger: CoalescingTrigger<RecordingKeyTrigger, 4> =
+            CoalescingTrigger::new(RecordingKeyTrigger::default(), 1);
+
+        trigger.push_key(9); // out of range for N = 4, silently dropped
+
+        assert!(trigger.inner.pushed.is_empty());
+        assert_eq!(trigger.inner.flushes, 0);
+    }
+}
@@ -1,3 +1,5 @@
+use crate::shadow::ShadowError;
+
 /// Controls read/write access to shadow table regions.
 pub trait AccessPolicy {
     /// Returns true if reading from `addr` for `len` bytes is allowed.
@@ -20,6 +22,143 @@ impl AccessPolicy for AllowAllPolicy {
     }
 }
 
+/// Read/write permission bits granted to a [`RegionAccessPolicy`] region.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Perm(u8);
+
+impl Perm {
+    /// No access.
+    pub const NONE: Perm = Perm(0);
+    /// Read access only.
+    pub const READ: Perm = Perm(0b01);
+    /// Write access only.
+    pub const WRITE: Perm = Perm(0b10);
+    /// Both read and write access.
+    pub const READ_WRITE: Perm = Perm(0b11);
+
+    /// Returns true if this grants every bit set in `other`.
+    pub const fn contains(self, other: Perm) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl core::ops::BitOr for Perm {
+    type Output = Perm;
+
+    fn bitor(self, rhs: Perm) -> Perm {
+        Perm(self.0 | rhs.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Region {
+    start: u16,
+    len: u16,
+    perm: Perm,
+}
+
+impl Region {
+    const EMPTY: Region = Region {
+        start: 0,
+        len: 0,
+        perm: Perm::NONE,
+    };
+}
+
+/// MPU/PMP-style [`AccessPolicy`]: a fixed table of up to `N` regions, each
+/// with its own [`Perm`].
+///
+/// A request is allowed only if `[addr, addr+len)` falls entirely within a
+/// single declared region granting the requested permission; a range that
+/// straddles two regions, or falls partly or fully in a gap between them,
+/// is denied. This protects e.g. a read-only status block and a writable
+/// control block in the same shadow table without a custom [`AccessPolicy`]
+/// impl.
+///
+/// Built with [`Self::with_region`], which is `const fn` so a full policy
+/// can be assembled at compile time for a `static` [`ShadowStorage`](crate::shadow::ShadowStorage).
+#[derive(Debug, Clone, Copy)]
+pub struct RegionAccessPolicy<const N: usize> {
+    regions: [Region; N],
+    count: usize,
+}
+
+impl<const N: usize> RegionAccessPolicy<N> {
+    /// Creates an empty policy that denies every request.
+    pub const fn new() -> Self {
+        Self {
+            regions: [Region::EMPTY; N],
+            count: 0,
+        }
+    }
+
+    /// Declares a region `[start, start+len)` with the given permissions.
+    ///
+    /// # Panics
+    /// Panics if more than `N` regions have already been declared.
+    pub const fn with_region(mut self, start: u16, len: u16, perm: Perm) -> Self {
+        assert!(self.count < N, "RegionAccessPolicy: too many regions for N");
+        self.regions[self.count] = Region { start, len, perm };
+        self.count += 1;
+        self
+    }
+
+    fn allows(&self, addr: u16, len: usize, perm: Perm) -> bool {
+        let start = addr as u32;
+        let end = start + len as u32;
+        self.regions[..self.count].iter().any(|r| {
+            let r_start = r.start as u32;
+            let r_end = r_start + r.len as u32;
+            start >= r_start && end <= r_end && r.perm.contains(perm)
+        })
+    }
+
+    /// Reconfigures region `slot` to `[start, start+len)` with `perm`, e.g.
+    /// so firmware can open a calibration window while in service mode and
+    /// close it again afterward. Unlike [`Self::with_region`], this mutates
+    /// an already-built policy at runtime; `slot` out of range (`>= N`)
+    /// returns [`ShadowError::OutOfBounds`] instead of panicking.
+    pub fn set_region(
+        &mut self,
+        slot: usize,
+        start: u16,
+        len: u16,
+        perm: Perm,
+    ) -> Result<(), ShadowError> {
+        let region = self.regions.get_mut(slot).ok_or(ShadowError::OutOfBounds)?;
+        *region = Region { start, len, perm };
+        if slot >= self.count {
+            self.count = slot + 1;
+        }
+        Ok(())
+    }
+
+    /// Revokes all access granted through region `slot`, turning its range
+    /// back into a denied gap. The slot can be reused later with
+    /// [`Self::set_region`].
+    pub fn clear_region(&mut self, slot: usize) -> Result<(), ShadowError> {
+        let region = self.regions.get_mut(slot).ok_or(ShadowError::OutOfBounds)?;
+        *region = Region::EMPTY;
+        Ok(())
+    }
+}
+
+impl<const N: usize> Default for RegionAccessPolicy<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> AccessPolicy for RegionAccessPolicy<N> {
+    fn can_read(&self, addr: u16, len: usize) -> bool {
+        self.allows(addr, len, Perm::READ)
+    }
+
+    fn can_write(&self, addr: u16, len: usize) -> bool {
+        self.allows(addr, len, Perm::WRITE)
+    }
+}
+
 /// Determines which regions require persistence and emits keys for them.
 pub trait PersistPolicy<PK> {
     /// Pushes persistence keys for the given range and returns true if persistence is needed.
@@ -37,3 +176,92 @@ impl PersistPolicy<()> for NoPersistPolicy {
         false
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status_and_control() -> RegionAccessPolicy<2> {
+        RegionAccessPolicy::new()
+            .with_region(0, 16, Perm::READ)
+            .with_region(16, 16, Perm::READ_WRITE)
+    }
+
+    #[test]
+    fn read_only_region_denies_writes() {
+        let policy = status_and_control();
+
+        assert!(policy.can_read(0, 16));
+        assert!(!policy.can_write(0, 16));
+    }
+
+    #[test]
+    fn read_write_region_allows_both() {
+        let policy = status_and_control();
+
+        assert!(policy.can_read(16, 16));
+        assert!(policy.can_write(16, 16));
+    }
+
+    #[test]
+    fn range_straddling_two_regions_is_denied() {
+        let policy = status_and_control();
+
+        assert!(!policy.can_read(8, 16));
+        assert!(!policy.can_write(8, 16));
+    }
+
+    #[test]
+    fn range_in_a_gap_is_denied() {
+        let policy = RegionAccessPolicy::<1>::new().with_region(0, 16, Perm::READ_WRITE);
+
+        assert!(!policy.can_read(16, 4));
+        assert!(!policy.can_write(16, 4));
+    }
+
+    #[test]
+    fn empty_policy_denies_everything() {
+        let policy = RegionAccessPolicy::<0>::new();
+
+        assert!(!policy.can_read(0, 4));
+        assert!(!policy.can_write(0, 4));
+    }
+
+    #[test]
+    fn set_region_opens_a_window_at_runtime() {
+        let mut policy = RegionAccessPolicy::<1>::new();
+        assert!(!policy.can_read(0, 16));
+
+        policy.set_region(0, 0, 16, Perm::READ_WRITE).unwrap();
+
+        assert!(policy.can_read(0, 16));
+        assert!(policy.can_write(0, 16));
+    }
+
+    #[test]
+    fn clear_region_closes_a_previously_opened_window() {
+        let mut policy = RegionAccessPolicy::<1>::new();
+        policy.set_region(0, 0, 16, Perm::READ_WRITE).unwrap();
+        assert!(policy.can_read(0, 16));
+
+        policy.clear_region(0).unwrap();
+
+        assert!(!policy.can_read(0, 16));
+        assert!(!policy.can_write(0, 16));
+    }
+
+    #[test]
+    fn set_region_rejects_out_of_range_slot() {
+        let mut policy = RegionAccessPolicy::<2>::new();
+        assert_eq!(
+            policy.set_region(2, 0, 16, Perm::READ),
+            Err(ShadowError::OutOfBounds)
+        );
+    }
+
+    #[test]
+    fn clear_region_rejects_out_of_range_slot() {
+        let mut policy = RegionAccessPolicy::<2>::new();
+        assert_eq!(policy.clear_region(2), Err(ShadowError::OutOfBounds));
+    }
+}
@@ -0,0 +1,411 @@
+use super::{ROSlice, RWSlice};
+
+/// Generates `get_*`/`try_get_*` cursor methods for a single primitive type.
+macro_rules! impl_cursor_get_primitive {
+    (u8) => {
+        /// Reads a `u8` at the cursor and advances it by 1 byte.
+        ///
+        /// # Panics
+        /// Panics if no bytes remain.
+        #[inline]
+        pub fn get_u8(&mut self) -> u8 {
+            let v = self.slice.read_u8_at(self.pos);
+            self.pos += 1;
+            v
+        }
+
+        /// Tries to read a `u8` at the cursor, advancing it by 1 byte on
+        /// success. Returns `None`, without advancing, if no bytes remain.
+        #[inline]
+        pub fn try_get_u8(&mut self) -> Option<u8> {
+            let v = self.slice.try_read_u8_at(self.pos)?;
+            self.pos += 1;
+            Some(v)
+        }
+    };
+    (i8) => {
+        /// Reads an `i8` at the cursor and advances it by 1 byte.
+        ///
+        /// # Panics
+        /// Panics if no bytes remain.
+        #[inline]
+        pub fn get_i8(&mut self) -> i8 {
+            let v = self.slice.read_i8_at(self.pos);
+            self.pos += 1;
+            v
+        }
+
+        /// Tries to read an `i8` at the cursor, advancing it by 1 byte on
+        /// success. Returns `None`, without advancing, if no bytes remain.
+        #[inline]
+        pub fn try_get_i8(&mut self) -> Option<i8> {
+            let v = self.slice.try_read_i8_at(self.pos)?;
+            self.pos += 1;
+            Some(v)
+        }
+    };
+    ($type:ty, $size:literal) => {
+        paste::paste! {
+            #[doc = "Reads a little-endian `" $type "` at the cursor and advances it by " $size " bytes."]
+            #[doc = ""]
+            #[doc = "# Panics"]
+            #[doc = "Panics if fewer than " $size " bytes remain."]
+            #[inline]
+            pub fn [<get_ $type _le>](&mut self) -> $type {
+                let v = self.slice.[<read_ $type _le_at>](self.pos);
+                self.pos += $size;
+                v
+            }
+
+            #[doc = "Reads a big-endian `" $type "` at the cursor and advances it by " $size " bytes."]
+            #[doc = ""]
+            #[doc = "# Panics"]
+            #[doc = "Panics if fewer than " $size " bytes remain."]
+            #[inline]
+            pub fn [<get_ $type _be>](&mut self) -> $type {
+                let v = self.slice.[<read_ $type _be_at>](self.pos);
+                self.pos += $size;
+                v
+            }
+
+            #[doc = "Tries to read a little-endian `" $type "` at the cursor, advancing it by " $size " bytes on success."]
+            #[doc = ""]
+            #[doc = "Returns `None`, without advancing, if fewer than " $size " bytes remain."]
+            #[inline]
+            pub fn [<try_get_ $type _le>](&mut self) -> Option<$type> {
+                let v = self.slice.[<try_read_ $type _le_at>](self.pos)?;
+                self.pos += $size;
+                Some(v)
+            }
+
+            #[doc = "Tries to read a big-endian `" $type "` at the cursor, advancing it by " $size " bytes on success."]
+            #[doc = ""]
+            #[doc = "Returns `None`, without advancing, if fewer than " $size " bytes remain."]
+            #[inline]
+            pub fn [<try_get_ $type _be>](&mut self) -> Option<$type> {
+                let v = self.slice.[<try_read_ $type _be_at>](self.pos)?;
+                self.pos += $size;
+                Some(v)
+            }
+        }
+    };
+}
+
+/// Generates `put_*`/`try_put_*` cursor methods for a single primitive type.
+macro_rules! impl_cursor_put_primitive {
+    (u8) => {
+        /// Writes a `u8` at the cursor and advances it by 1 byte.
+        ///
+        /// # Panics
+        /// Panics if no bytes remain.
+        #[inline]
+        pub fn put_u8(&mut self, value: u8) {
+            self.slice.write_u8_at(self.pos, value);
+            self.pos += 1;
+        }
+
+        /// Tries to write a `u8` at the cursor, advancing it by 1 byte on
+        /// success. Returns `None`, without advancing or writing, if no
+        /// bytes remain.
+        #[inline]
+        pub fn try_put_u8(&mut self, value: u8) -> Option<()> {
+            self.slice.try_write_u8_at(self.pos, value)?;
+            self.pos += 1;
+            Some(())
+        }
+    };
+    (i8) => {
+        /// Writes an `i8` at the cursor and advances it by 1 byte.
+        ///
+        /// # Panics
+        /// Panics if no bytes remain.
+        #[inline]
+        pub fn put_i8(&mut self, value: i8) {
+            self.slice.write_i8_at(self.pos, value);
+            self.pos += 1;
+        }
+
+        /// Tries to write an `i8` at the cursor, advancing it by 1 byte on
+        /// success. Returns `None`, without advancing or writing, if no
+        /// bytes remain.
+        #[inline]
+        pub fn try_put_i8(&mut self, value: i8) -> Option<()> {
+            self.slice.try_write_i8_at(self.pos, value)?;
+            self.pos += 1;
+            Some(())
+        }
+    };
+    ($type:ty, $size:literal) => {
+        paste::paste! {
+            #[doc = "Writes a little-endian `" $type "` at the cursor and advances it by " $size " bytes."]
+            #[doc = ""]
+            #[doc = "# Panics"]
+            #[doc = "Panics if fewer than " $size " bytes remain."]
+            #[inline]
+            pub fn [<put_ $type _le>](&mut self, value: $type) {
+                self.slice.[<write_ $type _le_at>](self.pos, value);
+                self.pos += $size;
+            }
+
+            #[doc = "Writes a big-endian `" $type "` at the cursor and advances it by " $size " bytes."]
+            #[doc = ""]
+            #[doc = "# Panics"]
+            #[doc = "Panics if fewer than " $size " bytes remain."]
+            #[inline]
+            pub fn [<put_ $type _be>](&mut self, value: $type) {
+                self.slice.[<write_ $type _be_at>](self.pos, value);
+                self.pos += $size;
+            }
+
+            #[doc = "Tries to write a little-endian `" $type "` at the cursor, advancing it by " $size " bytes on success."]
+            #[doc = ""]
+            #[doc = "Returns `None`, without advancing or writing, if fewer than " $size " bytes remain."]
+            #[inline]
+            pub fn [<try_put_ $type _le>](&mut self, value: $type) -> Option<()> {
+                self.slice.[<try_write_ $type _le_at>](self.pos, value)?;
+                self.pos += $size;
+                Some(())
+            }
+
+            #[doc = "Tries to write a big-endian `" $type "` at the cursor, advancing it by " $size " bytes on success."]
+            #[doc = ""]
+            #[doc = "Returns `None`, without advancing or writing, if fewer than " $size " bytes remain."]
+            #[inline]
+            pub fn [<try_put_ $type _be>](&mut self, value: $type) -> Option<()> {
+                self.slice.[<try_write_ $type _be_at>](self.pos, value)?;
+                self.pos += $size;
+                Some(())
+            }
+        }
+    };
+}
+
+/// Sequential `Buf`-style cursor over an [`ROSlice`], modeled on the `bytes`
+/// crate's `Buf` trait.
+///
+/// Each `get_*` call reads at the current position and advances past it, so
+/// decoding a packed register layout reads as a linear sequence of calls
+/// instead of a hand-maintained list of byte offsets. The existing `_at`
+/// methods on [`ROSlice`] are unaffected; `Reader` is an alternative way to
+/// walk the same slice, not a replacement.
+pub struct Reader<'a> {
+    slice: ROSlice<'a>,
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    #[inline]
+    pub(crate) fn new(slice: ROSlice<'a>) -> Self {
+        Self { slice, pos: 0 }
+    }
+
+    /// Number of bytes not yet consumed.
+    #[inline]
+    pub fn remaining(&self) -> usize {
+        self.slice.len() - self.pos
+    }
+
+    /// Advances the cursor by `n` bytes without reading them.
+    ///
+    /// # Panics
+    /// Panics if `n > remaining()`.
+    #[inline]
+    pub fn skip(&mut self, n: usize) {
+        assert!(n <= self.remaining(), "skip out of bounds");
+        self.pos += n;
+    }
+
+    impl_cursor_get_primitive!(u8);
+    impl_cursor_get_primitive!(i8);
+    impl_cursor_get_primitive!(u16, 2);
+    impl_cursor_get_primitive!(i16, 2);
+    impl_cursor_get_primitive!(u32, 4);
+    impl_cursor_get_primitive!(i32, 4);
+    impl_cursor_get_primitive!(u64, 8);
+    impl_cursor_get_primitive!(i64, 8);
+    impl_cursor_get_primitive!(f32, 4);
+    impl_cursor_get_primitive!(f64, 8);
+
+    /// Copies `dest.len()` bytes starting at the cursor into `dest` and
+    /// advances the cursor past them.
+    ///
+    /// # Panics
+    /// Panics if fewer than `dest.len()` bytes remain.
+    #[inline]
+    pub fn read_slice(&mut self, dest: &mut [u8]) {
+        self.slice.copy_to_slice_at(self.pos, dest);
+        self.pos += dest.len();
+    }
+}
+
+/// Sequential `BufMut`-style cursor over an [`RWSlice`], modeled on the
+/// `bytes` crate's `BufMut` trait. Mirrors [`Reader`] for the write side:
+/// each `put_*` call writes at the current position and advances past it.
+pub struct Writer<'a> {
+    slice: RWSlice<'a>,
+    pos: usize,
+}
+
+impl<'a> Writer<'a> {
+    #[inline]
+    pub(crate) fn new(slice: RWSlice<'a>) -> Self {
+        Self { slice, pos: 0 }
+    }
+
+    /// Number of bytes not yet written.
+    #[inline]
+    pub fn remaining(&self) -> usize {
+        self.slice.len() - self.pos
+    }
+
+    /// Advances the cursor by `n` bytes without writing them.
+    ///
+    /// # Panics
+    /// Panics if `n > remaining()`.
+    #[inline]
+    pub fn skip(&mut self, n: usize) {
+        assert!(n <= self.remaining(), "skip out of bounds");
+        self.pos += n;
+    }
+
+    impl_cursor_put_primitive!(u8);
+    impl_cursor_put_primitive!(i8);
+    impl_cursor_put_primitive!(u16, 2);
+    impl_cursor_put_primitive!(i16, 2);
+    impl_cursor_put_primitive!(u32, 4);
+    impl_cursor_put_primitive!(i32, 4);
+    impl_cursor_put_primitive!(u64, 8);
+    impl_cursor_put_primitive!(i64, 8);
+    impl_cursor_put_primitive!(f32, 4);
+    impl_cursor_put_primitive!(f64, 8);
+
+    /// Writes `src` starting at the cursor and advances the cursor past it.
+    ///
+    /// # Panics
+    /// Panics if fewer than `src.len()` bytes remain.
+    #[inline]
+    pub fn write_slice(&mut self, src: &[u8]) {
+        self.slice.copy_from_slice_at(self.pos, src);
+        self.pos += src.len();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reader_reads_sequentially_and_advances() {
+        let data = [0x01, 0x34, 0x12, 0xFF];
+        let mut reader = ROSlice::new(&data).reader();
+
+        assert_eq!(reader.get_u8(), 0x01);
+        assert_eq!(reader.get_u16_le(), 0x1234);
+        assert_eq!(reader.remaining(), 1);
+        assert_eq!(reader.get_i8(), -1);
+        assert_eq!(reader.remaining(), 0);
+    }
+
+    #[test]
+    fn reader_skip_advances_without_reading() {
+        let data = [0xAA, 0xBB, 0xCC];
+        let mut reader = ROSlice::new(&data).reader();
+
+        reader.skip(2);
+        assert_eq!(reader.get_u8(), 0xCC);
+    }
+
+    #[test]
+    #[should_panic(expected = "index out of bounds")]
+    fn reader_get_past_end_panics() {
+        let data = [0x01];
+        let mut reader = ROSlice::new(&data).reader();
+        reader.get_u8();
+        reader.get_u8();
+    }
+
+    #[test]
+    fn reader_try_get_returns_none_at_end_of_buffer() {
+        let data = [0x01];
+        let mut reader = ROSlice::new(&data).reader();
+
+        assert_eq!(reader.try_get_u8(), Some(0x01));
+        assert_eq!(reader.try_get_u8(), None);
+        assert_eq!(reader.remaining(), 0);
+    }
+
+    #[test]
+    fn writer_writes_sequentially_and_advances() {
+        let mut data = [0u8; 4];
+        {
+            let mut writer = RWSlice::new(&mut data).writer();
+            writer.put_u8(0x01);
+            writer.put_u16_be(0x1234);
+            writer.put_i8(-1);
+        }
+
+        assert_eq!(data, [0x01, 0x12, 0x34, 0xFF]);
+    }
+
+    #[test]
+    fn writer_try_put_returns_none_without_writing_past_end() {
+        let mut data = [0u8; 1];
+        let mut writer = RWSlice::new(&mut data).writer();
+
+        assert_eq!(writer.try_put_u8(0xAA), Some(()));
+        assert_eq!(writer.try_put_u8(0xBB), None);
+        assert_eq!(data, [0xAA]);
+    }
+
+    #[test]
+    fn reader_reads_64_bit_and_float_primitives() {
+        let mut data = [0u8; 16];
+        data[0..8].copy_from_slice(&0x0102_0304_0506_0708u64.to_le_bytes());
+        data[8..12].copy_from_slice(&1.5f32.to_le_bytes());
+        data[12..16].copy_from_slice(&(-1i32).to_be_bytes());
+        let mut reader = ROSlice::new(&data).reader();
+
+        assert_eq!(reader.get_u64_le(), 0x0102_0304_0506_0708);
+        assert_eq!(reader.get_f32_le(), 1.5f32);
+        assert_eq!(reader.get_i32_be(), -1);
+        assert_eq!(reader.remaining(), 0);
+    }
+
+    #[test]
+    fn reader_read_slice_copies_and_advances() {
+        let data = [0x01, 0x02, 0x03, 0x04];
+        let mut reader = ROSlice::new(&data).reader();
+
+        let mut dest = [0u8; 3];
+        reader.read_slice(&mut dest);
+        assert_eq!(dest, [0x01, 0x02, 0x03]);
+        assert_eq!(reader.get_u8(), 0x04);
+    }
+
+    #[test]
+    fn writer_writes_64_bit_and_float_primitives() {
+        let mut data = [0u8; 12];
+        {
+            let mut writer = RWSlice::new(&mut data).writer();
+            writer.put_u64_le(0x0102_0304_0506_0708);
+            writer.put_f32_be(2.5f32);
+        }
+
+        let mut expected = [0u8; 12];
+        expected[0..8].copy_from_slice(&0x0102_0304_0506_0708u64.to_le_bytes());
+        expected[8..12].copy_from_slice(&2.5f32.to_be_bytes());
+        assert_eq!(data, expected);
+    }
+
+    #[test]
+    fn writer_write_slice_copies_and_advances() {
+        let mut data = [0u8; 4];
+        {
+            let mut writer = RWSlice::new(&mut data).writer();
+            writer.write_slice(&[0xAA, 0xBB, 0xCC]);
+            writer.put_u8(0xDD);
+        }
+
+        assert_eq!(data, [0xAA, 0xBB, 0xCC, 0xDD]);
+    }
+}
@@ -0,0 +1,439 @@
+use core::marker::PhantomData;
+
+use super::RWSlice;
+use crate::shadow::ShadowError;
+
+/// Byte order a [`Field`] reads/writes its on-the-wire bytes in.
+///
+/// Kept per-field rather than per-block since mixed-endian structured data
+/// (a little-endian register next to a big-endian one inherited from a
+/// peripheral's datasheet) shows up across the STM32/zynq register work.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endian {
+    /// Least-significant byte first.
+    Little,
+    /// Most-significant byte first.
+    Big,
+}
+
+/// Primitive types a [`Field`] can describe.
+///
+/// Implemented for `u8`, `i8`, `u16`, `i16`, `u32` and `i32`; the 64-bit
+/// and float types the slice read/write macros also cover have no impl.
+pub trait FieldPrimitive: Copy {
+    /// Reads `Self` at `offset` in `slice` using `endian`.
+    fn read_at(slice: &RWSlice<'_>, offset: usize, endian: Endian) -> Self;
+    /// Writes `value` at `offset` in `slice` using `endian`.
+    fn write_at(slice: &mut RWSlice<'_>, offset: usize, endian: Endian, value: Self);
+    /// Tries to read `Self` at `offset` in `slice` using `endian`, returning
+    /// `None` instead of panicking if `offset` runs past `slice`'s bounds.
+    fn try_read_at(slice: &RWSlice<'_>, offset: usize, endian: Endian) -> Option<Self>;
+    /// Tries to write `value` at `offset` in `slice` using `endian`,
+    /// returning `None` instead of panicking if `offset` runs past `slice`'s
+    /// bounds.
+    fn try_write_at(
+        slice: &mut RWSlice<'_>,
+        offset: usize,
+        endian: Endian,
+        value: Self,
+    ) -> Option<()>;
+    /// Widens `self` to `i64` for scale arithmetic.
+    fn to_i64(self) -> i64;
+    /// Narrows `raw` back from `i64` after scale arithmetic.
+    fn from_i64(raw: i64) -> Self;
+}
+
+macro_rules! impl_field_primitive_byte {
+    ($type:ty, $read:ident, $write:ident, $try_read:ident, $try_write:ident, $as_i64:expr, $from_i64:expr) => {
+        impl FieldPrimitive for $type {
+            fn read_at(slice: &RWSlice<'_>, offset: usize, _endian: Endian) -> Self {
+                slice.$read(offset)
+            }
+
+            fn write_at(slice: &mut RWSlice<'_>, offset: usize, _endian: Endian, value: Self) {
+                slice.$write(offset, value)
+            }
+
+            fn try_read_at(slice: &RWSlice<'_>, offset: usize, _endian: Endian) -> Option<Self> {
+                slice.$try_read(offset)
+            }
+
+            fn try_write_at(
+                slice: &mut RWSlice<'_>,
+                offset: usize,
+                _endian: Endian,
+                value: Self,
+            ) -> Option<()> {
+                slice.$try_write(offset, value)
+            }
+
+            fn to_i64(self) -> i64 {
+                let f: fn(Self) -> i64 = $as_i64;
+                f(self)
+            }
+
+            fn from_i64(raw: i64) -> Self {
+                let f: fn(i64) -> Self = $from_i64;
+                f(raw)
+            }
+        }
+    };
+}
+
+macro_rules! impl_field_primitive {
+    ($type:ty, $read_le:ident, $read_be:ident, $write_le:ident, $write_be:ident, $try_read_le:ident, $try_read_be:ident, $try_write_le:ident, $try_write_be:ident, $as_i64:expr, $from_i64:expr) => {
+        impl FieldPrimitive for $type {
+            fn read_at(slice: &RWSlice<'_>, offset: usize, endian: Endian) -> Self {
+                match endian {
+                    Endian::Little => slice.$read_le(offset),
+                    Endian::Big => slice.$read_be(offset),
+                }
+            }
+
+            fn write_at(slice: &mut RWSlice<'_>, offset: usize, endian: Endian, value: Self) {
+                match endian {
+                    Endian::Little => slice.$write_le(offset, value),
+                    Endian::Big => slice.$write_be(offset, value),
+                }
+            }
+
+            fn try_read_at(slice: &RWSlice<'_>, offset: usize, endian: Endian) -> Option<Self> {
+                match endian {
+                    Endian::Little => slice.$try_read_le(offset),
+                    Endian::Big => slice.$try_read_be(offset),
+                }
+            }
+
+            fn try_write_at(
+                slice: &mut RWSlice<'_>,
+                offset: usize,
+                endian: Endian,
+                value: Self,
+            ) -> Option<()> {
+                match endian {
+                    Endian::Little => slice.$try_write_le(offset, value),
+                    Endian::Big => slice.$try_write_be(offset, value),
+                }
+            }
+
+            fn to_i64(self) -> i64 {
+                let f: fn(Self) -> i64 = $as_i64;
+                f(self)
+            }
+
+            fn from_i64(raw: i64) -> Self {
+                let f: fn(i64) -> Self = $from_i64;
+                f(raw)
+            }
+        }
+    };
+}
+
+impl_field_primitive_byte!(
+    u8,
+    read_u8_at,
+    write_u8_at,
+    try_read_u8_at,
+    try_write_u8_at,
+    |v| v as i64,
+    |raw| raw as u8
+);
+impl_field_primitive_byte!(
+    i8,
+    read_i8_at,
+    write_i8_at,
+    try_read_i8_at,
+    try_write_i8_at,
+    |v| v as i64,
+    |raw| raw as i8
+);
+impl_field_primitive!(
+    u16,
+    read_u16_le_at,
+    read_u16_be_at,
+    write_u16_le_at,
+    write_u16_be_at,
+    try_read_u16_le_at,
+    try_read_u16_be_at,
+    try_write_u16_le_at,
+    try_write_u16_be_at,
+    |v| v as i64,
+    |raw| raw as u16
+);
+impl_field_primitive!(
+    i16,
+    read_i16_le_at,
+    read_i16_be_at,
+    write_i16_le_at,
+    write_i16_be_at,
+    try_read_i16_le_at,
+    try_read_i16_be_at,
+    try_write_i16_le_at,
+    try_write_i16_be_at,
+    |v| v as i64,
+    |raw| raw as i16
+);
+impl_field_primitive!(
+    u32,
+    read_u32_le_at,
+    read_u32_be_at,
+    write_u32_le_at,
+    write_u32_be_at,
+    try_read_u32_le_at,
+    try_read_u32_be_at,
+    try_write_u32_le_at,
+    try_write_u32_be_at,
+    |v| v as i64,
+    |raw| raw as u32
+);
+impl_field_primitive!(
+    i32,
+    read_i32_le_at,
+    read_i32_be_at,
+    write_i32_le_at,
+    write_i32_be_at,
+    try_read_i32_le_at,
+    try_read_i32_be_at,
+    try_write_i32_le_at,
+    try_write_i32_be_at,
+    |v| v as i64,
+    |raw| raw as i32
+);
+
+/// Declarative descriptor for one named value inside a block: its byte
+/// offset, [`Endian`]ness, and an optional fixed-point scale.
+///
+/// Meant to replace hand-coded offsets like `slice.write_u16_le_at(6, 768)
+/// // pid_p: 3.0 scaled by 256` with a named, reusable constant. With
+/// [`Self::with_scale`], [`Self::read`] divides the stored raw value by the
+/// scale and [`Self::write`] multiplies by it, so callers work in real
+/// units while the wire format stays a plain integer. Bounds checking is
+/// inherited from the underlying [`RWSlice`] accessor, which panics the
+/// same as `read_u16_le_at`/`write_u16_le_at` do today.
+#[derive(Debug)]
+pub struct Field<T> {
+    offset: usize,
+    endian: Endian,
+    scale: Option<i32>,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Clone for Field<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Field<T> {}
+
+impl<T: FieldPrimitive> Field<T> {
+    /// Describes a field at `offset` with no scaling.
+    pub const fn new(offset: usize, endian: Endian) -> Self {
+        Self {
+            offset,
+            endian,
+            scale: None,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Scales this field's value by `scale`: [`Self::read`] divides the
+    /// stored raw value by `scale`, [`Self::write`] multiplies by it.
+    pub const fn with_scale(mut self, scale: i32) -> Self {
+        self.scale = Some(scale);
+        self
+    }
+
+    /// Reads this field's value out of `slice`, applying the scale if set.
+    ///
+    /// # Panics
+    /// Panics if the field's offset/size exceeds `slice`'s bounds, the
+    /// same as the underlying `read_*_at` accessor.
+    pub fn read(&self, slice: &RWSlice<'_>) -> T {
+        let raw = T::read_at(slice, self.offset, self.endian);
+        match self.scale {
+            Some(scale) if scale != 0 => T::from_i64(raw.to_i64() / scale as i64),
+            _ => raw,
+        }
+    }
+
+    /// Writes `value` to this field in `slice`, applying the scale if set.
+    ///
+    /// # Panics
+    /// Panics if the field's offset/size exceeds `slice`'s bounds, the
+    /// same as the underlying `write_*_at` accessor.
+    pub fn write(&self, slice: &mut RWSlice<'_>, value: T) {
+        let raw = match self.scale {
+            Some(scale) if scale != 0 => T::from_i64(value.to_i64() * scale as i64),
+            _ => value,
+        };
+        T::write_at(slice, self.offset, self.endian, raw);
+    }
+
+    /// Reads this field's value out of `slice`, applying the scale if set.
+    ///
+    /// Returns `Err(ShadowError::OutOfBounds)` instead of panicking if the
+    /// field's offset/size exceeds `slice`'s bounds.
+    pub fn try_read(&self, slice: &RWSlice<'_>) -> Result<T, ShadowError> {
+        let raw =
+            T::try_read_at(slice, self.offset, self.endian).ok_or(ShadowError::OutOfBounds)?;
+        Ok(match self.scale {
+            Some(scale) if scale != 0 => T::from_i64(raw.to_i64() / scale as i64),
+            _ => raw,
+        })
+    }
+
+    /// Writes `value` to this field in `slice`, applying the scale if set.
+    ///
+    /// Returns `Err(ShadowError::OutOfBounds)` instead of panicking if the
+    /// field's offset/size exceeds `slice`'s bounds.
+    pub fn try_write(&self, slice: &mut RWSlice<'_>, value: T) -> Result<(), ShadowError> {
+        let raw = match self.scale {
+            Some(scale) if scale != 0 => T::from_i64(value.to_i64() * scale as i64),
+            _ => value,
+        };
+        T::try_write_at(slice, self.offset, self.endian, raw).ok_or(ShadowError::OutOfBounds)
+    }
+}
+
+/// Bundles an [`RWSlice`] with a caller-supplied field layout `L`, handed
+/// to the closure passed to
+/// [`HostView::with_fields`](crate::shadow::HostView::with_fields).
+///
+/// `layout` stays a plain `&L` field rather than a method, so a layout of
+/// `Field<T>` constants (each `Copy`) can be read out and passed to
+/// [`Self::read`]/[`Self::write`] without fighting the borrow checker over
+/// a simultaneous mutable self-borrow.
+pub struct FieldCursor<'s, 'l, L> {
+    /// The field layout this cursor was opened with.
+    pub layout: &'l L,
+    slice: RWSlice<'s>,
+}
+
+impl<'s, 'l, L> FieldCursor<'s, 'l, L> {
+    pub(crate) fn new(layout: &'l L, slice: RWSlice<'s>) -> Self {
+        Self { layout, slice }
+    }
+
+    /// Reads `field`'s value out of the underlying slice.
+    pub fn read<T: FieldPrimitive>(&self, field: &Field<T>) -> T {
+        field.read(&self.slice)
+    }
+
+    /// Writes `value` to `field` in the underlying slice.
+    pub fn write<T: FieldPrimitive>(&mut self, field: &Field<T>, value: T) {
+        field.write(&mut self.slice, value)
+    }
+
+    /// Tries to read `field`'s value out of the underlying slice, returning
+    /// `Err(ShadowError::OutOfBounds)` instead of panicking on overrun.
+    pub fn try_read<T: FieldPrimitive>(&self, field: &Field<T>) -> Result<T, ShadowError> {
+        field.try_read(&self.slice)
+    }
+
+    /// Tries to write `value` to `field` in the underlying slice, returning
+    /// `Err(ShadowError::OutOfBounds)` instead of panicking on overrun.
+    pub fn try_write<T: FieldPrimitive>(
+        &mut self,
+        field: &Field<T>,
+        value: T,
+    ) -> Result<(), ShadowError> {
+        field.try_write(&mut self.slice, value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct MotorConfigLayout {
+        max_speed: Field<u16>,
+        pid_p: Field<u16>,
+        temperature: Field<i16>,
+    }
+
+    static LAYOUT: MotorConfigLayout = MotorConfigLayout {
+        max_speed: Field::new(0, Endian::Little),
+        pid_p: Field::new(2, Endian::Little).with_scale(256),
+        temperature: Field::new(4, Endian::Big),
+    };
+
+    #[test]
+    fn field_roundtrips_without_scale() {
+        let mut data = [0u8; 8];
+        let mut slice = RWSlice::new(&mut data);
+
+        LAYOUT.max_speed.write(&mut slice, 1500);
+        assert_eq!(LAYOUT.max_speed.read(&slice), 1500);
+    }
+
+    #[test]
+    fn field_applies_fixed_point_scale() {
+        let mut data = [0u8; 8];
+        let mut slice = RWSlice::new(&mut data);
+
+        // 3.0 stored as 768 raw (3.0 * 256).
+        LAYOUT.pid_p.write(&mut slice, 3);
+        assert_eq!(slice.read_u16_le_at(2), 768);
+        assert_eq!(LAYOUT.pid_p.read(&slice), 3);
+    }
+
+    #[test]
+    fn field_honors_endianness() {
+        let mut data = [0u8; 8];
+        let mut slice = RWSlice::new(&mut data);
+
+        LAYOUT.temperature.write(&mut slice, -10);
+        assert_eq!(slice.read_i16_be_at(4), -10);
+        assert_eq!(LAYOUT.temperature.read(&slice), -10);
+    }
+
+    #[test]
+    fn field_cursor_reads_and_writes_by_name() {
+        let mut data = [0u8; 8];
+        let slice = RWSlice::new(&mut data);
+        let mut cursor = FieldCursor::new(&LAYOUT, slice);
+
+        cursor.write(&cursor.layout.max_speed, 256);
+        cursor.write(&cursor.layout.pid_p, 3);
+        cursor.write(&cursor.layout.temperature, 25);
+
+        assert_eq!(cursor.read(&cursor.layout.max_speed), 256);
+        assert_eq!(cursor.read(&cursor.layout.pid_p), 3);
+        assert_eq!(cursor.read(&cursor.layout.temperature), 25);
+    }
+
+    #[test]
+    fn field_try_read_write_roundtrips_in_bounds() {
+        let mut data = [0u8; 8];
+        let mut slice = RWSlice::new(&mut data);
+
+        LAYOUT.max_speed.try_write(&mut slice, 1500).unwrap();
+        assert_eq!(LAYOUT.max_speed.try_read(&slice).unwrap(), 1500);
+    }
+
+    #[test]
+    fn field_try_read_write_reports_out_of_bounds() {
+        let mut data = [0u8; 2];
+        let mut slice = RWSlice::new(&mut data);
+
+        assert_eq!(
+            LAYOUT.temperature.try_write(&mut slice, 1),
+            Err(ShadowError::OutOfBounds)
+        );
+        assert_eq!(
+            LAYOUT.temperature.try_read(&slice),
+            Err(ShadowError::OutOfBounds)
+        );
+    }
+
+    #[test]
+    fn field_cursor_try_read_write_by_name() {
+        let mut data = [0u8; 8];
+        let slice = RWSlice::new(&mut data);
+        let mut cursor = FieldCursor::new(&LAYOUT, slice);
+
+        cursor.try_write(&cursor.layout.pid_p, 3).unwrap();
+        assert_eq!(cursor.try_read(&cursor.layout.pid_p).unwrap(), 3);
+    }
+}
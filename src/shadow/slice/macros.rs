@@ -10,6 +10,17 @@ macro_rules! impl_read_primitive {
         pub fn read_u8_at(&self, offset: usize) -> u8 {
             self.0[offset]
         }
+
+        /// Reads a `u8` at the given offset, or returns
+        /// [`ShadowError::UnexpectedEof`](crate::shadow::ShadowError::UnexpectedEof)
+        /// instead of panicking if `offset >= len()`.
+        #[inline]
+        pub fn read_u8_exact_at(&self, offset: usize) -> Result<u8, crate::shadow::ShadowError> {
+            self.0
+                .get(offset)
+                .copied()
+                .ok_or(crate::shadow::ShadowError::UnexpectedEof)
+        }
     };
     (i8) => {
         /// Reads an `i8` at the given offset.
@@ -20,6 +31,17 @@ macro_rules! impl_read_primitive {
         pub fn read_i8_at(&self, offset: usize) -> i8 {
             self.0[offset] as i8
         }
+
+        /// Reads an `i8` at the given offset, or returns
+        /// [`ShadowError::UnexpectedEof`](crate::shadow::ShadowError::UnexpectedEof)
+        /// instead of panicking if `offset >= len()`.
+        #[inline]
+        pub fn read_i8_exact_at(&self, offset: usize) -> Result<i8, crate::shadow::ShadowError> {
+            self.0
+                .get(offset)
+                .map(|&b| b as i8)
+                .ok_or(crate::shadow::ShadowError::UnexpectedEof)
+        }
     };
     // Multi-byte types - le/be variants
     ($type:ty, $size:literal) => {
@@ -51,6 +73,54 @@ macro_rules! impl_read_primitive {
                 );
                 <$type>::from_be_bytes(self.0[offset..offset + $size].try_into().unwrap())
             }
+
+            #[doc = "Reads a little-endian `" $type "` at the given offset, or returns"]
+            #[doc = "[`ShadowError::UnexpectedEof`](crate::shadow::ShadowError::UnexpectedEof)"]
+            #[doc = "instead of panicking if `offset + " $size " > len()`."]
+            #[inline]
+            pub fn [<read_ $type _le_exact_at>](
+                &self,
+                offset: usize,
+            ) -> Result<$type, crate::shadow::ShadowError> {
+                self.0
+                    .get(offset..offset + $size)
+                    .and_then(|slice| slice.try_into().ok())
+                    .map(<$type>::from_le_bytes)
+                    .ok_or(crate::shadow::ShadowError::UnexpectedEof)
+            }
+
+            #[doc = "Reads a big-endian `" $type "` at the given offset, or returns"]
+            #[doc = "[`ShadowError::UnexpectedEof`](crate::shadow::ShadowError::UnexpectedEof)"]
+            #[doc = "instead of panicking if `offset + " $size " > len()`."]
+            #[inline]
+            pub fn [<read_ $type _be_exact_at>](
+                &self,
+                offset: usize,
+            ) -> Result<$type, crate::shadow::ShadowError> {
+                self.0
+                    .get(offset..offset + $size)
+                    .and_then(|slice| slice.try_into().ok())
+                    .map(<$type>::from_be_bytes)
+                    .ok_or(crate::shadow::ShadowError::UnexpectedEof)
+            }
+
+            #[doc = "Reads a `" $type "` at the given offset, using `endian`"]
+            #[doc = "to pick the byte order at runtime rather than baking it"]
+            #[doc = "into the call site."]
+            #[doc = ""]
+            #[doc = "# Panics"]
+            #[doc = "Panics if `offset + " $size " > len()`."]
+            #[inline]
+            pub fn [<read_ $type _at>](
+                &self,
+                offset: usize,
+                endian: crate::shadow::slice::Endian,
+            ) -> $type {
+                match endian {
+                    crate::shadow::slice::Endian::Little => self.[<read_ $type _le_at>](offset),
+                    crate::shadow::slice::Endian::Big => self.[<read_ $type _be_at>](offset),
+                }
+            }
         }
     };
 }
@@ -98,6 +168,23 @@ macro_rules! impl_try_read_primitive {
                     .and_then(|slice| slice.try_into().ok())
                     .map(<$type>::from_be_bytes)
             }
+
+            #[doc = "Tries to read a `" $type "` at the given offset, using"]
+            #[doc = "`endian` to pick the byte order at runtime rather than"]
+            #[doc = "baking it into the call site."]
+            #[doc = ""]
+            #[doc = "Returns `None` if `offset + " $size " > len()`."]
+            #[inline]
+            pub fn [<try_read_ $type _at>](
+                &self,
+                offset: usize,
+                endian: crate::shadow::slice::Endian,
+            ) -> Option<$type> {
+                match endian {
+                    crate::shadow::slice::Endian::Little => self.[<try_read_ $type _le_at>](offset),
+                    crate::shadow::slice::Endian::Big => self.[<try_read_ $type _be_at>](offset),
+                }
+            }
         }
     };
 }
@@ -111,6 +198,10 @@ macro_rules! impl_read_primitives {
         impl_read_primitive!(i16, 2);
         impl_read_primitive!(u32, 4);
         impl_read_primitive!(i32, 4);
+        impl_read_primitive!(u64, 8);
+        impl_read_primitive!(i64, 8);
+        impl_read_primitive!(f32, 4);
+        impl_read_primitive!(f64, 8);
     };
 }
 
@@ -123,6 +214,10 @@ macro_rules! impl_try_read_primitives {
         impl_try_read_primitive!(i16, 2);
         impl_try_read_primitive!(u32, 4);
         impl_try_read_primitive!(i32, 4);
+        impl_try_read_primitive!(u64, 8);
+        impl_try_read_primitive!(i64, 8);
+        impl_try_read_primitive!(f32, 4);
+        impl_try_read_primitive!(f64, 8);
     };
 }
 
@@ -138,6 +233,23 @@ macro_rules! impl_write_primitive {
         pub fn write_u8_at(&mut self, offset: usize, value: u8) {
             self.0[offset] = value;
         }
+
+        /// Writes a `u8` at the given offset, or returns
+        /// [`ShadowError::UnexpectedEof`](crate::shadow::ShadowError::UnexpectedEof)
+        /// instead of panicking if `offset >= len()`.
+        #[inline]
+        pub fn write_u8_exact_at(
+            &mut self,
+            offset: usize,
+            value: u8,
+        ) -> Result<(), crate::shadow::ShadowError> {
+            let slot = self
+                .0
+                .get_mut(offset)
+                .ok_or(crate::shadow::ShadowError::UnexpectedEof)?;
+            *slot = value;
+            Ok(())
+        }
     };
     (i8) => {
         /// Writes an `i8` at the given offset.
@@ -148,6 +260,23 @@ macro_rules! impl_write_primitive {
         pub fn write_i8_at(&mut self, offset: usize, value: i8) {
             self.0[offset] = value as u8;
         }
+
+        /// Writes an `i8` at the given offset, or returns
+        /// [`ShadowError::UnexpectedEof`](crate::shadow::ShadowError::UnexpectedEof)
+        /// instead of panicking if `offset >= len()`.
+        #[inline]
+        pub fn write_i8_exact_at(
+            &mut self,
+            offset: usize,
+            value: i8,
+        ) -> Result<(), crate::shadow::ShadowError> {
+            let slot = self
+                .0
+                .get_mut(offset)
+                .ok_or(crate::shadow::ShadowError::UnexpectedEof)?;
+            *slot = value as u8;
+            Ok(())
+        }
     };
     // Multi-byte types - le/be variants
     ($type:ty, $size:literal) => {
@@ -179,6 +308,57 @@ macro_rules! impl_write_primitive {
                 );
                 self.0[offset..offset + $size].copy_from_slice(&value.to_be_bytes());
             }
+
+            #[doc = "Writes a little-endian `" $type "` at the given offset, or returns"]
+            #[doc = "[`ShadowError::UnexpectedEof`](crate::shadow::ShadowError::UnexpectedEof)"]
+            #[doc = "instead of panicking if `offset + " $size " > len()`."]
+            #[inline]
+            pub fn [<write_ $type _le_exact_at>](
+                &mut self,
+                offset: usize,
+                value: $type,
+            ) -> Result<(), crate::shadow::ShadowError> {
+                if offset + $size > self.0.len() {
+                    return Err(crate::shadow::ShadowError::UnexpectedEof);
+                }
+                self.0[offset..offset + $size].copy_from_slice(&value.to_le_bytes());
+                Ok(())
+            }
+
+            #[doc = "Writes a big-endian `" $type "` at the given offset, or returns"]
+            #[doc = "[`ShadowError::UnexpectedEof`](crate::shadow::ShadowError::UnexpectedEof)"]
+            #[doc = "instead of panicking if `offset + " $size " > len()`."]
+            #[inline]
+            pub fn [<write_ $type _be_exact_at>](
+                &mut self,
+                offset: usize,
+                value: $type,
+            ) -> Result<(), crate::shadow::ShadowError> {
+                if offset + $size > self.0.len() {
+                    return Err(crate::shadow::ShadowError::UnexpectedEof);
+                }
+                self.0[offset..offset + $size].copy_from_slice(&value.to_be_bytes());
+                Ok(())
+            }
+
+            #[doc = "Writes a `" $type "` at the given offset, using `endian`"]
+            #[doc = "to pick the byte order at runtime rather than baking it"]
+            #[doc = "into the call site."]
+            #[doc = ""]
+            #[doc = "# Panics"]
+            #[doc = "Panics if `offset + " $size " > len()`."]
+            #[inline]
+            pub fn [<write_ $type _at>](
+                &mut self,
+                offset: usize,
+                endian: crate::shadow::slice::Endian,
+                value: $type,
+            ) {
+                match endian {
+                    crate::shadow::slice::Endian::Little => self.[<write_ $type _le_at>](offset, value),
+                    crate::shadow::slice::Endian::Big => self.[<write_ $type _be_at>](offset, value),
+                }
+            }
         }
     };
 }
@@ -192,6 +372,10 @@ macro_rules! impl_write_primitives {
         impl_write_primitive!(i16, 2);
         impl_write_primitive!(u32, 4);
         impl_write_primitive!(i32, 4);
+        impl_write_primitive!(u64, 8);
+        impl_write_primitive!(i64, 8);
+        impl_write_primitive!(f32, 4);
+        impl_write_primitive!(f64, 8);
     };
 }
 
@@ -242,6 +426,24 @@ macro_rules! impl_try_write_primitive {
                 self.0[offset..offset + $size].copy_from_slice(&value.to_be_bytes());
                 Some(())
             }
+
+            #[doc = "Tries to write a `" $type "` at the given offset, using"]
+            #[doc = "`endian` to pick the byte order at runtime rather than"]
+            #[doc = "baking it into the call site."]
+            #[doc = ""]
+            #[doc = "Returns `None` if `offset + " $size " > len()`."]
+            #[inline]
+            pub fn [<try_write_ $type _at>](
+                &mut self,
+                offset: usize,
+                endian: crate::shadow::slice::Endian,
+                value: $type,
+            ) -> Option<()> {
+                match endian {
+                    crate::shadow::slice::Endian::Little => self.[<try_write_ $type _le_at>](offset, value),
+                    crate::shadow::slice::Endian::Big => self.[<try_write_ $type _be_at>](offset, value),
+                }
+            }
         }
     };
 }
@@ -255,6 +457,10 @@ macro_rules! impl_try_write_primitives {
         impl_try_write_primitive!(i16, 2);
         impl_try_write_primitive!(u32, 4);
         impl_try_write_primitive!(i32, 4);
+        impl_try_write_primitive!(u64, 8);
+        impl_try_write_primitive!(i64, 8);
+        impl_try_write_primitive!(f32, 4);
+        impl_try_write_primitive!(f64, 8);
     };
 }
 
@@ -309,8 +515,110 @@ macro_rules! impl_slice_ro {
             Some(())
         }
 
+        /// Fills `dest` with the bytes starting at `offset`.
+        ///
+        /// Returns
+        /// [`ShadowError::UnexpectedEof`](crate::shadow::ShadowError::UnexpectedEof)
+        /// instead of panicking if `offset + dest.len() > len()`, following
+        /// the semantics of `Read::read_exact`.
+        #[inline]
+        pub fn read_exact_at(
+            &self,
+            offset: usize,
+            dest: &mut [u8],
+        ) -> Result<(), crate::shadow::ShadowError> {
+            let end = offset
+                .checked_add(dest.len())
+                .ok_or(crate::shadow::ShadowError::UnexpectedEof)?;
+            if end > self.0.len() {
+                return Err(crate::shadow::ShadowError::UnexpectedEof);
+            }
+            dest.copy_from_slice(&self.0[offset..end]);
+            Ok(())
+        }
+
         impl_read_primitives!();
         impl_try_read_primitives!();
+
+        /// Reads `bit_len` bits starting at `bit_offset`, counting bits
+        /// little-endian: bit 0 is the LSB of the first byte, and bit
+        /// numbering increases toward the MSB of later bytes. The result is
+        /// right-aligned in the returned `u32`.
+        ///
+        /// # Panics
+        /// Panics if `bit_len > 32` or the field runs past the end of the
+        /// slice.
+        pub fn read_bits_le_at(&self, bit_offset: usize, bit_len: u32) -> u32 {
+            assert!(bit_len <= 32, "bit_len {} exceeds 32", bit_len);
+            let total_bits = self.0.len() * 8;
+            assert!(
+                bit_offset + bit_len as usize <= total_bits,
+                "read out of bounds: bit_offset {} + bit_len {} > {} bits",
+                bit_offset,
+                bit_len,
+                total_bits
+            );
+            if bit_len == 0 {
+                return 0;
+            }
+
+            let start_byte = bit_offset / 8;
+            let start_bit = bit_offset % 8;
+            let end_byte = (bit_offset + bit_len as usize - 1) / 8;
+
+            let mut acc: u64 = 0;
+            for (i, &byte) in self.0[start_byte..=end_byte].iter().enumerate() {
+                acc |= (byte as u64) << (i * 8);
+            }
+
+            let mask = if bit_len == 32 {
+                u64::from(u32::MAX)
+            } else {
+                (1u64 << bit_len) - 1
+            };
+            ((acc >> start_bit) & mask) as u32
+        }
+
+        /// Reads `bit_len` bits starting at `bit_offset`, counting bits
+        /// big-endian: bit 0 is the MSB of the first byte, and bit
+        /// numbering increases toward the LSB of later bytes — the usual
+        /// numbering for protocol bitfields. The result is right-aligned in
+        /// the returned `u32`.
+        ///
+        /// # Panics
+        /// Panics if `bit_len > 32` or the field runs past the end of the
+        /// slice.
+        pub fn read_bits_be_at(&self, bit_offset: usize, bit_len: u32) -> u32 {
+            assert!(bit_len <= 32, "bit_len {} exceeds 32", bit_len);
+            let total_bits = self.0.len() * 8;
+            assert!(
+                bit_offset + bit_len as usize <= total_bits,
+                "read out of bounds: bit_offset {} + bit_len {} > {} bits",
+                bit_offset,
+                bit_len,
+                total_bits
+            );
+            if bit_len == 0 {
+                return 0;
+            }
+
+            let start_byte = bit_offset / 8;
+            let end_byte = (bit_offset + bit_len as usize - 1) / 8;
+
+            let mut acc: u64 = 0;
+            for &byte in &self.0[start_byte..=end_byte] {
+                acc = (acc << 8) | byte as u64;
+            }
+
+            let span_bits = (end_byte - start_byte + 1) * 8;
+            let shift = span_bits - (bit_offset - start_byte * 8) - bit_len as usize;
+            let mask = if bit_len == 32 {
+                u64::from(u32::MAX)
+            } else {
+                (1u64 << bit_len) - 1
+            };
+            ((acc >> shift) & mask) as u32
+        }
     };
 }
 
@@ -376,8 +684,123 @@ macro_rules! impl_slice_wo {
             Some(())
         }
 
+        /// Writes `src` starting at `offset`.
+        ///
+        /// Returns
+        /// [`ShadowError::UnexpectedEof`](crate::shadow::ShadowError::UnexpectedEof)
+        /// instead of panicking if `offset + src.len() > len()`, following
+        /// the semantics of `Read::read_exact`.
+        #[inline]
+        pub fn write_exact_at(
+            &mut self,
+            offset: usize,
+            src: &[u8],
+        ) -> Result<(), crate::shadow::ShadowError> {
+            let end = offset
+                .checked_add(src.len())
+                .ok_or(crate::shadow::ShadowError::UnexpectedEof)?;
+            if end > self.0.len() {
+                return Err(crate::shadow::ShadowError::UnexpectedEof);
+            }
+            self.0[offset..end].copy_from_slice(src);
+            Ok(())
+        }
+
         impl_write_primitives!();
         impl_try_write_primitives!();
+
+        /// Writes the low `bit_len` bits of `value` starting at
+        /// `bit_offset`, counting bits little-endian as in
+        /// [`read_bits_le_at`](Self::read_bits_le_at). Masks and shifts the
+        /// value into place a byte at a time, so bits outside
+        /// `[bit_offset, bit_offset + bit_len)` are left untouched —
+        /// neighboring fields packed into the same bytes survive.
+        ///
+        /// # Panics
+        /// Panics if `bit_len > 32` or the field runs past the end of the
+        /// slice.
+        pub fn write_bits_le_at(&mut self, bit_offset: usize, bit_len: u32, value: u32) {
+            assert!(bit_len <= 32, "bit_len {} exceeds 32", bit_len);
+            let total_bits = self.0.len() * 8;
+            assert!(
+                bit_offset + bit_len as usize <= total_bits,
+                "write out of bounds: bit_offset {} + bit_len {} > {} bits",
+                bit_offset,
+                bit_len,
+                total_bits
+            );
+            if bit_len == 0 {
+                return;
+            }
+
+            let mask = if bit_len == 32 {
+                u32::MAX
+            } else {
+                (1u32 << bit_len) - 1
+            };
+            let value = u64::from(value & mask);
+
+            let start_byte = bit_offset / 8;
+            let start_bit = bit_offset % 8;
+            let end_byte = (bit_offset + bit_len as usize - 1) / 8;
+
+            let mask64 = u64::from(mask) << start_bit;
+            let value64 = value << start_bit;
+
+            for k in start_byte..=end_byte {
+                let shift = (k - start_byte) * 8;
+                let byte_mask = ((mask64 >> shift) & 0xFF) as u8;
+                let byte_value = ((value64 >> shift) & 0xFF) as u8;
+                self.0[k] = (self.0[k] & !byte_mask) | byte_value;
+            }
+        }
+
+        /// Writes the low `bit_len` bits of `value` starting at
+        /// `bit_offset`, counting bits big-endian as in
+        /// [`read_bits_be_at`](Self::read_bits_be_at). Masks and shifts the
+        /// value into place a byte at a time, so bits outside
+        /// `[bit_offset, bit_offset + bit_len)` are left untouched —
+        /// neighboring fields packed into the same bytes survive.
+        ///
+        /// # Panics
+        /// Panics if `bit_len > 32` or the field runs past the end of the
+        /// slice.
+        pub fn write_bits_be_at(&mut self, bit_offset: usize, bit_len: u32, value: u32) {
+            assert!(bit_len <= 32, "bit_len {} exceeds 32", bit_len);
+            let total_bits = self.0.len() * 8;
+            assert!(
+                bit_offset + bit_len as usize <= total_bits,
+                "write out of bounds: bit_offset {} + bit_len {} > {} bits",
+                bit_offset,
+                bit_len,
+                total_bits
+            );
+            if bit_len == 0 {
+                return;
+            }
+
+            let mask = if bit_len == 32 {
+                u32::MAX
+            } else {
+                (1u32 << bit_len) - 1
+            };
+            let value = u64::from(value & mask);
+
+            let start_byte = bit_offset / 8;
+            let end_byte = (bit_offset + bit_len as usize - 1) / 8;
+            let span_bits = (end_byte - start_byte + 1) * 8;
+            let shift = span_bits - (bit_offset - start_byte * 8) - bit_len as usize;
+
+            let mask64 = u64::from(mask) << shift;
+            let value64 = value << shift;
+
+            for k in start_byte..=end_byte {
+                let byte_shift = (end_byte - k) * 8;
+                let byte_mask = ((mask64 >> byte_shift) & 0xFF) as u8;
+                let byte_value = ((value64 >> byte_shift) & 0xFF) as u8;
+                self.0[k] = (self.0[k] & !byte_mask) | byte_value;
+            }
+        }
     };
 }
 
@@ -0,0 +1,12 @@
+mod cursor;
+mod field;
+mod macros;
+mod ro;
+mod rw;
+mod wo;
+
+pub use cursor::{Reader, Writer};
+pub use field::{Endian, Field, FieldCursor, FieldPrimitive};
+pub use ro::ROSlice;
+pub use rw::RWSlice;
+pub use wo::WOSlice;
@@ -2,6 +2,7 @@ use super::macros::{
     impl_read_primitive, impl_read_primitives, impl_slice_common, impl_slice_ro,
     impl_try_read_primitive, impl_try_read_primitives,
 };
+use super::Reader;
 
 /// Read-only slice wrapper.
 ///
@@ -19,11 +20,20 @@ impl<'a> ROSlice<'a> {
 
     impl_slice_common!();
     impl_slice_ro!();
+
+    /// Opens a sequential cursor over this slice, starting at offset 0. See
+    /// [`Reader`] for the `get_*`/`try_get_*` methods it provides.
+    #[inline]
+    pub fn reader(self) -> Reader<'a> {
+        Reader::new(self)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::shadow::slice::Endian;
+    use crate::shadow::ShadowError;
 
     #[test]
     fn ro_slice_operations() {
@@ -45,6 +55,67 @@ mod tests {
         assert_eq!(slice.read_u8_at(0), 0x78);
     }
 
+    #[test]
+    fn read_64_bit_and_float_primitives() {
+        let data = [0x00, 0x00, 0x80, 0x3F, 0x11, 0x22, 0x33, 0x44];
+        let slice = ROSlice::new(&data);
+
+        assert_eq!(slice.read_f32_le_at(0), 1.0f32);
+        assert_eq!(slice.read_u64_le_at(0), 0x4433_2211_3F80_0000);
+        assert_eq!(slice.try_read_u64_le_at(1), None);
+    }
+
+    #[test]
+    fn read_exact_at_fills_dest_or_reports_unexpected_eof() {
+        let data = [0x78, 0x56, 0x34, 0x12];
+        let slice = ROSlice::new(&data);
+
+        let mut dest = [0u8; 2];
+        assert_eq!(slice.read_exact_at(1, &mut dest), Ok(()));
+        assert_eq!(dest, [0x56, 0x34]);
+
+        let mut dest = [0u8; 4];
+        assert_eq!(
+            slice.read_exact_at(1, &mut dest),
+            Err(ShadowError::UnexpectedEof)
+        );
+    }
+
+    #[test]
+    fn read_primitive_exact_at_reports_unexpected_eof() {
+        let data = [0x78, 0x56, 0x34, 0x12];
+        let slice = ROSlice::new(&data);
+
+        assert_eq!(slice.read_u8_exact_at(0), Ok(0x78));
+        assert_eq!(slice.read_u32_le_exact_at(0), Ok(0x12345678));
+        assert_eq!(
+            slice.read_u32_le_exact_at(1),
+            Err(ShadowError::UnexpectedEof)
+        );
+        assert_eq!(slice.read_u8_exact_at(4), Err(ShadowError::UnexpectedEof));
+    }
+
+    #[test]
+    fn read_u32_at_dispatches_on_runtime_endian() {
+        let data = [0x78, 0x56, 0x34, 0x12];
+        let slice = ROSlice::new(&data);
+
+        assert_eq!(slice.read_u32_at(0, Endian::Little), 0x12345678);
+        assert_eq!(slice.read_u32_at(0, Endian::Big), 0x78563412);
+        assert_eq!(slice.try_read_u32_at(1, Endian::Little), None);
+        assert_eq!(slice.try_read_u32_at(0, Endian::Big), Some(0x78563412));
+    }
+
+    #[test]
+    fn read_f64_le_at_round_trips() {
+        let mut data = [0u8; 8];
+        data.copy_from_slice(&1.5f64.to_le_bytes());
+        let slice = ROSlice::new(&data);
+
+        assert_eq!(slice.read_f64_le_at(0), 1.5f64);
+        assert_eq!(slice.try_read_i64_be_at(0), Some(i64::from_be_bytes(data)));
+    }
+
     #[test]
     #[should_panic(expected = "read out of bounds")]
     fn ro_slice_read_u32_out_of_bounds() {
@@ -94,4 +165,48 @@ mod tests {
         let mut dest = [0u8; 4];
         assert_eq!(slice.try_copy_to_slice_at(1, &mut dest), None);
     }
+
+    #[test]
+    fn read_bits_le_at_extracts_packed_fields() {
+        // enable: bit 0, mode: bits 1-2, prescaler: bits 3-7
+        let data = [0b1011_0101u8];
+        let slice = ROSlice::new(&data);
+
+        assert_eq!(slice.read_bits_le_at(0, 1), 1);
+        assert_eq!(slice.read_bits_le_at(1, 2), 0b10);
+        assert_eq!(slice.read_bits_le_at(3, 5), 0b10110);
+    }
+
+    #[test]
+    fn read_bits_le_at_straddles_byte_boundary() {
+        let data = [0b1111_0101u8, 0b0000_0011u8];
+        let slice = ROSlice::new(&data);
+
+        assert_eq!(slice.read_bits_le_at(6, 4), 0b1111);
+    }
+
+    #[test]
+    fn read_bits_be_at_extracts_msb_first_fields() {
+        let data = [0b1011_0110u8];
+        let slice = ROSlice::new(&data);
+
+        assert_eq!(slice.read_bits_be_at(0, 3), 0b101);
+        assert_eq!(slice.read_bits_be_at(3, 5), 0b10110);
+    }
+
+    #[test]
+    #[should_panic(expected = "read out of bounds")]
+    fn read_bits_out_of_bounds() {
+        let data = [0u8; 1];
+        let slice = ROSlice::new(&data);
+        slice.read_bits_le_at(6, 4);
+    }
+
+    #[test]
+    #[should_panic(expected = "exceeds 32")]
+    fn read_bits_len_too_large() {
+        let data = [0u8; 8];
+        let slice = ROSlice::new(&data);
+        slice.read_bits_le_at(0, 33);
+    }
 }
@@ -1,7 +1,9 @@
 use super::macros::{
     impl_read_primitive, impl_read_primitives, impl_slice_common, impl_slice_ro, impl_slice_wo,
-    impl_write_primitive, impl_write_primitives,
+    impl_try_read_primitive, impl_try_read_primitives, impl_try_write_primitive,
+    impl_try_write_primitives, impl_write_primitive, impl_write_primitives,
 };
+use super::Writer;
 
 /// Read-write slice wrapper.
 ///
@@ -19,11 +21,20 @@ impl<'a> RWSlice<'a> {
     impl_slice_common!();
     impl_slice_ro!();
     impl_slice_wo!();
+
+    /// Opens a sequential cursor over this slice, starting at offset 0. See
+    /// [`Writer`] for the `put_*`/`try_put_*` methods it provides.
+    #[inline]
+    pub fn writer(self) -> Writer<'a> {
+        Writer::new(self)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::shadow::slice::Endian;
+    use crate::shadow::ShadowError;
 
     #[test]
     fn read_write_roundtrip() {
@@ -34,6 +45,67 @@ mod tests {
         assert_eq!(slice.read_u32_le_at(0), 0x12345678);
     }
 
+    #[test]
+    fn write_64_bit_and_float_primitives() {
+        let mut data = [0u8; 8];
+        let mut slice = RWSlice::new(&mut data);
+
+        slice.write_f64_le_at(0, 1.5f64);
+        assert_eq!(slice.read_f64_le_at(0), 1.5f64);
+
+        slice.write_u64_be_at(0, 0x0102_0304_0506_0708);
+        assert_eq!(slice.read_u64_be_at(0), 0x0102_0304_0506_0708);
+
+        assert_eq!(slice.try_write_i64_le_at(1, -1), None);
+        assert_eq!(slice.try_write_f32_le_at(4, 2.5f32), Some(()));
+        assert_eq!(slice.read_f32_le_at(4), 2.5f32);
+    }
+
+    #[test]
+    fn write_exact_at_fills_src_or_reports_unexpected_eof() {
+        let mut data = [0u8; 4];
+        let mut slice = RWSlice::new(&mut data);
+
+        assert_eq!(slice.write_exact_at(1, &[0x56, 0x34]), Ok(()));
+        assert_eq!(data, [0x00, 0x56, 0x34, 0x00]);
+
+        assert_eq!(
+            RWSlice::new(&mut data).write_exact_at(3, &[0x01, 0x02]),
+            Err(ShadowError::UnexpectedEof)
+        );
+    }
+
+    #[test]
+    fn write_primitive_exact_at_reports_unexpected_eof() {
+        let mut data = [0u8; 4];
+        let mut slice = RWSlice::new(&mut data);
+
+        assert_eq!(slice.write_u32_le_exact_at(0, 0x12345678), Ok(()));
+        assert_eq!(slice.read_u32_le_at(0), 0x12345678);
+        assert_eq!(
+            slice.write_u32_le_exact_at(1, 0),
+            Err(ShadowError::UnexpectedEof)
+        );
+        assert_eq!(
+            slice.write_u8_exact_at(4, 0xFF),
+            Err(ShadowError::UnexpectedEof)
+        );
+    }
+
+    #[test]
+    fn write_u32_at_dispatches_on_runtime_endian() {
+        let mut data = [0u8; 4];
+        let mut slice = RWSlice::new(&mut data);
+
+        slice.write_u32_at(0, Endian::Big, 0x12345678);
+        assert_eq!(slice.read_u32_be_at(0), 0x12345678);
+
+        slice.write_u32_at(0, Endian::Little, 0x12345678);
+        assert_eq!(slice.read_u32_le_at(0), 0x12345678);
+
+        assert_eq!(slice.try_write_u32_at(1, Endian::Little, 0), None);
+    }
+
     #[test]
     fn read_modify_write() {
         let mut data = [0x00, 0x00, 0x00, 0x01];
@@ -51,4 +123,18 @@ mod tests {
         let mut data = [0u8; 4];
         RWSlice::new(&mut data).read_u32_le_at(1);
     }
+
+    #[test]
+    fn bit_field_roundtrip() {
+        let mut data = [0u8; 1];
+        let mut slice = RWSlice::new(&mut data);
+
+        slice.write_bits_le_at(0, 1, 1);
+        slice.write_bits_le_at(1, 2, 0b10);
+        slice.write_bits_le_at(3, 5, 0b10110);
+
+        assert_eq!(slice.read_bits_le_at(0, 1), 1);
+        assert_eq!(slice.read_bits_le_at(1, 2), 0b10);
+        assert_eq!(slice.read_bits_le_at(3, 5), 0b10110);
+    }
 }
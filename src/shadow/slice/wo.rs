@@ -1,5 +1,6 @@
 use super::macros::{
-    impl_slice_common, impl_slice_wo, impl_write_primitive, impl_write_primitives,
+    impl_slice_common, impl_slice_wo, impl_try_write_primitive, impl_try_write_primitives,
+    impl_write_primitive, impl_write_primitives,
 };
 
 /// Write-only slice wrapper.
@@ -90,4 +91,51 @@ mod tests {
         let mut data = [0u8; 4];
         WOSlice::new(&mut data).write_u32_le_at(1, 0);
     }
+
+    #[test]
+    fn write_bits_le_at_packs_fields_without_disturbing_neighbors() {
+        let mut data = [0u8; 2];
+        let mut slice = WOSlice::new(&mut data);
+
+        // enable: bit 0, mode: bits 1-2, prescaler: bits 3-7
+        slice.write_bits_le_at(0, 1, 1);
+        slice.write_bits_le_at(1, 2, 0b10);
+        slice.write_bits_le_at(3, 5, 0b10110);
+        assert_eq!(data[0], 0b1011_0101);
+
+        // A field straddling the byte boundary only touches its own bits.
+        WOSlice::new(&mut data).write_bits_le_at(6, 4, 0b1111);
+        assert_eq!(data, [0b1111_0101, 0b0000_0011]);
+    }
+
+    #[test]
+    fn write_bits_le_at_masks_value_to_bit_len() {
+        let mut data = [0u8; 1];
+        WOSlice::new(&mut data).write_bits_le_at(2, 3, 0xFF);
+        assert_eq!(data[0], 0b0001_1100);
+    }
+
+    #[test]
+    fn write_bits_be_at_packs_fields_msb_first() {
+        let mut data = [0u8; 1];
+        let mut slice = WOSlice::new(&mut data);
+
+        slice.write_bits_be_at(0, 3, 0b101);
+        slice.write_bits_be_at(3, 5, 0b10110);
+        assert_eq!(data[0], 0b101_10110);
+    }
+
+    #[test]
+    #[should_panic]
+    fn write_bits_out_of_bounds() {
+        let mut data = [0u8; 1];
+        WOSlice::new(&mut data).write_bits_le_at(6, 4, 0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn write_bits_len_too_large() {
+        let mut data = [0u8; 8];
+        WOSlice::new(&mut data).write_bits_le_at(0, 33, 0);
+    }
 }
@@ -0,0 +1,317 @@
+//! Write-ahead journal framing for [`HostViewStaged::commit_journaled`].
+//!
+//! Each staged write is framed as `[addr: u16 le][len: u16 le][data…]`,
+//! followed by a commit marker: the same header shape with a sentinel
+//! `len` and a trailing CRC16 over every preceding record. A crash between
+//! two writes either leaves a marker-less (or bad-CRC) journal — discarded
+//! wholesale by [`replay_journal`] — or a complete, verified one that's
+//! replayed in full, giving the staged commit all-or-nothing durability.
+
+use crate::shadow::{
+    backend::TableBackend,
+    persist::PersistTrigger,
+    policy::{AccessPolicy, PersistPolicy},
+    types::StagingBuffer,
+    HostView, ShadowError, WriteResult,
+};
+
+/// `addr(2) + len(2)`.
+const RECORD_HEADER_LEN: usize = 4;
+const CRC_LEN: usize = 2;
+
+/// `len` value marking the commit marker record rather than a data record.
+const COMMIT_MARKER_LEN: u16 = u16::MAX;
+
+/// Incremental CRC-16/MODBUS (poly 0xA001, init 0xFFFF), updated a chunk at
+/// a time so the journal's header and data for one record don't need to be
+/// copied into one contiguous buffer first. See [`crate::shadow::dirty_codec`]'s
+/// `crc16` for the one-shot sibling used to checksum a single frame.
+struct Crc16(u16);
+
+impl Crc16 {
+    fn new() -> Self {
+        Self(0xFFFF)
+    }
+
+    fn update(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= byte as u16;
+            for _ in 0..8 {
+                let mask = (self.0 & 1).wrapping_neg();
+                self.0 = (self.0 >> 1) ^ (0xA001 & mask);
+            }
+        }
+    }
+
+    fn value(&self) -> u16 {
+        self.0
+    }
+}
+
+/// Serializes every entry in `sb` as a journal record to `persist_trigger`,
+/// then appends the commit marker once every record is written.
+pub(crate) fn write_records<PK, PT, SB>(
+    sb: &SB,
+    persist_trigger: &mut PT,
+) -> Result<(), ShadowError>
+where
+    PT: PersistTrigger<PK>,
+    SB: StagingBuffer,
+{
+    let mut crc = Crc16::new();
+    sb.iter_staged(|addr, data| {
+        let mut header = [0u8; RECORD_HEADER_LEN];
+        header[0..2].copy_from_slice(&addr.to_le_bytes());
+        header[2..4].copy_from_slice(&(data.len() as u16).to_le_bytes());
+        crc.update(&header);
+        crc.update(data);
+        persist_trigger.journal_append(&header)?;
+        persist_trigger.journal_append(data)
+    })?;
+
+    let mut marker = [0u8; RECORD_HEADER_LEN];
+    marker[0..2].copy_from_slice(&0u16.to_le_bytes());
+    marker[2..4].copy_from_slice(&COMMIT_MARKER_LEN.to_le_bytes());
+    persist_trigger.journal_append(&marker)?;
+    persist_trigger.journal_append(&crc.value().to_le_bytes())
+}
+
+/// Computes how many bytes [`write_records`] will append to the journal for
+/// `sb`'s currently staged entries, so callers can size their journal
+/// region up front instead of guessing. Equal to every record's
+/// `RECORD_HEADER_LEN + data.len()`, plus the trailing commit marker and
+/// its CRC.
+pub fn journal_bytes_needed<SB>(sb: &SB) -> usize
+where
+    SB: StagingBuffer,
+{
+    let mut total = 0usize;
+    let _ = sb.iter_staged(|_addr, data| {
+        total += RECORD_HEADER_LEN + data.len();
+        Ok(())
+    });
+    total + RECORD_HEADER_LEN + CRC_LEN
+}
+
+/// Scans `journal` for a complete, CRC-valid write-ahead journal and
+/// re-applies every record it contains to `view`, marking each written
+/// block dirty.
+///
+/// Returns `Ok(true)` if a valid journal was found and replayed, or
+/// `Ok(false)` if the commit marker or its CRC is missing/invalid — in
+/// which case nothing in `journal` is applied. Call this once at boot,
+/// before normal host/kernel traffic begins.
+pub fn replay_journal<const TS: usize, const BS: usize, const BC: usize, AP, PP, PT, PK, TB>(
+    view: &mut HostView<'_, TS, BS, BC, AP, PP, PT, PK, TB>,
+    journal: &[u8],
+) -> Result<bool, ShadowError>
+where
+    bitmaps::BitsImpl<BC>: bitmaps::Bits,
+    AP: AccessPolicy,
+    PP: PersistPolicy<PK>,
+    PT: PersistTrigger<PK>,
+    TB: TableBackend<TS>,
+{
+    let mut crc = Crc16::new();
+    let mut offset = 0;
+
+    loop {
+        if offset + RECORD_HEADER_LEN > journal.len() {
+            return Ok(false);
+        }
+
+        let addr = u16::from_le_bytes([journal[offset], journal[offset + 1]]);
+        let len = u16::from_le_bytes([journal[offset + 2], journal[offset + 3]]);
+
+        if len == COMMIT_MARKER_LEN {
+            if offset + RECORD_HEADER_LEN + CRC_LEN > journal.len() {
+                return Ok(false);
+            }
+            let stored_crc = u16::from_le_bytes([
+                journal[offset + RECORD_HEADER_LEN],
+                journal[offset + RECORD_HEADER_LEN + 1],
+            ]);
+            if stored_crc != crc.value() {
+                return Ok(false);
+            }
+
+            apply_records(view, &journal[..offset])?;
+            return Ok(true);
+        }
+
+        let len = len as usize;
+        if offset + RECORD_HEADER_LEN + len > journal.len() {
+            return Ok(false);
+        }
+
+        crc.update(&journal[offset..offset + RECORD_HEADER_LEN + len]);
+        let _ = addr;
+        offset += RECORD_HEADER_LEN + len;
+    }
+}
+
+fn apply_records<const TS: usize, const BS: usize, const BC: usize, AP, PP, PT, PK, TB>(
+    view: &mut HostView<'_, TS, BS, BC, AP, PP, PT, PK, TB>,
+    records: &[u8],
+) -> Result<(), ShadowError>
+where
+    bitmaps::BitsImpl<BC>: bitmaps::Bits,
+    AP: AccessPolicy,
+    PP: PersistPolicy<PK>,
+    PT: PersistTrigger<PK>,
+    TB: TableBackend<TS>,
+{
+    let mut offset = 0;
+    while offset < records.len() {
+        let addr = u16::from_le_bytes([records[offset], records[offset + 1]]);
+        let len = u16::from_le_bytes([records[offset + 2], records[offset + 3]]) as usize;
+        let data = &records[offset + RECORD_HEADER_LEN..offset + RECORD_HEADER_LEN + len];
+
+        view.with_wo_slice(addr, len, |mut slice| {
+            slice.copy_from_slice(data);
+            WriteResult::Dirty(())
+        })?;
+
+        offset += RECORD_HEADER_LEN + len;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shadow::backing::NoBackingStore;
+    use crate::shadow::fault::NoFaultHandler;
+    use crate::shadow::persist::NoPersist;
+    use crate::shadow::policy::{AllowAllPolicy, NoPersistPolicy};
+    use crate::shadow::staged::PatchStagingBuffer;
+    use crate::shadow::table::ShadowTable;
+
+    #[derive(Default)]
+    struct RecordingJournal {
+        bytes: heapless::Vec<u8, 256>,
+    }
+
+    impl PersistTrigger<()> for RecordingJournal {
+        fn push_key(&mut self, _key: ()) {}
+        fn request_persist(&mut self) {}
+        fn journal_append(&mut self, bytes: &[u8]) -> Result<(), ShadowError> {
+            self.bytes
+                .extend_from_slice(bytes)
+                .map_err(|_| ShadowError::StageFull)
+        }
+    }
+
+    fn staged_patch() -> PatchStagingBuffer<64, 8> {
+        let mut sb = PatchStagingBuffer::new();
+        sb.alloc_staged(0, 4, |data| {
+            data.copy_from_slice(&[0xAA, 0xBB, 0xCC, 0xDD]);
+            WriteResult::Dirty(())
+        })
+        .unwrap();
+        sb.alloc_staged(32, 2, |data| {
+            data.copy_from_slice(&[0x11, 0x22]);
+            WriteResult::Dirty(())
+        })
+        .unwrap();
+        sb
+    }
+
+    #[test]
+    fn journal_bytes_needed_matches_what_write_records_appends() {
+        let sb = staged_patch();
+        let mut journal = RecordingJournal::default();
+        write_records(&sb, &mut journal).unwrap();
+
+        assert_eq!(journal_bytes_needed(&sb), journal.bytes.len());
+    }
+
+    #[test]
+    fn replay_applies_a_valid_journal() {
+        let sb = staged_patch();
+        let mut journal = RecordingJournal::default();
+        write_records(&sb, &mut journal).unwrap();
+
+        let mut table: ShadowTable<64, 16, 4> = ShadowTable::new();
+        let policy = AllowAllPolicy::default();
+        let persist_policy = NoPersistPolicy::default();
+        let mut trigger = NoPersist;
+        let mut fault_handler = NoFaultHandler;
+        let mut view = HostView::new(
+            &mut table,
+            &policy,
+            &persist_policy,
+            &mut trigger,
+            &mut fault_handler,
+            &NoBackingStore,
+        );
+
+        let replayed = replay_journal(&mut view, &journal.bytes).unwrap();
+        assert!(replayed);
+
+        view.with_ro_slice(0, 4, |slice| {
+            let mut buf = [0u8; 4];
+            slice.copy_to_slice(&mut buf);
+            assert_eq!(buf, [0xAA, 0xBB, 0xCC, 0xDD]);
+        })
+        .unwrap();
+        assert!(view.table.is_dirty(0, 4).unwrap());
+    }
+
+    #[test]
+    fn replay_discards_a_journal_missing_its_commit_marker() {
+        let sb = staged_patch();
+        let mut journal = RecordingJournal::default();
+        write_records(&sb, &mut journal).unwrap();
+
+        // Truncate off the commit marker and CRC entirely.
+        let torn_len = journal.bytes.len() - (RECORD_HEADER_LEN + CRC_LEN);
+        let torn = &journal.bytes[..torn_len];
+
+        let mut table: ShadowTable<64, 16, 4> = ShadowTable::new();
+        let policy = AllowAllPolicy::default();
+        let persist_policy = NoPersistPolicy::default();
+        let mut trigger = NoPersist;
+        let mut fault_handler = NoFaultHandler;
+        let mut view = HostView::new(
+            &mut table,
+            &policy,
+            &persist_policy,
+            &mut trigger,
+            &mut fault_handler,
+            &NoBackingStore,
+        );
+
+        let replayed = replay_journal(&mut view, torn).unwrap();
+        assert!(!replayed);
+        assert!(!view.table.any_dirty());
+    }
+
+    #[test]
+    fn replay_discards_a_journal_with_a_corrupted_crc() {
+        let sb = staged_patch();
+        let mut journal = RecordingJournal::default();
+        write_records(&sb, &mut journal).unwrap();
+
+        let last = journal.bytes.len() - 1;
+        journal.bytes[last] ^= 0xFF;
+
+        let mut table: ShadowTable<64, 16, 4> = ShadowTable::new();
+        let policy = AllowAllPolicy::default();
+        let persist_policy = NoPersistPolicy::default();
+        let mut trigger = NoPersist;
+        let mut fault_handler = NoFaultHandler;
+        let mut view = HostView::new(
+            &mut table,
+            &policy,
+            &persist_policy,
+            &mut trigger,
+            &mut fault_handler,
+            &NoBackingStore,
+        );
+
+        let replayed = replay_journal(&mut view, &journal.bytes).unwrap();
+        assert!(!replayed);
+        assert!(!view.table.any_dirty());
+    }
+}
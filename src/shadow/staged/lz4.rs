@@ -0,0 +1,272 @@
+//! Self-contained LZ4 block (de)compressor for [`PatchStagingBuffer`]'s
+//! staged bytes, used by [`PatchStagingBuffer::compress_staged`].
+//!
+//! Unlike [`Lz4Codec`](crate::shadow::codec::Lz4Codec), which wraps the
+//! external `lz4_flex` crate for compressing a single contiguous block,
+//! this module is a small hand-rolled encoder/decoder kept local to
+//! `staged` so [`compress`] can read its input one byte at a time via a
+//! closure — staged writes aren't stored contiguously, so there's no
+//! single slice to hand a generic block compressor.
+//!
+//! # Token format
+//!
+//! Each sequence starts with one token byte: the high nibble is the
+//! literal run length (0-14, or 15 meaning "read more in the following
+//! `0xFF`-terminated extension bytes"), the low nibble is the match
+//! length minus 4 using the same extension scheme. The token is followed
+//! by that many literal bytes, then — unless this is the final sequence
+//! in the block — a 2-byte little-endian back-offset and any match-length
+//! extension bytes. The final sequence is literals only, with no offset;
+//! the decoder recognizes it by running out of input right after copying
+//! the literal run.
+
+use crate::shadow::ShadowError;
+
+const MIN_MATCH: usize = 4;
+const HASH_BITS: u32 = 8;
+const HASH_SIZE: usize = 1 << HASH_BITS;
+const NO_CANDIDATE: u32 = u32::MAX;
+
+fn hash4(key: u32) -> usize {
+    ((key.wrapping_mul(2654435761)) >> (32 - HASH_BITS)) as usize
+}
+
+fn read_u32_at(read: &impl Fn(usize) -> u8, pos: usize) -> u32 {
+    u32::from_le_bytes([read(pos), read(pos + 1), read(pos + 2), read(pos + 3)])
+}
+
+fn push_byte(out: &mut [u8], out_pos: &mut usize, b: u8) -> Result<(), ShadowError> {
+    *out.get_mut(*out_pos).ok_or(ShadowError::StageFull)? = b;
+    *out_pos += 1;
+    Ok(())
+}
+
+fn push_ext_len(mut extra: usize, out: &mut [u8], out_pos: &mut usize) -> Result<(), ShadowError> {
+    while extra >= 0xFF {
+        push_byte(out, out_pos, 0xFF)?;
+        extra -= 0xFF;
+    }
+    push_byte(out, out_pos, extra as u8)
+}
+
+fn read_ext_len(input: &[u8], in_pos: &mut usize) -> Result<usize, ShadowError> {
+    let mut extra = 0usize;
+    loop {
+        let b = *input.get(*in_pos).ok_or(ShadowError::OutOfBounds)?;
+        *in_pos += 1;
+        extra += b as usize;
+        if b != 0xFF {
+            return Ok(extra);
+        }
+    }
+}
+
+/// Compresses `len` bytes, read one at a time via `read` (so the input
+/// need not live in one contiguous slice), into `out`. Returns the number
+/// of bytes written, or [`ShadowError::StageFull`] if `out` is too small.
+///
+/// Greedily matches against a fixed-size hash table (the last position
+/// seen for each 4-byte prefix), with no chaining — cheap, at the cost of
+/// occasionally missing an older, equally-good match.
+pub(crate) fn compress(
+    len: usize,
+    read: impl Fn(usize) -> u8,
+    out: &mut [u8],
+) -> Result<usize, ShadowError> {
+    let mut table = [NO_CANDIDATE; HASH_SIZE];
+    let mut out_pos = 0usize;
+    let mut literal_start = 0usize;
+    let mut pos = 0usize;
+
+    while pos + MIN_MATCH <= len {
+        let key = read_u32_at(&read, pos);
+        let h = hash4(key);
+        let candidate = table[h];
+        table[h] = pos as u32;
+
+        let is_match = candidate != NO_CANDIDATE && read_u32_at(&read, candidate as usize) == key;
+        if !is_match {
+            pos += 1;
+            continue;
+        }
+
+        let candidate = candidate as usize;
+        let mut match_len = MIN_MATCH;
+        while pos + match_len < len && read(candidate + match_len) == read(pos + match_len) {
+            match_len += 1;
+        }
+
+        let literal_len = pos - literal_start;
+        let offset = (pos - candidate) as u16;
+
+        let lit_nib = literal_len.min(15);
+        let match_nib = (match_len - MIN_MATCH).min(15);
+        push_byte(out, &mut out_pos, ((lit_nib as u8) << 4) | match_nib as u8)?;
+        if literal_len >= 15 {
+            push_ext_len(literal_len - 15, out, &mut out_pos)?;
+        }
+        for i in 0..literal_len {
+            push_byte(out, &mut out_pos, read(literal_start + i))?;
+        }
+
+        out.get_mut(out_pos..out_pos + 2)
+            .ok_or(ShadowError::StageFull)?
+            .copy_from_slice(&offset.to_le_bytes());
+        out_pos += 2;
+        if match_len - MIN_MATCH >= 15 {
+            push_ext_len(match_len - MIN_MATCH - 15, out, &mut out_pos)?;
+        }
+
+        // Seed the table with a few positions inside the match so a later
+        // match can still reference it, then resume scanning past it.
+        let match_end = pos + match_len;
+        while pos < match_end && pos + MIN_MATCH <= len {
+            let key = read_u32_at(&read, pos);
+            table[hash4(key)] = pos as u32;
+            pos += 1;
+        }
+        pos = match_end;
+        literal_start = pos;
+    }
+
+    // Final sequence: whatever's left is literals only, no match/offset.
+    let literal_len = len - literal_start;
+    let lit_nib = literal_len.min(15);
+    push_byte(out, &mut out_pos, (lit_nib as u8) << 4)?;
+    if literal_len >= 15 {
+        push_ext_len(literal_len - 15, out, &mut out_pos)?;
+    }
+    for i in 0..literal_len {
+        push_byte(out, &mut out_pos, read(literal_start + i))?;
+    }
+
+    Ok(out_pos)
+}
+
+/// Decompresses a block produced by [`compress`] into `out`, copying
+/// matches byte-by-byte (rather than via `copy_from_slice`) so an overlap
+/// between the match source and destination — a run shorter than its own
+/// back-offset — replays correctly. Returns the number of bytes written,
+/// or [`ShadowError::OutOfBounds`] if `input` is truncated/malformed or
+/// `out` is too small.
+pub(crate) fn decompress(input: &[u8], out: &mut [u8]) -> Result<usize, ShadowError> {
+    let mut in_pos = 0usize;
+    let mut out_pos = 0usize;
+
+    while in_pos < input.len() {
+        let token = input[in_pos];
+        in_pos += 1;
+
+        let mut literal_len = (token >> 4) as usize;
+        if literal_len == 15 {
+            literal_len += read_ext_len(input, &mut in_pos)?;
+        }
+
+        let literals = input
+            .get(in_pos..in_pos + literal_len)
+            .ok_or(ShadowError::OutOfBounds)?;
+        out.get_mut(out_pos..out_pos + literal_len)
+            .ok_or(ShadowError::OutOfBounds)?
+            .copy_from_slice(literals);
+        in_pos += literal_len;
+        out_pos += literal_len;
+
+        if in_pos >= input.len() {
+            break; // Final sequence: literals only, no match follows.
+        }
+
+        let offset_bytes = input
+            .get(in_pos..in_pos + 2)
+            .ok_or(ShadowError::OutOfBounds)?;
+        let offset = u16::from_le_bytes([offset_bytes[0], offset_bytes[1]]) as usize;
+        in_pos += 2;
+
+        let mut match_len = (token & 0x0F) as usize + MIN_MATCH;
+        if token & 0x0F == 15 {
+            match_len += read_ext_len(input, &mut in_pos)?;
+        }
+
+        if offset == 0 || offset > out_pos {
+            return Err(ShadowError::OutOfBounds);
+        }
+        let match_start = out_pos - offset;
+        for i in 0..match_len {
+            let b = *out.get(match_start + i).ok_or(ShadowError::OutOfBounds)?;
+            *out.get_mut(out_pos + i).ok_or(ShadowError::OutOfBounds)? = b;
+        }
+        out_pos += match_len;
+    }
+
+    Ok(out_pos)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn roundtrip(input: &[u8]) {
+        let mut compressed = [0u8; 256];
+        let compressed_len = compress(input.len(), |i| input[i], &mut compressed).unwrap();
+
+        let mut decompressed = [0u8; 256];
+        let decompressed_len =
+            decompress(&compressed[..compressed_len], &mut decompressed).unwrap();
+
+        assert_eq!(&decompressed[..decompressed_len], input);
+    }
+
+    #[test]
+    fn roundtrips_empty_input() {
+        roundtrip(&[]);
+    }
+
+    #[test]
+    fn roundtrips_short_incompressible_input() {
+        roundtrip(&[0x01, 0x02, 0x03]);
+    }
+
+    #[test]
+    fn roundtrips_repetitive_input() {
+        roundtrip(&[0xAAu8; 64]);
+    }
+
+    #[test]
+    fn roundtrips_mixed_literal_and_match_runs() {
+        let mut input = [0u8; 40];
+        for (i, b) in input.iter_mut().enumerate() {
+            *b = (i % 7) as u8;
+        }
+        roundtrip(&input);
+    }
+
+    #[test]
+    fn roundtrips_input_needing_extended_literal_length() {
+        // Strictly ascending distinct bytes: no 4-byte window repeats, so
+        // the whole block ends up as one long literal run (len > 14,
+        // exercising the extended-length encoding).
+        let mut input = [0u8; 20];
+        for (i, b) in input.iter_mut().enumerate() {
+            *b = i as u8;
+        }
+        roundtrip(&input);
+    }
+
+    #[test]
+    fn decompress_rejects_truncated_input() {
+        let mut out = [0u8; 16];
+        assert_eq!(
+            decompress(&[0x10], &mut out), // claims 1 literal byte, none follow
+            Err(ShadowError::OutOfBounds)
+        );
+    }
+
+    #[test]
+    fn compress_reports_stage_full_when_out_is_too_small() {
+        let input = [0x01, 0x02, 0x03, 0x04];
+        let mut out = [0u8; 1];
+        assert_eq!(
+            compress(input.len(), |i| input[i], &mut out),
+            Err(ShadowError::StageFull)
+        );
+    }
+}
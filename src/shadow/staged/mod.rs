@@ -0,0 +1,10 @@
+mod journal;
+#[cfg(feature = "lz4")]
+mod lz4;
+mod patch;
+mod spsc;
+
+pub(crate) use journal::write_records;
+pub use journal::{journal_bytes_needed, replay_journal};
+pub use patch::{ConflictPolicy, PatchStagingBuffer};
+pub use spsc::SpscStagingQueue;
@@ -1,8 +1,9 @@
 use heapless::Vec;
 
 use crate::shadow::{
+    helpers::crc32,
+    types::{Savepoint, StagingBuffer, WriteResult},
     ShadowError,
-    types::{StagingBuffer, WriteResult},
 };
 
 #[derive(Clone, Copy)]
@@ -10,6 +11,29 @@ struct StagedWrite {
     addr: u16,
     len: u16,
     off: u16, // offset into data vec
+    touch: u32,
+}
+
+/// Conflict-resolution policy [`PatchStagingBuffer::alloc_staged`] applies
+/// to a newly-staged write that overlaps an already-staged range.
+///
+/// `alloc_staged` always keeps `entries` sorted and coalesced — an
+/// overlapping or adjacent write is merged into the existing interval
+/// immediately, new bytes winning — so [`Self::LastWins`] and
+/// [`Self::Merge`] observe the same entry layout; this policy only
+/// controls whether an overlap is rejected outright.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    /// The later write's bytes win on overlap. This is the default.
+    #[default]
+    LastWins,
+    /// An overlapping write is rejected with
+    /// [`ShadowError::StagingConflict`] instead of being staged, for
+    /// callers that need strictly non-overlapping transactions.
+    Reject,
+    /// Equivalent to [`Self::LastWins`]: kept so call sites that want to
+    /// state "merge on overlap" explicitly have a name for it.
+    Merge,
 }
 
 /// Fixed-capacity staging buffer for transactional writes.
@@ -18,6 +42,9 @@ struct StagedWrite {
 pub struct PatchStagingBuffer<const DC: usize, const EC: usize> {
     data: Vec<u8, DC>,
     entries: Vec<StagedWrite, EC>,
+    conflict_policy: ConflictPolicy,
+    evict_lru: bool,
+    next_touch: u32,
 }
 
 impl<const DC: usize, const EC: usize> PatchStagingBuffer<DC, EC> {
@@ -25,6 +52,121 @@ impl<const DC: usize, const EC: usize> PatchStagingBuffer<DC, EC> {
         Self {
             data: Vec::new(),
             entries: Vec::new(),
+            conflict_policy: ConflictPolicy::LastWins,
+            evict_lru: false,
+            next_touch: 0,
+        }
+    }
+
+    /// Sets the conflict-resolution policy applied to overlapping staged
+    /// writes. Defaults to [`ConflictPolicy::LastWins`].
+    pub fn with_conflict_policy(mut self, policy: ConflictPolicy) -> Self {
+        self.conflict_policy = policy;
+        self
+    }
+
+    /// Opts into LRU eviction: once staged writes fill `data` or `entries`,
+    /// [`StagingBuffer::would_overflow`]/[`StagingBuffer::evict_oldest_staged`]
+    /// force out the least-recently-touched staged entry instead of a new
+    /// [`StagingBuffer::alloc_staged`] call failing with
+    /// [`ShadowError::StageFull`]. Off by default, since forcing a commit
+    /// ahead of schedule isn't safe for every caller (e.g. one relying on
+    /// [`HostViewStaged::try_commit_staged`](crate::shadow::view::host_staged::HostViewStaged::try_commit_staged)
+    /// to validate the whole batch before anything reaches the table).
+    pub fn stage_evict_lru(mut self) -> Self {
+        self.evict_lru = true;
+        self
+    }
+
+    /// Monotonic counter stamped onto every entry [`Self::alloc_staged`]
+    /// creates or merges, so [`Self::evict_oldest_staged`] can find the
+    /// least-recently-touched one by taking the minimum.
+    fn bump_touch(&mut self) -> u32 {
+        let touch = self.next_touch;
+        self.next_touch = self.next_touch.wrapping_add(1);
+        touch
+    }
+
+    /// Range of entry indices that overlap or touch `[addr, addr+len)`,
+    /// found by binary-searching `entries` (kept sorted by `addr`) for the
+    /// first entry whose end exceeds `addr`, then scanning forward only as
+    /// far as entries still overlap or are adjacent.
+    fn merge_range(&self, addr: u16, len: usize) -> core::ops::Range<usize> {
+        let start = addr as u32;
+        let end = start + len as u32;
+
+        let lo = self
+            .entries
+            .partition_point(|e| (e.addr as u32 + e.len as u32) < start);
+
+        let mut hi = lo;
+        while hi < self.entries.len() && self.entries[hi].addr as u32 <= end {
+            hi += 1;
+        }
+
+        lo..hi
+    }
+
+    fn overlaps_staged(&self, addr: u16, len: usize) -> bool {
+        let range = self.merge_range(addr, len);
+        range.clone().any(|i| {
+            let e = &self.entries[i];
+            let e_start = e.addr as u32;
+            let e_end = e_start + e.len as u32;
+            (addr as u32) < e_end && e_start < (addr as u32 + len as u32)
+        })
+    }
+
+    /// Length of the serialized frame stream [`Self::integrity_crc32`] and
+    /// [`Self::compress_staged`] encode: each entry's little-endian
+    /// `addr:u16, len:u16` header followed immediately by its payload
+    /// bytes, back to back in address order.
+    fn frame_len(&self) -> usize {
+        self.entries.iter().map(|e| 4 + e.len as usize).sum()
+    }
+
+    /// Reads byte `pos` of the serialized frame stream directly out of
+    /// `entries`/`data` rather than assembling it in a scratch buffer
+    /// first — `entries` is small, so a linear scan per byte is cheap.
+    fn frame_byte_at(&self, mut pos: usize) -> u8 {
+        for e in self.entries.iter() {
+            let frame_len = 4 + e.len as usize;
+            if pos < frame_len {
+                return match pos {
+                    0 => e.addr.to_le_bytes()[0],
+                    1 => e.addr.to_le_bytes()[1],
+                    2 => e.len.to_le_bytes()[0],
+                    3 => e.len.to_le_bytes()[1],
+                    _ => self.data[e.off as usize + (pos - 4)],
+                };
+            }
+            pos -= frame_len;
+        }
+        unreachable!("pos out of range of the staged frame stream")
+    }
+
+    /// CRC-32 over the same address/length/payload frame stream
+    /// [`Self::compress_staged`] would serialize, so the checksum doesn't
+    /// depend on how entries happen to be laid out in `data`.
+    ///
+    /// Compute this when flushing staged writes to external storage and
+    /// store it alongside the data; after reloading, check it back with
+    /// [`Self::verify_integrity`] before trusting the result through
+    /// [`HostView`](crate::shadow::HostView).
+    pub fn integrity_crc32(&self) -> u32 {
+        crc32((0..self.frame_len()).map(|pos| self.frame_byte_at(pos)))
+    }
+
+    /// Checks the currently staged writes against a CRC previously
+    /// captured with [`Self::integrity_crc32`], returning
+    /// [`ShadowError::ChecksumMismatch`] on mismatch — the same variant
+    /// [`decode_frame`](crate::shadow::decode_frame) uses for its own CRC
+    /// check, rather than a second "bad checksum" variant.
+    pub fn verify_integrity(&self, expected_crc32: u32) -> Result<(), ShadowError> {
+        if self.integrity_crc32() == expected_crc32 {
+            Ok(())
+        } else {
+            Err(ShadowError::ChecksumMismatch)
         }
     }
 }
@@ -35,6 +177,55 @@ impl<const DC: usize, const EC: usize> Default for PatchStagingBuffer<DC, EC> {
     }
 }
 
+#[cfg(feature = "lz4")]
+impl<const DC: usize, const EC: usize> PatchStagingBuffer<DC, EC> {
+    /// Compresses every staged write — address, length and payload bytes —
+    /// into `out`, using the LZ4 block format in
+    /// [`lz4`](super::lz4); see its module docs for the exact token
+    /// layout. Restore the result later with [`Self::decompress_staged`].
+    ///
+    /// Returns the number of bytes written, or
+    /// [`ShadowError::StageFull`] if `out` is too small.
+    pub fn compress_staged(&self, out: &mut [u8]) -> Result<usize, ShadowError> {
+        super::lz4::compress(self.frame_len(), |pos| self.frame_byte_at(pos), out)
+    }
+
+    /// Restores writes previously staged and compressed by
+    /// [`Self::compress_staged`], replacing anything already staged.
+    ///
+    /// `scratch` holds the decompressed frame stream temporarily — it must
+    /// be at least as large as the original uncompressed size, so sizing
+    /// it like `data`'s capacity plus 4 bytes per staged entry is always
+    /// enough.
+    pub fn decompress_staged(
+        &mut self,
+        compressed: &[u8],
+        scratch: &mut [u8],
+    ) -> Result<(), ShadowError> {
+        let frame_len = super::lz4::decompress(compressed, scratch)?;
+        self.clear_staged()?;
+
+        let mut pos = 0usize;
+        while pos < frame_len {
+            let header = scratch.get(pos..pos + 4).ok_or(ShadowError::OutOfBounds)?;
+            let addr = u16::from_le_bytes([header[0], header[1]]);
+            let len = u16::from_le_bytes([header[2], header[3]]) as usize;
+            let payload = scratch
+                .get(pos + 4..pos + 4 + len)
+                .ok_or(ShadowError::OutOfBounds)?;
+
+            self.alloc_staged(addr, len, |dst| {
+                dst.copy_from_slice(payload);
+                WriteResult::Dirty(())
+            })?;
+
+            pos += 4 + len;
+        }
+
+        Ok(())
+    }
+}
+
 impl<const DC: usize, const EC: usize> StagingBuffer for PatchStagingBuffer<DC, EC> {
     fn any_staged(&self) -> bool {
         !self.entries.is_empty()
@@ -51,34 +242,146 @@ impl<const DC: usize, const EC: usize> StagingBuffer for PatchStagingBuffer<DC,
         Ok(())
     }
 
+    /// `false` unless [`Self::stage_evict_lru`] is set — computes the same
+    /// merge sizing [`Self::alloc_staged`] would, without staging anything,
+    /// so a caller can tell whether to evict first.
+    fn would_overflow(&self, addr: u16, len: usize) -> bool {
+        if !self.evict_lru {
+            return false;
+        }
+
+        let new_start = addr as u32;
+        let new_end = new_start + len as u32;
+        let range = self.merge_range(addr, len);
+
+        let merge_start = if range.is_empty() {
+            new_start
+        } else {
+            (self.entries[range.start].addr as u32).min(new_start)
+        };
+        let merge_end = if range.is_empty() {
+            new_end
+        } else {
+            let last = &self.entries[range.end - 1];
+            (last.addr as u32 + last.len as u32).max(new_end)
+        };
+        let merge_len = (merge_end - merge_start) as usize;
+        let entries_after = self.entries.len() - range.len() + 1;
+
+        self.data.len() + merge_len > DC || entries_after > EC
+    }
+
+    /// No-op unless [`Self::stage_evict_lru`] is set. Finds the
+    /// least-recently-touched staged entry, hands it to `f`, then drops it
+    /// from `entries` and [`Self::compact`]s to reclaim its bytes from
+    /// `data` — eviction only removes one entry at a time, so a caller
+    /// needing more room calls this in a loop against
+    /// [`Self::would_overflow`].
+    fn evict_oldest_staged<F>(&mut self, f: F) -> Result<bool, ShadowError>
+    where
+        F: FnOnce(u16, &[u8]) -> Result<(), ShadowError>,
+    {
+        if !self.evict_lru || self.entries.is_empty() {
+            return Ok(false);
+        }
+
+        let idx = self
+            .entries
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, e)| e.touch)
+            .map(|(i, _)| i)
+            .expect("entries is non-empty");
+        let evicted = self.entries[idx];
+        let data = &self.data[evicted.off as usize..(evicted.off + evicted.len) as usize];
+        f(evicted.addr, data)?;
+
+        self.entries.remove(idx);
+        self.compact()?;
+        Ok(true)
+    }
+
+    /// Stages `[addr, addr+len)`, keeping `entries` sorted by `addr` and
+    /// coalesced: the new range is merged with any existing entry it
+    /// overlaps or immediately touches into a single interval, new bytes
+    /// winning over old ones wherever they overlap. This bounds `entries`
+    /// to the number of non-overlapping ranges ever staged (rather than
+    /// the number of writes), and keeps [`Self::iter_staged`]'s per-commit
+    /// scan over a minimal, non-overlapping set.
+    ///
+    /// Merging reuses the old bytes of any absorbed entries, appended
+    /// alongside the new ones — their original storage in `data` becomes
+    /// unreferenced rather than being reclaimed immediately. Call
+    /// [`Self::compact`] periodically to rewrite `data` and drop that
+    /// unreferenced span.
     fn alloc_staged(
         &mut self,
         addr: u16,
         len: usize,
         f: impl FnOnce(&mut [u8]) -> WriteResult<()>,
     ) -> Result<WriteResult<()>, ShadowError> {
-        let off = self.data.len();
+        if self.conflict_policy == ConflictPolicy::Reject && self.overlaps_staged(addr, len) {
+            return Err(ShadowError::StagingConflict);
+        }
 
-        // Pre-allocate space (zero-filled)
-        self.data
-            .resize(off + len, 0)
+        let new_start = addr as u32;
+        let new_end = new_start + len as u32;
+        let range = self.merge_range(addr, len);
+
+        let merge_start = if range.is_empty() {
+            new_start
+        } else {
+            (self.entries[range.start].addr as u32).min(new_start)
+        };
+        let merge_end = if range.is_empty() {
+            new_end
+        } else {
+            let last = &self.entries[range.end - 1];
+            (last.addr as u32 + last.len as u32).max(new_end)
+        };
+        let merge_len = (merge_end - merge_start) as usize;
+
+        // Assemble the merged bytes in a scratch buffer: old bytes from
+        // absorbed entries outside [new_start, new_end), and the new
+        // write's bytes (via the caller's callback) inside it.
+        let mut merged: Vec<u8, DC> = Vec::new();
+        merged
+            .resize(merge_len, 0)
             .map_err(|_| ShadowError::StageFull)?;
 
-        // Call user callback - returns WriteResult::Dirty to commit the write
-        let result = f(&mut self.data[off..off + len]);
+        for e in &self.entries[range.clone()] {
+            let e_start = e.addr as u32;
+            for k in 0..e.len as u32 {
+                let pos = e_start + k;
+                if pos >= new_start && pos < new_end {
+                    continue; // overwritten by the new write below
+                }
+                merged[(pos - merge_start) as usize] = self.data[(e.off as u32 + k) as usize];
+            }
+        }
+
+        let rel_off = (new_start - merge_start) as usize;
+        let result = f(&mut merged[rel_off..rel_off + len]);
 
         if result.is_dirty() {
-            // Record the entry
+            let off = self.data.len();
+            self.data
+                .extend_from_slice(&merged)
+                .map_err(|_| ShadowError::StageFull)?;
+
+            let merged_entry = StagedWrite {
+                addr: merge_start as u16,
+                len: merge_len as u16,
+                off: off as u16,
+                touch: self.bump_touch(),
+            };
+
+            for _ in range.clone() {
+                self.entries.remove(range.start);
+            }
             self.entries
-                .push(StagedWrite {
-                    addr,
-                    len: len as u16,
-                    off: off as u16,
-                })
+                .insert(range.start, merged_entry)
                 .map_err(|_| ShadowError::StageFull)?;
-        } else {
-            // Reclaim space
-            self.data.truncate(off);
         }
 
         Ok(result)
@@ -89,12 +392,133 @@ impl<const DC: usize, const EC: usize> StagingBuffer for PatchStagingBuffer<DC,
         self.entries.clear();
         Ok(())
     }
+
+    /// Captures the current `data`/`entries` lengths.
+    ///
+    /// Valid only against writes staged via [`Self::alloc_staged`]
+    /// afterward — [`Self::compact`] rewrites both vectors in place, which
+    /// invalidates any savepoint taken before it. So does an `alloc_staged`
+    /// call after the savepoint whose range overlaps or touches an entry
+    /// staged before it: coalescing rewrites that earlier entry in place,
+    /// and rolling back would truncate away bytes it still references.
+    /// Savepoints are safe to use across disjoint address ranges, which is
+    /// the common case for grouping related register writes.
+    fn savepoint(&self) -> Savepoint {
+        Savepoint::new(self.data.len() as u16, self.entries.len() as u16)
+    }
+
+    fn rollback_to(&mut self, sp: Savepoint) -> Result<(), ShadowError> {
+        self.data.truncate(sp.data_len() as usize);
+        // `entries` is sorted by address, so a write staged after the
+        // savepoint can sit anywhere in it; its bytes always start at or
+        // past the savepoint's `data` length.
+        self.entries
+            .retain(|e| (e.off as usize) < sp.data_len() as usize);
+        Ok(())
+    }
+
+    /// Rewrites `data` to drop bytes orphaned by merges in
+    /// [`Self::alloc_staged`] (which always keeps `entries` itself
+    /// non-overlapping, so this no longer reduces the entry count in the
+    /// common case — only reclaims `data` capacity).
+    fn compact(&mut self) -> Result<(), ShadowError> {
+        let old_entries = self.entries.clone();
+        let old_data = self.data.clone();
+
+        // Ranges already claimed by a higher-priority (later) entry,
+        // accumulated as entries are visited last-to-first.
+        let mut covered: Vec<(u16, u16), EC> = Vec::new();
+        // Surviving (addr, len, offset-into-old_data, touch) fragments, in
+        // no particular order yet.
+        let mut fragments: Vec<(u16, u16, u16, u32), EC> = Vec::new();
+
+        for e in old_entries.iter().rev() {
+            let mut free: Vec<(u16, u16), EC> = Vec::new();
+            free.push((e.addr, e.len))
+                .map_err(|_| ShadowError::StageFull)?;
+
+            for &(c_addr, c_len) in covered.iter() {
+                let mut next: Vec<(u16, u16), EC> = Vec::new();
+                for &(f_addr, f_len) in free.iter() {
+                    let f_end = f_addr as u32 + f_len as u32;
+                    let c_start = c_addr as u32;
+                    let c_end = c_start + c_len as u32;
+                    let overlap_start = (f_addr as u32).max(c_start);
+                    let overlap_end = f_end.min(c_end);
+
+                    if overlap_start >= overlap_end {
+                        next.push((f_addr, f_len))
+                            .map_err(|_| ShadowError::StageFull)?;
+                        continue;
+                    }
+                    if (f_addr as u32) < overlap_start {
+                        next.push((f_addr, (overlap_start - f_addr as u32) as u16))
+                            .map_err(|_| ShadowError::StageFull)?;
+                    }
+                    if overlap_end < f_end {
+                        next.push((overlap_end as u16, (f_end - overlap_end) as u16))
+                            .map_err(|_| ShadowError::StageFull)?;
+                    }
+                }
+                free = next;
+            }
+
+            for (f_addr, f_len) in free {
+                let src_off = e.off + (f_addr - e.addr);
+                fragments
+                    .push((f_addr, f_len, src_off, e.touch))
+                    .map_err(|_| ShadowError::StageFull)?;
+            }
+
+            covered
+                .push((e.addr, e.len))
+                .map_err(|_| ShadowError::StageFull)?;
+        }
+
+        fragments.sort_unstable_by_key(|&(addr, _, _, _)| addr);
+
+        let mut new_data: Vec<u8, DC> = Vec::new();
+        let mut new_entries: Vec<StagedWrite, EC> = Vec::new();
+
+        for (addr, len, src_off, touch) in fragments {
+            let bytes = &old_data[src_off as usize..(src_off + len) as usize];
+
+            if let Some(last) = new_entries.last_mut() {
+                if last.addr as u32 + last.len as u32 == addr as u32 {
+                    new_data
+                        .extend_from_slice(bytes)
+                        .map_err(|_| ShadowError::StageFull)?;
+                    last.len += len;
+                    last.touch = last.touch.max(touch);
+                    continue;
+                }
+            }
+
+            let off = new_data.len() as u16;
+            new_data
+                .extend_from_slice(bytes)
+                .map_err(|_| ShadowError::StageFull)?;
+            new_entries
+                .push(StagedWrite {
+                    addr,
+                    len,
+                    off,
+                    touch,
+                })
+                .map_err(|_| ShadowError::StageFull)?;
+        }
+
+        self.data = new_data;
+        self.entries = new_entries;
+
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::shadow::test_support::{TestStage, stage_write};
+    use crate::shadow::test_support::{stage_write, TestStage};
 
     #[test]
     fn with_staged_write_accumulates_entries() {
@@ -201,4 +625,368 @@ mod tests {
         assert!(!result.is_dirty());
         assert!(!stage.any_staged());
     }
+
+    fn entry_count(stage: &TestStage) -> usize {
+        let mut count = 0;
+        stage
+            .iter_staged(|_, _| {
+                count += 1;
+                Ok(())
+            })
+            .unwrap();
+        count
+    }
+
+    #[test]
+    fn compact_merges_overlapping_writes_with_last_writer_winning() {
+        let mut stage = TestStage::new();
+
+        stage_write(&mut stage, 0, &[0x11, 0x11, 0x11, 0x11]).unwrap();
+        stage_write(&mut stage, 2, &[0x22, 0x22]).unwrap();
+        stage.compact().unwrap();
+
+        assert_eq!(entry_count(&stage), 1);
+
+        let mut seen = [0u8; 4];
+        stage
+            .iter_staged(|addr, data| {
+                assert_eq!(addr, 0);
+                seen.copy_from_slice(data);
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(seen, [0x11, 0x11, 0x22, 0x22]);
+    }
+
+    #[test]
+    fn compact_merges_adjacent_writes_into_one_entry() {
+        let mut stage = TestStage::new();
+
+        stage_write(&mut stage, 0, &[0x01, 0x02]).unwrap();
+        stage_write(&mut stage, 2, &[0x03, 0x04]).unwrap();
+        stage.compact().unwrap();
+
+        assert_eq!(entry_count(&stage), 1);
+
+        let mut seen = [0u8; 4];
+        stage
+            .iter_staged(|addr, data| {
+                assert_eq!(addr, 0);
+                seen.copy_from_slice(data);
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(seen, [0x01, 0x02, 0x03, 0x04]);
+    }
+
+    #[test]
+    fn compact_leaves_non_adjacent_writes_as_separate_entries() {
+        let mut stage = TestStage::new();
+
+        stage_write(&mut stage, 0, &[0x01]).unwrap();
+        stage_write(&mut stage, 10, &[0x02]).unwrap();
+        stage.compact().unwrap();
+
+        assert_eq!(entry_count(&stage), 2);
+    }
+
+    #[test]
+    fn reject_policy_errors_on_overlapping_write() {
+        let mut stage = TestStage::new().with_conflict_policy(ConflictPolicy::Reject);
+
+        stage_write(&mut stage, 0, &[0x01, 0x02, 0x03, 0x04]).unwrap();
+
+        assert_eq!(
+            stage.alloc_staged(2, 2, |data| {
+                data.copy_from_slice(&[0xAA, 0xBB]);
+                WriteResult::Dirty(())
+            }),
+            Err(ShadowError::StagingConflict)
+        );
+        assert_eq!(entry_count(&stage), 1);
+    }
+
+    #[test]
+    fn reject_policy_allows_non_overlapping_writes() {
+        let mut stage = TestStage::new().with_conflict_policy(ConflictPolicy::Reject);
+
+        stage_write(&mut stage, 0, &[0x01, 0x02]).unwrap();
+        stage_write(&mut stage, 10, &[0x03, 0x04]).unwrap();
+
+        assert_eq!(entry_count(&stage), 2);
+    }
+
+    #[test]
+    fn merge_policy_compacts_on_every_overlapping_write() {
+        let mut stage = TestStage::new().with_conflict_policy(ConflictPolicy::Merge);
+
+        stage_write(&mut stage, 0, &[0x11, 0x11, 0x11, 0x11]).unwrap();
+        stage_write(&mut stage, 2, &[0x22, 0x22]).unwrap();
+
+        assert_eq!(entry_count(&stage), 1);
+    }
+
+    #[test]
+    fn rollback_to_discards_writes_staged_since_the_savepoint() {
+        let mut stage = TestStage::new();
+
+        stage_write(&mut stage, 0, &[0x01, 0x02]).unwrap();
+        let sp = stage.savepoint();
+        stage_write(&mut stage, 10, &[0x03, 0x04]).unwrap();
+        stage_write(&mut stage, 20, &[0x05]).unwrap();
+        assert_eq!(entry_count(&stage), 3);
+
+        stage.rollback_to(sp).unwrap();
+
+        assert_eq!(entry_count(&stage), 1);
+        let mut seen = [0u8; 2];
+        stage
+            .iter_staged(|addr, data| {
+                assert_eq!(addr, 0);
+                seen.copy_from_slice(data);
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(seen, [0x01, 0x02]);
+    }
+
+    #[test]
+    fn savepoints_nest() {
+        let mut stage = TestStage::new();
+
+        stage_write(&mut stage, 0, &[0x01]).unwrap();
+        let outer = stage.savepoint();
+        stage_write(&mut stage, 10, &[0x02]).unwrap();
+        let _inner = stage.savepoint();
+        stage_write(&mut stage, 20, &[0x03]).unwrap();
+        assert_eq!(entry_count(&stage), 3);
+
+        // Rolling back to the outer savepoint also undoes the inner one.
+        stage.rollback_to(outer).unwrap();
+        assert_eq!(entry_count(&stage), 1);
+    }
+
+    #[test]
+    fn rollback_to_initial_savepoint_clears_everything_staged_after_it() {
+        let mut stage = TestStage::new();
+        let sp = stage.savepoint();
+
+        stage_write(&mut stage, 0, &[0x01, 0x02]).unwrap();
+        assert!(stage.any_staged());
+
+        stage.rollback_to(sp).unwrap();
+        assert!(!stage.any_staged());
+    }
+
+    #[test]
+    fn alloc_staged_merges_overlapping_write_without_compact() {
+        let mut stage = TestStage::new();
+
+        stage_write(&mut stage, 0, &[0x11, 0x11, 0x11, 0x11]).unwrap();
+        stage_write(&mut stage, 2, &[0x22, 0x22]).unwrap();
+
+        assert_eq!(entry_count(&stage), 1);
+
+        let mut seen = [0u8; 4];
+        stage
+            .iter_staged(|addr, data| {
+                assert_eq!(addr, 0);
+                seen.copy_from_slice(data);
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(seen, [0x11, 0x11, 0x22, 0x22]);
+    }
+
+    #[test]
+    fn alloc_staged_merges_adjacent_write_without_compact() {
+        let mut stage = TestStage::new();
+
+        stage_write(&mut stage, 2, &[0x03, 0x04]).unwrap();
+        stage_write(&mut stage, 0, &[0x01, 0x02]).unwrap();
+
+        assert_eq!(entry_count(&stage), 1);
+
+        let mut seen = [0u8; 4];
+        stage
+            .iter_staged(|addr, data| {
+                assert_eq!(addr, 0);
+                seen.copy_from_slice(data);
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(seen, [0x01, 0x02, 0x03, 0x04]);
+    }
+
+    #[test]
+    fn alloc_staged_keeps_entries_sorted_by_addr_for_disjoint_writes() {
+        let mut stage = TestStage::new();
+
+        stage_write(&mut stage, 20, &[0x03]).unwrap();
+        stage_write(&mut stage, 0, &[0x01]).unwrap();
+        stage_write(&mut stage, 10, &[0x02]).unwrap();
+
+        let mut addrs: heapless::Vec<u16, 4> = heapless::Vec::new();
+        stage
+            .iter_staged(|addr, _| {
+                addrs.push(addr).unwrap();
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(addrs.as_slice(), &[0, 10, 20]);
+    }
+
+    #[test]
+    fn reject_policy_allows_adjacent_but_not_overlapping_writes() {
+        let mut stage = TestStage::new().with_conflict_policy(ConflictPolicy::Reject);
+
+        stage_write(&mut stage, 0, &[0x01, 0x02]).unwrap();
+
+        // Adjacent (touches but doesn't overlap) is allowed.
+        stage_write(&mut stage, 2, &[0x03, 0x04]).unwrap();
+        assert_eq!(entry_count(&stage), 1);
+
+        // Overlapping is rejected.
+        assert_eq!(
+            stage.alloc_staged(3, 2, |data| {
+                data.copy_from_slice(&[0xAA, 0xBB]);
+                WriteResult::Dirty(())
+            }),
+            Err(ShadowError::StagingConflict)
+        );
+    }
+
+    #[cfg(feature = "lz4")]
+    #[test]
+    fn compress_staged_roundtrips_through_decompress_staged() {
+        let mut stage = TestStage::new();
+        stage_write(&mut stage, 0, &[0x01, 0x02]).unwrap();
+        stage_write(&mut stage, 20, &[0x03, 0x04, 0x05]).unwrap();
+
+        let mut compressed = [0u8; 64];
+        let compressed_len = stage.compress_staged(&mut compressed).unwrap();
+
+        let mut restored = TestStage::new();
+        let mut scratch = [0u8; 64];
+        restored
+            .decompress_staged(&compressed[..compressed_len], &mut scratch)
+            .unwrap();
+
+        assert_eq!(entry_count(&restored), 2);
+        let mut seen: heapless::Vec<(u16, heapless::Vec<u8, 4>), 4> = heapless::Vec::new();
+        restored
+            .iter_staged(|addr, data| {
+                seen.push((addr, heapless::Vec::from_slice(data).unwrap()))
+                    .unwrap();
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(seen[0].0, 0);
+        assert_eq!(seen[0].1.as_slice(), &[0x01, 0x02]);
+        assert_eq!(seen[1].0, 20);
+        assert_eq!(seen[1].1.as_slice(), &[0x03, 0x04, 0x05]);
+    }
+
+    #[cfg(feature = "lz4")]
+    #[test]
+    fn compress_staged_reports_stage_full_when_out_is_too_small() {
+        let mut stage = TestStage::new();
+        stage_write(&mut stage, 0, &[0x01, 0x02, 0x03, 0x04]).unwrap();
+
+        let mut out = [0u8; 1];
+        assert_eq!(stage.compress_staged(&mut out), Err(ShadowError::StageFull));
+    }
+
+    #[test]
+    fn verify_integrity_accepts_a_matching_crc() {
+        let mut stage = TestStage::new();
+        stage_write(&mut stage, 0, &[0x01, 0x02]).unwrap();
+        stage_write(&mut stage, 10, &[0x03]).unwrap();
+
+        let crc = stage.integrity_crc32();
+        assert_eq!(stage.verify_integrity(crc), Ok(()));
+    }
+
+    #[test]
+    fn verify_integrity_rejects_a_stale_crc_after_further_writes() {
+        let mut stage = TestStage::new();
+        stage_write(&mut stage, 0, &[0x01, 0x02]).unwrap();
+        let crc = stage.integrity_crc32();
+
+        stage_write(&mut stage, 10, &[0x03]).unwrap();
+
+        assert_eq!(
+            stage.verify_integrity(crc),
+            Err(ShadowError::ChecksumMismatch)
+        );
+    }
+
+    #[test]
+    fn would_overflow_is_always_false_without_stage_evict_lru() {
+        let mut stage = TestStage::new();
+        stage_write(&mut stage, 0, &[0xFF; 60]).unwrap();
+
+        assert!(!stage.would_overflow(60, 8));
+    }
+
+    #[test]
+    fn would_overflow_reports_true_once_stage_evict_lru_is_set_and_data_is_full() {
+        let mut stage = TestStage::new().stage_evict_lru();
+        stage_write(&mut stage, 0, &[0xFF; 60]).unwrap();
+
+        assert!(!stage.would_overflow(100, 4));
+        assert!(stage.would_overflow(100, 8));
+    }
+
+    #[test]
+    fn evict_oldest_staged_is_a_no_op_without_stage_evict_lru() {
+        let mut stage = TestStage::new();
+        stage_write(&mut stage, 0, &[0x01]).unwrap();
+
+        let mut called = false;
+        let evicted = stage
+            .evict_oldest_staged(|_, _| {
+                called = true;
+                Ok(())
+            })
+            .unwrap();
+
+        assert!(!evicted);
+        assert!(!called);
+        assert_eq!(entry_count(&stage), 1);
+    }
+
+    #[test]
+    fn evict_oldest_staged_forces_out_the_least_recently_touched_entry_first() {
+        let mut stage = TestStage::new().stage_evict_lru();
+        stage_write(&mut stage, 0, &[0x01, 0x02]).unwrap();
+        stage_write(&mut stage, 10, &[0x03, 0x04]).unwrap();
+
+        let mut seen: Option<(u16, heapless::Vec<u8, 4>)> = None;
+        let evicted = stage
+            .evict_oldest_staged(|addr, data| {
+                seen = Some((addr, heapless::Vec::from_slice(data).unwrap()));
+                Ok(())
+            })
+            .unwrap();
+
+        assert!(evicted);
+        assert_eq!(seen.unwrap(), (0, heapless::Vec::from_slice(&[0x01, 0x02]).unwrap()));
+        assert_eq!(entry_count(&stage), 1);
+
+        // addr20 is staged before addr10 is re-touched, so the re-touch
+        // jumps addr10 ahead of it in the eviction order even though
+        // addr10 was staged first.
+        stage_write(&mut stage, 20, &[0x07]).unwrap();
+        stage_write(&mut stage, 10, &[0x05, 0x06]).unwrap();
+
+        let mut seen_addr = None;
+        stage
+            .evict_oldest_staged(|addr, _| {
+                seen_addr = Some(addr);
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(seen_addr, Some(20));
+    }
 }
@@ -0,0 +1,285 @@
+#![allow(unsafe_code)]
+
+use core::ptr;
+use core::sync::atomic::{AtomicPtr, AtomicUsize, Ordering};
+
+use crate::shadow::ShadowError;
+
+/// `addr(2) + len(2)`.
+const FRAME_HEADER_LEN: usize = 4;
+
+/// Sentinel `addr` written at the ring's tail when a frame doesn't fit
+/// before wrap-around, telling the consumer to skip the rest of the tail
+/// and resume at physical offset 0.
+const WRAP_MARKER_ADDR: u16 = u16::MAX;
+
+/// Lock-free single-producer/single-consumer staging queue for handing
+/// writes from a host running in thread context to a kernel drained from
+/// an IRQ, with no critical section on either side.
+///
+/// Unlike [`StagingBuffer`](crate::shadow::types::StagingBuffer) — which
+/// assumes one exclusive owner drives both staging and commit through
+/// [`HostViewStaged`](crate::shadow::view::HostViewStaged) under a single
+/// critical section — this queue is meant to be reached by two
+/// independent execution contexts at once: the producer calls
+/// [`Self::write_staged`] directly, and the consumer calls
+/// [`Self::for_each_staged`] to drain and apply them (typically followed
+/// by committing the results to the shadow table via
+/// `KernelShadow::with_view`). It does not implement `StagingBuffer`,
+/// since that trait's `alloc_staged`/`iter_staged` assume a single caller
+/// drives staging end to end — this queue's two sides are meant to run
+/// concurrently instead.
+///
+/// Meant to live in a `static`: build with [`Self::new`] (no backing
+/// storage, so it's `const`-constructible), then call [`Self::init`] once
+/// at startup with a backing slice before the producer/consumer sides run
+/// concurrently.
+pub struct SpscStagingQueue {
+    buf: AtomicPtr<u8>,
+    cap: AtomicUsize,
+    start: AtomicUsize,
+    end: AtomicUsize,
+}
+
+impl SpscStagingQueue {
+    /// Creates an uninitialized queue. Call [`Self::init`] before use;
+    /// until then, [`Self::write_staged`] returns `StageFull` and
+    /// [`Self::for_each_staged`] is a no-op.
+    pub const fn new() -> Self {
+        Self {
+            buf: AtomicPtr::new(ptr::null_mut()),
+            cap: AtomicUsize::new(0),
+            start: AtomicUsize::new(0),
+            end: AtomicUsize::new(0),
+        }
+    }
+
+    /// Backs this queue with `backing`.
+    ///
+    /// # Safety
+    /// Caller must ensure `backing` outlives this queue and that nothing
+    /// else accesses it for as long as the queue is in use. Must be
+    /// called once, before the producer and consumer sides can run
+    /// concurrently — it is not itself synchronized against a concurrent
+    /// [`Self::write_staged`]/[`Self::for_each_staged`] call.
+    pub unsafe fn init(&self, backing: &mut [u8]) {
+        self.cap.store(backing.len(), Ordering::Relaxed);
+        self.buf.store(backing.as_mut_ptr(), Ordering::Release);
+    }
+
+    /// Stages a write of `data` at `addr`.
+    ///
+    /// Reserves a framed record `[addr: u16 le][len: u16 le][data…]` at the
+    /// ring's tail and publishes it by storing the new end cursor with
+    /// `Release` ordering, so [`Self::for_each_staged`]'s matching
+    /// `Acquire` load observes every byte written here.
+    ///
+    /// # Errors
+    /// * [`ShadowError::ZeroLength`] - if `data` is empty.
+    /// * [`ShadowError::StageFull`] - if the free span is too small, or
+    ///   the queue hasn't been [`Self::init`]ialized.
+    pub fn write_staged(&self, addr: u16, data: &[u8]) -> Result<(), ShadowError> {
+        if data.is_empty() {
+            return Err(ShadowError::ZeroLength);
+        }
+        if data.len() > u16::MAX as usize {
+            return Err(ShadowError::StageFull);
+        }
+        let frame_len = data.len();
+        let needed = FRAME_HEADER_LEN + frame_len;
+
+        let cap = self.cap.load(Ordering::Relaxed);
+        if cap == 0 {
+            return Err(ShadowError::StageFull);
+        }
+
+        let end = self.end.load(Ordering::Relaxed);
+        let start = self.start.load(Ordering::Acquire);
+
+        let phys_end = end % cap;
+        let remaining_to_tail = cap - phys_end;
+        let wraps = remaining_to_tail < needed;
+        let write_end = if wraps { end + remaining_to_tail } else { end };
+
+        if write_end + needed - start > cap {
+            return Err(ShadowError::StageFull);
+        }
+
+        if wraps && remaining_to_tail >= FRAME_HEADER_LEN {
+            self.write_bytes(phys_end, &WRAP_MARKER_ADDR.to_le_bytes());
+            self.write_bytes(phys_end + 2, &0u16.to_le_bytes());
+        }
+
+        let phys = write_end % cap;
+        self.write_bytes(phys, &addr.to_le_bytes());
+        self.write_bytes(phys + 2, &(frame_len as u16).to_le_bytes());
+        self.write_bytes(phys + FRAME_HEADER_LEN, data);
+
+        self.end.store(write_end + needed, Ordering::Release);
+        Ok(())
+    }
+
+    /// Drains every staged write in order, invoking `f(addr, data)` for
+    /// each, then publishes the new start cursor with `Release` ordering.
+    ///
+    /// `f` runs entirely inside this call, reading directly out of the
+    /// ring — the caller must ensure only one consumer calls this at a
+    /// time.
+    pub fn for_each_staged(&self, mut f: impl FnMut(u16, &[u8])) {
+        let cap = self.cap.load(Ordering::Relaxed);
+        if cap == 0 {
+            return;
+        }
+
+        let end = self.end.load(Ordering::Acquire);
+        let mut start = self.start.load(Ordering::Relaxed);
+
+        while start < end {
+            let phys_start = start % cap;
+            let remaining_to_tail = cap - phys_start;
+            if remaining_to_tail < FRAME_HEADER_LEN {
+                start += remaining_to_tail;
+                continue;
+            }
+
+            let addr = u16::from_le_bytes(self.read_bytes::<2>(phys_start));
+            if addr == WRAP_MARKER_ADDR {
+                start += remaining_to_tail;
+                continue;
+            }
+
+            let len = u16::from_le_bytes(self.read_bytes::<2>(phys_start + 2)) as usize;
+            let data = self.read_slice(phys_start + FRAME_HEADER_LEN, len);
+            f(addr, data);
+
+            start += FRAME_HEADER_LEN + len;
+        }
+
+        self.start.store(start, Ordering::Release);
+    }
+
+    fn write_bytes(&self, phys: usize, bytes: &[u8]) {
+        let base = self.buf.load(Ordering::Acquire);
+        for (i, &b) in bytes.iter().enumerate() {
+            unsafe { ptr::write(base.add(phys + i), b) };
+        }
+    }
+
+    fn read_bytes<const N: usize>(&self, phys: usize) -> [u8; N] {
+        let base = self.buf.load(Ordering::Acquire);
+        let mut out = [0u8; N];
+        for (i, slot) in out.iter_mut().enumerate() {
+            *slot = unsafe { ptr::read(base.add(phys + i)) };
+        }
+        out
+    }
+
+    fn read_slice(&self, phys: usize, len: usize) -> &[u8] {
+        let base = self.buf.load(Ordering::Acquire);
+        unsafe { core::slice::from_raw_parts(base.add(phys), len) }
+    }
+}
+
+impl Default for SpscStagingQueue {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_then_drain_round_trips_in_order() {
+        let mut backing = [0u8; 64];
+        let queue = SpscStagingQueue::new();
+        unsafe { queue.init(&mut backing) };
+
+        queue.write_staged(10, &[0x01, 0x02]).unwrap();
+        queue.write_staged(20, &[0x03, 0x04, 0x05]).unwrap();
+
+        let mut seen = heapless::Vec::<(u16, heapless::Vec<u8, 8>), 4>::new();
+        queue.for_each_staged(|addr, data| {
+            seen.push((addr, heapless::Vec::from_slice(data).unwrap()))
+                .unwrap();
+        });
+
+        assert_eq!(seen.len(), 2);
+        assert_eq!(seen[0].0, 10);
+        assert_eq!(&seen[0].1[..], &[0x01, 0x02]);
+        assert_eq!(seen[1].0, 20);
+        assert_eq!(&seen[1].1[..], &[0x03, 0x04, 0x05]);
+    }
+
+    #[test]
+    fn drain_is_empty_after_consuming_everything() {
+        let mut backing = [0u8; 64];
+        let queue = SpscStagingQueue::new();
+        unsafe { queue.init(&mut backing) };
+
+        queue.write_staged(0, &[0xAA]).unwrap();
+        queue.for_each_staged(|_, _| {});
+
+        let mut count = 0;
+        queue.for_each_staged(|_, _| count += 1);
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    fn wraps_around_the_tail_without_splitting_a_frame() {
+        // 12-byte ring: a 4-byte frame (header + 0 data... use 1 byte data
+        // => 5 bytes) fits twice with 2 bytes left over, too small for a
+        // third frame's header, forcing a wrap.
+        let mut backing = [0u8; 12];
+        let queue = SpscStagingQueue::new();
+        unsafe { queue.init(&mut backing) };
+
+        queue.write_staged(1, &[0x11]).unwrap(); // bytes 0..5
+        queue.write_staged(2, &[0x22]).unwrap(); // bytes 5..10
+
+        // Drain both so bytes 0..5 are free again for the wrapped frame.
+        let mut seen = heapless::Vec::<u16, 4>::new();
+        queue.for_each_staged(|addr, data| {
+            assert_eq!(data.len(), 1);
+            seen.push(addr).unwrap();
+        });
+
+        queue.write_staged(3, &[0x33]).unwrap(); // doesn't fit in remaining 2, wraps to 0
+        queue.for_each_staged(|addr, data| {
+            assert_eq!(data.len(), 1);
+            seen.push(addr).unwrap();
+        });
+
+        assert_eq!(&seen[..], &[1, 2, 3]);
+    }
+
+    #[test]
+    fn stage_full_when_free_span_too_small() {
+        let mut backing = [0u8; 8];
+        let queue = SpscStagingQueue::new();
+        unsafe { queue.init(&mut backing) };
+
+        queue.write_staged(0, &[0x01, 0x02, 0x03]).unwrap();
+        assert_eq!(
+            queue.write_staged(1, &[0x04, 0x05, 0x06]),
+            Err(ShadowError::StageFull)
+        );
+    }
+
+    #[test]
+    fn zero_length_write_rejected() {
+        let mut backing = [0u8; 16];
+        let queue = SpscStagingQueue::new();
+        unsafe { queue.init(&mut backing) };
+
+        assert_eq!(queue.write_staged(0, &[]), Err(ShadowError::ZeroLength));
+    }
+
+    #[test]
+    fn uninitialized_queue_is_stage_full() {
+        let queue = SpscStagingQueue::new();
+        assert_eq!(queue.write_staged(0, &[0x01]), Err(ShadowError::StageFull));
+        queue.for_each_staged(|_, _| panic!("nothing should be staged"));
+    }
+}
@@ -3,12 +3,17 @@
 use core::{cell::UnsafeCell, marker::PhantomData};
 
 use crate::shadow::{
-    ShadowError,
+    backend::{DenseBackend, TableBackend},
+    backing::{BackingStore, NoBackingStore},
+    cache::{CacheMaintenance, NoCache},
+    codec::{Codec, NoCodec},
+    fault::{AccessFaultHandler, NoFaultHandler},
     handle::{HostShadow, KernelShadow},
     persist::PersistTrigger,
     policy::{AccessPolicy, PersistPolicy},
     table::ShadowTable,
     types::StagingBuffer,
+    ShadowError,
 };
 
 /// Marker type for storage without staging support.
@@ -32,31 +37,114 @@ pub struct WithStage<SB: StagingBuffer> {
 /// - `PT`: Persist trigger receiving persistence requests
 /// - `PK`: Persist key type used to identify regions
 /// - `SS`: Stage state (`NoStage` or `WithStage<SB>`)
-pub struct ShadowStorageBase<const TS: usize, const BS: usize, const BC: usize, AP, PP, PT, PK, SS>
-where
+/// - `CC`: Block [`Codec`] applied when serializing persisted blocks (defaults to [`NoCodec`])
+/// - `FH`: [`AccessFaultHandler`] notified of policy denials (defaults to [`NoFaultHandler`])
+/// - `CM`: [`CacheMaintenance`] hooks for DMA-backed tables (defaults to [`NoCache`])
+/// - `TB`: [`TableBackend`] backing the shadow table's raw bytes (defaults to [`DenseBackend`])
+/// - `BK`: [`BackingStore`] read-through fill for never-written addresses (defaults to [`NoBackingStore`])
+pub struct ShadowStorageBase<
+    const TS: usize,
+    const BS: usize,
+    const BC: usize,
+    AP,
+    PP,
+    PT,
+    PK,
+    SS,
+    CC = NoCodec,
+    FH = NoFaultHandler,
+    CM = NoCache,
+    TB = DenseBackend<TS>,
+    BK = NoBackingStore,
+> where
     AP: AccessPolicy,
     PP: PersistPolicy<PK>,
     PT: PersistTrigger<PK>,
+    CC: Codec,
+    FH: AccessFaultHandler,
+    CM: CacheMaintenance,
+    TB: TableBackend<TS>,
+    BK: BackingStore,
     bitmaps::BitsImpl<BC>: bitmaps::Bits,
 {
-    pub(crate) table: UnsafeCell<ShadowTable<TS, BS, BC>>,
+    pub(crate) table: UnsafeCell<ShadowTable<TS, BS, BC, TB>>,
     pub(crate) access_policy: AP,
     pub(crate) persist_policy: PP,
     pub(crate) persist_trigger: UnsafeCell<PT>,
     pub(crate) stage_state: UnsafeCell<SS>,
+    pub(crate) compression: CC,
+    pub(crate) fault_handler: UnsafeCell<FH>,
+    pub(crate) cache: UnsafeCell<CM>,
+    pub(crate) backing_store: BK,
+    #[cfg(feature = "async")]
+    pub(crate) dirty_signal: crate::shadow::notify::DirtySignal,
     _phantom: PhantomData<PK>,
 }
 
-/// Shadow storage without staging support (type alias).
-pub type ShadowStorage<const TS: usize, const BS: usize, const BC: usize, AP, PP, PT, PK> =
-    ShadowStorageBase<TS, BS, BC, AP, PP, PT, PK, NoStage>;
+// SAFETY: every `UnsafeCell` field is only ever dereferenced through
+// `HostShadow`/`KernelShadow`, which either hold a `critical_section` for
+// the duration of the access (`sync` feature) or document exclusive access
+// as the caller's responsibility (the `_unchecked` variants). Sharing a
+// `&ShadowStorageBase` across execution contexts — e.g. a `static` read
+// from both a main loop and an ISR — is therefore sound. The `async`
+// feature's `dirty_signal` relies on the same discipline: it uses a
+// `NoopRawMutex` rather than a `Sync` one, since it's only ever touched
+// from inside that same `critical_section`.
+unsafe impl<
+        const TS: usize,
+        const BS: usize,
+        const BC: usize,
+        AP,
+        PP,
+        PT,
+        PK,
+        SS,
+        CC,
+        FH,
+        CM,
+        TB,
+        BK,
+    > Sync for ShadowStorageBase<TS, BS, BC, AP, PP, PT, PK, SS, CC, FH, CM, TB, BK>
+where
+    AP: AccessPolicy,
+    PP: PersistPolicy<PK>,
+    PT: PersistTrigger<PK>,
+    CC: Codec,
+    FH: AccessFaultHandler,
+    CM: CacheMaintenance,
+    TB: TableBackend<TS>,
+    BK: BackingStore,
+    bitmaps::BitsImpl<BC>: bitmaps::Bits,
+{
+}
 
-impl<const TS: usize, const BS: usize, const BC: usize, AP, PP, PT, PK>
-    ShadowStorageBase<TS, BS, BC, AP, PP, PT, PK, NoStage>
+/// Shadow storage without staging support (type alias).
+pub type ShadowStorage<
+    const TS: usize,
+    const BS: usize,
+    const BC: usize,
+    AP,
+    PP,
+    PT,
+    PK,
+    CC = NoCodec,
+    FH = NoFaultHandler,
+    CM = NoCache,
+    TB = DenseBackend<TS>,
+    BK = NoBackingStore,
+> = ShadowStorageBase<TS, BS, BC, AP, PP, PT, PK, NoStage, CC, FH, CM, TB, BK>;
+
+impl<const TS: usize, const BS: usize, const BC: usize, AP, PP, PT, PK, CC, FH, CM, TB, BK>
+    ShadowStorageBase<TS, BS, BC, AP, PP, PT, PK, NoStage, CC, FH, CM, TB, BK>
 where
     AP: AccessPolicy,
     PP: PersistPolicy<PK>,
     PT: PersistTrigger<PK>,
+    CC: Codec + Default,
+    FH: AccessFaultHandler + Default,
+    CM: CacheMaintenance + Default,
+    TB: TableBackend<TS> + Default,
+    BK: BackingStore + Default,
     bitmaps::BitsImpl<BC>: bitmaps::Bits,
 {
     pub fn new(policy: AP, persist: PP, trigger: PT) -> Self {
@@ -66,21 +154,47 @@ where
             persist_policy: persist,
             persist_trigger: UnsafeCell::new(trigger),
             stage_state: UnsafeCell::new(NoStage),
+            compression: CC::default(),
+            fault_handler: UnsafeCell::new(FH::default()),
+            cache: UnsafeCell::new(CM::default()),
+            backing_store: BK::default(),
+            #[cfg(feature = "async")]
+            dirty_signal: crate::shadow::notify::DirtySignal::new(),
             _phantom: PhantomData,
         }
     }
+}
 
+impl<const TS: usize, const BS: usize, const BC: usize, AP, PP, PT, PK, CC, FH, CM, TB, BK>
+    ShadowStorageBase<TS, BS, BC, AP, PP, PT, PK, NoStage, CC, FH, CM, TB, BK>
+where
+    AP: AccessPolicy,
+    PP: PersistPolicy<PK>,
+    PT: PersistTrigger<PK>,
+    CC: Codec,
+    FH: AccessFaultHandler,
+    CM: CacheMaintenance,
+    TB: TableBackend<TS>,
+    BK: BackingStore,
+    bitmaps::BitsImpl<BC>: bitmaps::Bits,
+{
     /// Upgrade this storage to staged mode by supplying a staging implementation.
     pub fn with_staging<SB: StagingBuffer>(
         self,
         sb: SB,
-    ) -> ShadowStorageBase<TS, BS, BC, AP, PP, PT, PK, WithStage<SB>> {
+    ) -> ShadowStorageBase<TS, BS, BC, AP, PP, PT, PK, WithStage<SB>, CC, FH, CM, TB, BK> {
         ShadowStorageBase {
             table: self.table,
             access_policy: self.access_policy,
             persist_policy: self.persist_policy,
             persist_trigger: self.persist_trigger,
             stage_state: UnsafeCell::new(WithStage { sb }),
+            compression: self.compression,
+            fault_handler: self.fault_handler,
+            cache: self.cache,
+            backing_store: self.backing_store,
+            #[cfg(feature = "async")]
+            dirty_signal: self.dirty_signal,
             _phantom: PhantomData,
         }
     }
@@ -89,22 +203,177 @@ where
 /// Write function type for [`ShadowStorageBase::load_defaults`].
 pub type WriteFn = dyn FnMut(u16, &[u8]) -> Result<(), ShadowError>;
 
-impl<const TS: usize, const BS: usize, const BC: usize, AP, PP, PT, PK, SS>
-    ShadowStorageBase<TS, BS, BC, AP, PP, PT, PK, SS>
+impl<const TS: usize, const BS: usize, const BC: usize, AP, PP, PT, PK, SS, CC, FH, CM, TB, BK>
+    ShadowStorageBase<TS, BS, BC, AP, PP, PT, PK, SS, CC, FH, CM, TB, BK>
 where
     AP: AccessPolicy,
     PP: PersistPolicy<PK>,
     PT: PersistTrigger<PK>,
+    CC: Codec,
+    FH: AccessFaultHandler,
+    CM: CacheMaintenance,
+    TB: TableBackend<TS>,
+    BK: BackingStore,
     bitmaps::BitsImpl<BC>: bitmaps::Bits,
 {
-    pub fn host_shadow(&self) -> HostShadow<'_, TS, BS, BC, AP, PP, PT, PK, SS> {
+    pub fn host_shadow(
+        &self,
+    ) -> HostShadow<'_, TS, BS, BC, AP, PP, PT, PK, SS, CC, FH, CM, TB, BK> {
         HostShadow::new(self)
     }
 
-    pub fn kernel_shadow(&self) -> KernelShadow<'_, TS, BS, BC, AP, PP, PT, PK, SS> {
+    pub fn kernel_shadow(
+        &self,
+    ) -> KernelShadow<'_, TS, BS, BC, AP, PP, PT, PK, SS, CC, FH, CM, TB, BK> {
         KernelShadow::new(self)
     }
 
+    /// Swaps in a different block [`Codec`], applied when serializing
+    /// persisted blocks and inverted by [`Self::decode_block`] on restore.
+    pub fn with_compression<CC2: Codec>(
+        self,
+        compression: CC2,
+    ) -> ShadowStorageBase<TS, BS, BC, AP, PP, PT, PK, SS, CC2, FH, CM, TB, BK> {
+        ShadowStorageBase {
+            table: self.table,
+            access_policy: self.access_policy,
+            persist_policy: self.persist_policy,
+            persist_trigger: self.persist_trigger,
+            stage_state: self.stage_state,
+            compression,
+            fault_handler: self.fault_handler,
+            cache: self.cache,
+            backing_store: self.backing_store,
+            #[cfg(feature = "async")]
+            dirty_signal: self.dirty_signal,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Swaps in a different [`AccessFaultHandler`], notified whenever the
+    /// access policy denies a read or write.
+    pub fn with_fault_handler<FH2: AccessFaultHandler>(
+        self,
+        fault_handler: FH2,
+    ) -> ShadowStorageBase<TS, BS, BC, AP, PP, PT, PK, SS, CC, FH2, CM, TB, BK> {
+        ShadowStorageBase {
+            table: self.table,
+            access_policy: self.access_policy,
+            persist_policy: self.persist_policy,
+            persist_trigger: self.persist_trigger,
+            stage_state: self.stage_state,
+            compression: self.compression,
+            fault_handler: UnsafeCell::new(fault_handler),
+            cache: self.cache,
+            backing_store: self.backing_store,
+            #[cfg(feature = "async")]
+            dirty_signal: self.dirty_signal,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Swaps in a different [`CacheMaintenance`], invoked to keep a CPU
+    /// data cache coherent with shadow bytes a DMA engine reads or writes
+    /// directly — see [`HostViewStaged::commit_staged`](crate::shadow::HostViewStaged::commit_staged)
+    /// and [`KernelShadow::flush_dirty`](crate::shadow::KernelShadow::flush_dirty).
+    pub fn with_cache_maintenance<CM2: CacheMaintenance>(
+        self,
+        cache: CM2,
+    ) -> ShadowStorageBase<TS, BS, BC, AP, PP, PT, PK, SS, CC, FH, CM2, TB, BK> {
+        ShadowStorageBase {
+            table: self.table,
+            access_policy: self.access_policy,
+            persist_policy: self.persist_policy,
+            persist_trigger: self.persist_trigger,
+            stage_state: self.stage_state,
+            compression: self.compression,
+            fault_handler: self.fault_handler,
+            cache: UnsafeCell::new(cache),
+            backing_store: self.backing_store,
+            #[cfg(feature = "async")]
+            dirty_signal: self.dirty_signal,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Swaps in a different [`TableBackend`], replacing the shadow table's
+    /// raw byte store (e.g. a dense array for a
+    /// [`SparseBackend`](crate::shadow::backend::SparseBackend)). Since the
+    /// backend is owned by the table itself, this rebuilds the table from
+    /// scratch with `backend`, discarding any data or dirty state
+    /// previously held — call this before the storage is used.
+    pub fn with_backend<TB2: TableBackend<TS>>(
+        self,
+        backend: TB2,
+    ) -> ShadowStorageBase<TS, BS, BC, AP, PP, PT, PK, SS, CC, FH, CM, TB2, BK> {
+        ShadowStorageBase {
+            table: UnsafeCell::new(ShadowTable::with_backend(backend)),
+            access_policy: self.access_policy,
+            persist_policy: self.persist_policy,
+            persist_trigger: self.persist_trigger,
+            stage_state: self.stage_state,
+            compression: self.compression,
+            fault_handler: self.fault_handler,
+            cache: self.cache,
+            backing_store: self.backing_store,
+            #[cfg(feature = "async")]
+            dirty_signal: self.dirty_signal,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Swaps in a different [`BackingStore`], consulted by
+    /// [`HostView::with_ro_slice`](crate::shadow::HostView::with_ro_slice) and
+    /// [`HostView::with_rw_slice`](crate::shadow::HostView::with_rw_slice) to
+    /// fill addresses the shadow table has never been written to.
+    pub fn with_backing_store<BK2: BackingStore>(
+        self,
+        backing_store: BK2,
+    ) -> ShadowStorageBase<TS, BS, BC, AP, PP, PT, PK, SS, CC, FH, CM, TB, BK2> {
+        ShadowStorageBase {
+            table: self.table,
+            access_policy: self.access_policy,
+            persist_policy: self.persist_policy,
+            persist_trigger: self.persist_trigger,
+            stage_state: self.stage_state,
+            compression: self.compression,
+            fault_handler: self.fault_handler,
+            cache: self.cache,
+            backing_store,
+            #[cfg(feature = "async")]
+            dirty_signal: self.dirty_signal,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Encodes a persisted block payload through the configured [`Codec`].
+    pub fn encode_block(&self, block: &[u8], out: &mut [u8]) -> Result<usize, ShadowError> {
+        self.compression.encode(block, out)
+    }
+
+    /// Decodes a persisted block payload through the configured [`Codec`],
+    /// inverting [`Self::encode_block`] on restore.
+    pub fn decode_block(&self, encoded: &[u8], out: &mut [u8]) -> Result<usize, ShadowError> {
+        self.compression.decode(encoded, out)
+    }
+}
+
+impl<const TS: usize, const BS: usize, const BC: usize, AP, PP, PT, PK, SS, CC, FH, CM, TB, BK>
+    ShadowStorageBase<TS, BS, BC, AP, PP, PT, PK, SS, CC, FH, CM, TB, BK>
+where
+    AP: AccessPolicy,
+    PP: PersistPolicy<PK>,
+    PT: PersistTrigger<PK>,
+    CC: Codec,
+    FH: AccessFaultHandler,
+    CM: CacheMaintenance,
+    // `f` coerces its `write` closure to `&mut WriteFn`, which implicitly
+    // bounds the closure's captured data (including `TB`, via the `table`
+    // it closes over) by `'static`.
+    TB: TableBackend<TS> + 'static,
+    BK: BackingStore,
+    bitmaps::BitsImpl<BC>: bitmaps::Bits,
+{
     /// Load initial values into the shadow table without marking dirty.
     ///
     /// Use this during system initialization to populate the shadow
@@ -144,7 +413,12 @@ where
 
 #[cfg(test)]
 mod tests {
-    use crate::shadow::{WriteResult, test_support::test_storage};
+    #[cfg(feature = "async")]
+    use crate::shadow::persist::AsyncPersistBackend;
+    use crate::shadow::{
+        persist::PersistBackend, policy::PersistPolicy, test_support::test_storage, AllowAllPolicy,
+        DmaDirection, NoPersist, NoPersistPolicy, ShadowError, ShadowStorage, WriteResult,
+    };
 
     #[test]
     fn load_defaults_writes_data_without_marking_dirty() {
@@ -315,4 +589,261 @@ mod tests {
             assert!(!view.is_dirty(0, 16).unwrap());
         });
     }
+
+    /// Persist policy keying each dirty block by its own address, so a test
+    /// [`PersistBackend`] can assert exactly which blocks were committed.
+    #[derive(Default)]
+    struct AddrKeyedPolicy;
+
+    impl PersistPolicy<u16> for AddrKeyedPolicy {
+        fn push_persist_keys_for_range<F>(&self, addr: u16, _len: usize, mut push_key: F) -> bool
+        where
+            F: FnMut(u16),
+        {
+            push_key(addr);
+            true
+        }
+    }
+
+    #[derive(Default)]
+    struct RecordingBackend {
+        committed: [(u16, [u8; 16]); 4],
+        count: usize,
+        fail_addr: Option<u16>,
+    }
+
+    impl PersistBackend<u16> for RecordingBackend {
+        fn persist(&mut self, key: u16, addr: u16, data: &[u8]) -> Result<(), ShadowError> {
+            assert_eq!(key, addr, "policy keys blocks by their own address");
+            if self.fail_addr == Some(addr) {
+                return Err(ShadowError::PersistFailed);
+            }
+            let mut buf = [0u8; 16];
+            buf[..data.len()].copy_from_slice(data);
+            self.committed[self.count] = (addr, buf);
+            self.count += 1;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn flush_dirty_commits_and_clears_dirty_blocks() {
+        let storage: ShadowStorage<64, 16, 4, AllowAllPolicy, AddrKeyedPolicy, NoPersist, u16> =
+            ShadowStorage::new(AllowAllPolicy::default(), AddrKeyedPolicy, NoPersist);
+
+        storage.host_shadow().with_view(|view| {
+            view.with_wo_slice(0, 4, |mut slice| {
+                slice.copy_from_slice(&[0x11, 0x22, 0x33, 0x44]);
+                WriteResult::Dirty(())
+            })
+            .unwrap();
+        });
+
+        let mut backend = RecordingBackend::default();
+        storage.kernel_shadow().flush_dirty(&mut backend).unwrap();
+
+        assert_eq!(backend.count, 1);
+        assert_eq!(backend.committed[0].0, 0);
+        assert_eq!(&backend.committed[0].1[..4], &[0x11, 0x22, 0x33, 0x44]);
+
+        storage.kernel_shadow().with_view(|view| {
+            assert!(!view.is_dirty(0, 16).unwrap());
+        });
+    }
+
+    #[test]
+    fn flush_dirty_leaves_block_dirty_on_backend_failure() {
+        let storage: ShadowStorage<64, 16, 4, AllowAllPolicy, AddrKeyedPolicy, NoPersist, u16> =
+            ShadowStorage::new(AllowAllPolicy::default(), AddrKeyedPolicy, NoPersist);
+
+        storage.host_shadow().with_view(|view| {
+            view.with_wo_slice(0, 4, |mut slice| {
+                slice.copy_from_slice(&[1, 2, 3, 4]);
+                WriteResult::Dirty(())
+            })
+            .unwrap();
+        });
+
+        let mut backend = RecordingBackend {
+            fail_addr: Some(0),
+            ..Default::default()
+        };
+        let result = storage.kernel_shadow().flush_dirty(&mut backend);
+
+        assert_eq!(result, Err(ShadowError::PersistFailed));
+        storage.kernel_shadow().with_view(|view| {
+            assert!(view.is_dirty(0, 16).unwrap());
+        });
+    }
+
+    #[cfg(feature = "async")]
+    #[derive(Default)]
+    struct RecordingAsyncBackend {
+        committed: [(u16, [u8; 16]); 4],
+        count: usize,
+        fail_addr: Option<u16>,
+    }
+
+    #[cfg(feature = "async")]
+    impl AsyncPersistBackend<u16> for RecordingAsyncBackend {
+        async fn persist(&mut self, key: u16, addr: u16, data: &[u8]) -> Result<(), ShadowError> {
+            assert_eq!(key, addr, "policy keys blocks by their own address");
+            if self.fail_addr == Some(addr) {
+                return Err(ShadowError::PersistFailed);
+            }
+            let mut buf = [0u8; 16];
+            buf[..data.len()].copy_from_slice(data);
+            self.committed[self.count] = (addr, buf);
+            self.count += 1;
+            Ok(())
+        }
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn flush_dirty_async_commits_and_clears_dirty_blocks() {
+        use core::{future::Future, task::Context};
+
+        let storage: ShadowStorage<64, 16, 4, AllowAllPolicy, AddrKeyedPolicy, NoPersist, u16> =
+            ShadowStorage::new(AllowAllPolicy::default(), AddrKeyedPolicy, NoPersist);
+
+        storage.host_shadow().with_view(|view| {
+            view.with_wo_slice(0, 4, |mut slice| {
+                slice.copy_from_slice(&[0x11, 0x22, 0x33, 0x44]);
+                WriteResult::Dirty(())
+            })
+            .unwrap();
+        });
+
+        let mut backend = RecordingAsyncBackend::default();
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+        let kernel_shadow = storage.kernel_shadow();
+        let mut fut = core::pin::pin!(kernel_shadow.flush_dirty_async::<_, 4>(&mut backend));
+        assert_eq!(fut.as_mut().poll(&mut cx), core::task::Poll::Ready(Ok(())));
+
+        assert_eq!(backend.count, 1);
+        assert_eq!(backend.committed[0].0, 0);
+        assert_eq!(&backend.committed[0].1[..4], &[0x11, 0x22, 0x33, 0x44]);
+
+        storage.kernel_shadow().with_view(|view| {
+            assert!(!view.is_dirty(0, 16).unwrap());
+        });
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn flush_dirty_async_leaves_block_dirty_on_backend_failure() {
+        use core::{future::Future, task::Context};
+
+        let storage: ShadowStorage<64, 16, 4, AllowAllPolicy, AddrKeyedPolicy, NoPersist, u16> =
+            ShadowStorage::new(AllowAllPolicy::default(), AddrKeyedPolicy, NoPersist);
+
+        storage.host_shadow().with_view(|view| {
+            view.with_wo_slice(0, 4, |mut slice| {
+                slice.copy_from_slice(&[1, 2, 3, 4]);
+                WriteResult::Dirty(())
+            })
+            .unwrap();
+        });
+
+        let mut backend = RecordingAsyncBackend {
+            fail_addr: Some(0),
+            ..Default::default()
+        };
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+        let kernel_shadow = storage.kernel_shadow();
+        let mut fut = core::pin::pin!(kernel_shadow.flush_dirty_async::<_, 4>(&mut backend));
+        assert_eq!(
+            fut.as_mut().poll(&mut cx),
+            core::task::Poll::Ready(Err(ShadowError::PersistFailed))
+        );
+
+        storage.kernel_shadow().with_view(|view| {
+            assert!(view.is_dirty(0, 16).unwrap());
+        });
+    }
+
+    #[cfg(feature = "async")]
+    fn noop_waker() -> core::task::Waker {
+        use core::task::{RawWaker, RawWakerVtable};
+
+        fn clone(_: *const ()) -> RawWaker {
+            raw()
+        }
+        fn no_op(_: *const ()) {}
+        static VTABLE: RawWakerVtable = RawWakerVtable::new(clone, no_op, no_op, no_op);
+        fn raw() -> RawWaker {
+            RawWaker::new(core::ptr::null(), &VTABLE)
+        }
+
+        // SAFETY: the vtable's functions are all no-ops, so the `Waker` does
+        // nothing but satisfy the `Future::poll` signature.
+        unsafe { core::task::Waker::from_raw(raw()) }
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn wait_dirty_resolves_immediately_when_already_dirty() {
+        use core::{future::Future, task::Context};
+
+        let storage = test_storage();
+        storage.host_shadow().with_view(|view| {
+            view.with_wo_slice(0, 4, |mut slice| {
+                slice.copy_from_slice(&[1, 2, 3, 4]);
+                WriteResult::Dirty(())
+            })
+            .unwrap();
+        });
+
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+        let kernel_shadow = storage.kernel_shadow();
+        let mut fut = core::pin::pin!(kernel_shadow.wait_dirty(0, 4));
+        assert_eq!(fut.as_mut().poll(&mut cx), core::task::Poll::Ready(Ok(())));
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn wait_dirty_wakes_after_a_host_write() {
+        use core::{future::Future, task::Context};
+
+        let storage = test_storage();
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+        let kernel_shadow = storage.kernel_shadow();
+        let mut fut = core::pin::pin!(kernel_shadow.wait_dirty(0, 4));
+        assert_eq!(fut.as_mut().poll(&mut cx), core::task::Poll::Pending);
+
+        storage.host_shadow().with_view(|view| {
+            view.with_wo_slice(0, 4, |mut slice| {
+                slice.copy_from_slice(&[1, 2, 3, 4]);
+                WriteResult::Dirty(())
+            })
+            .unwrap();
+        });
+
+        assert_eq!(fut.as_mut().poll(&mut cx), core::task::Poll::Ready(Ok(())));
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn wait_dirty_wakes_after_a_kernel_dma_completes() {
+        use core::{future::Future, task::Context};
+
+        let storage = test_storage();
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+        let kernel_shadow = storage.kernel_shadow();
+        let mut fut = core::pin::pin!(kernel_shadow.wait_dirty(0, 4));
+        assert_eq!(fut.as_mut().poll(&mut cx), core::task::Poll::Pending);
+
+        let region = kernel_shadow
+            .with_view(|view| view.with_dma_region(0, 4, DmaDirection::DeviceToMemory))
+            .unwrap();
+        kernel_shadow.complete_dma(region).unwrap();
+
+        assert_eq!(fut.as_mut().poll(&mut cx), core::task::Poll::Ready(Ok(())));
+    }
 }
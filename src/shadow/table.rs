@@ -1,19 +1,45 @@
 use crate::shadow::{
+    backend::{DenseBackend, TableBackend},
     error::ShadowError,
-    helpers::{block_span, range_span},
+    helpers::{block_span, crc32, range_span},
+    types::DirtyLease,
 };
 
-pub(crate) struct ShadowTable<const TS: usize, const BS: usize, const BC: usize>
-where
+/// `addr: u16 LE` + `len: u16 LE`, preceding each record's payload in the
+/// [`ShadowTable::encode_dirty_deltas`] stream format.
+const DELTA_HEADER_LEN: usize = 2 + 2;
+
+/// Trailing CRC-32 length in a [`ShadowTable::encode_dirty_deltas`] record.
+const DELTA_CRC_LEN: usize = 4;
+
+pub(crate) struct ShadowTable<
+    const TS: usize,
+    const BS: usize,
+    const BC: usize,
+    TB = DenseBackend<TS>,
+> where
     bitmaps::BitsImpl<BC>: bitmaps::Bits,
+    TB: TableBackend<TS>,
 {
-    bytes: [u8; TS],
+    backend: TB,
     dirty: bitmaps::Bitmap<BC>,
+    in_flight: bitmaps::Bitmap<BC>,
+    pinned: bitmaps::Bitmap<BC>,
+    locked: bitmaps::Bitmap<BC>,
+    /// Blocks that hold real data, either from a host/kernel write or a
+    /// [`BackingStore`](crate::shadow::backing::BackingStore) read-through
+    /// fill. Never-populated blocks are where `with_ro_slice` falls through
+    /// to the backing store instead of returning whatever the backend's
+    /// fill byte happens to be.
+    populated: bitmaps::Bitmap<BC>,
+    generations: [u32; BC],
+    cursor: usize,
 }
 
-impl<const TS: usize, const BS: usize, const BC: usize> ShadowTable<TS, BS, BC>
+impl<const TS: usize, const BS: usize, const BC: usize, TB> ShadowTable<TS, BS, BC, TB>
 where
     bitmaps::BitsImpl<BC>: bitmaps::Bits,
+    TB: TableBackend<TS> + Default,
 {
     pub(crate) fn new() -> Self {
         debug_assert!(
@@ -21,9 +47,33 @@ where
             "Total size must match block size x block count",
         );
 
+        Self::with_backend(TB::default())
+    }
+}
+
+impl<const TS: usize, const BS: usize, const BC: usize, TB> ShadowTable<TS, BS, BC, TB>
+where
+    bitmaps::BitsImpl<BC>: bitmaps::Bits,
+    TB: TableBackend<TS>,
+{
+    /// Builds a table wrapping an already-constructed [`TableBackend`],
+    /// e.g. a [`SparseBackend`](crate::shadow::backend::SparseBackend)
+    /// configured with a non-zero fill byte.
+    pub(crate) fn with_backend(backend: TB) -> Self {
+        debug_assert!(
+            TS == BS * BC,
+            "Total size must match block size x block count",
+        );
+
         Self {
-            bytes: [0; TS],
+            backend,
             dirty: bitmaps::Bitmap::new(),
+            in_flight: bitmaps::Bitmap::new(),
+            pinned: bitmaps::Bitmap::new(),
+            locked: bitmaps::Bitmap::new(),
+            populated: bitmaps::Bitmap::new(),
+            generations: [0; BC],
+            cursor: 0,
         }
     }
 
@@ -32,7 +82,7 @@ where
         F: FnOnce(&[u8]) -> Result<R, ShadowError>,
     {
         let (offset, end) = range_span::<TS>(addr, len)?;
-        f(&self.bytes[offset..end])
+        self.backend.with_bytes(offset, end, f)
     }
 
     pub(crate) fn with_bytes_mut<F, R>(
@@ -45,9 +95,15 @@ where
         F: FnOnce(&mut [u8]) -> Result<R, ShadowError>,
     {
         let (offset, end) = range_span::<TS>(addr, len)?;
-        f(&mut self.bytes[offset..end])
+        let result = self.backend.with_bytes_mut(offset, end, f)?;
+        self.mark_populated(addr, len)?;
+        Ok(result)
     }
 
+    /// Iterates over every dirty block individually, one `(addr, bytes)`
+    /// call per `BS`-sized block. See [`Self::iter_dirty_runs`] for a
+    /// variant that coalesces adjacent dirty blocks into a single span per
+    /// maximal run, cutting the number of bus transactions a flush issues.
     pub(crate) fn iter_dirty<F>(&self, mut f: F) -> Result<(), ShadowError>
     where
         F: FnMut(u16, &[u8]) -> Result<(), ShadowError>,
@@ -55,13 +111,188 @@ where
         let mut idx = self.dirty.first_index();
         while let Some(block) = idx {
             let off = block * BS;
-            let buf = &self.bytes[off..(off + BS)];
-            f(off as u16, buf)?;
+            self.backend
+                .with_bytes(off, off + BS, |buf| f(off as u16, buf))?;
             idx = self.dirty.next_index(block);
         }
         Ok(())
     }
 
+    /// Iterates over each maximal run of contiguous dirty blocks, handing
+    /// `f` one coalesced `(addr, bytes)` span per run instead of one per
+    /// block — so a caller syncing to hardware with fixed per-transaction
+    /// overhead (a DMA burst, a flash page write) issues one transaction
+    /// per contiguous dirty region rather than one per block. [`Self::iter_dirty`]
+    /// remains available for callers that want the uncoalesced, one-block-
+    /// at-a-time view.
+    ///
+    /// Finds runs via [`bitmaps::Bitmap::first_index`]/`next_index`, which
+    /// already scan at word granularity internally (deriving each set
+    /// bit's index from its word's trailing-zero count) — reused here
+    /// rather than re-deriving the same trick against a raw word array
+    /// `Bitmap` already keeps private.
+    #[doc(alias = "iter_dirty_spans")]
+    pub(crate) fn iter_dirty_runs<F>(&self, mut f: F) -> Result<(), ShadowError>
+    where
+        F: FnMut(u16, &[u8]) -> Result<(), ShadowError>,
+    {
+        let mut idx = self.dirty.first_index();
+        while let Some(run_start) = idx {
+            let mut run_end = run_start;
+            let mut next = self.dirty.next_index(run_end);
+            while next == Some(run_end + 1) {
+                run_end += 1;
+                next = self.dirty.next_index(run_end);
+            }
+
+            let off = run_start * BS;
+            let len = (run_end - run_start + 1) * BS;
+            self.backend
+                .with_bytes(off, off + len, |buf| f(off as u16, buf))?;
+
+            idx = next;
+        }
+        Ok(())
+    }
+
+    /// Serializes every dirty run into `out` as a sequence of
+    /// self-describing, CRC-protected records, for mirroring changed
+    /// blocks to a companion chip or host over a link (UART, SPI) instead
+    /// of shipping the whole table. Returns the number of bytes written.
+    ///
+    /// Record layout, one per [`Self::iter_dirty_runs`] run: `[addr: u16
+    /// LE][len: u16 LE][payload...][crc32: u32 LE]`, the CRC computed over
+    /// everything preceding it so [`Self::decode_dirty_deltas`] can reject
+    /// a corrupted record before applying it.
+    ///
+    /// Returns [`ShadowError::OutOfBounds`] if a run's payload doesn't fit
+    /// a `u16` length field or `out` is too small for the full stream; `out`
+    /// may hold a partial stream in that case and should be discarded.
+    pub(crate) fn encode_dirty_deltas(&self, out: &mut [u8]) -> Result<usize, ShadowError> {
+        let mut pos = 0usize;
+        self.iter_dirty_runs(|addr, payload| {
+            if payload.len() > u16::MAX as usize {
+                return Err(ShadowError::OutOfBounds);
+            }
+            let record_len = DELTA_HEADER_LEN + payload.len() + DELTA_CRC_LEN;
+            let record = out
+                .get_mut(pos..pos + record_len)
+                .ok_or(ShadowError::OutOfBounds)?;
+
+            record[0..2].copy_from_slice(&addr.to_le_bytes());
+            record[2..4].copy_from_slice(&(payload.len() as u16).to_le_bytes());
+            record[DELTA_HEADER_LEN..DELTA_HEADER_LEN + payload.len()].copy_from_slice(payload);
+
+            let crc = crc32(record[..DELTA_HEADER_LEN + payload.len()].iter().copied());
+            record[DELTA_HEADER_LEN + payload.len()..record_len]
+                .copy_from_slice(&crc.to_le_bytes());
+
+            pos += record_len;
+            Ok(())
+        })?;
+        Ok(pos)
+    }
+
+    /// Applies a stream produced by [`Self::encode_dirty_deltas`], writing
+    /// each record's payload back to its address via [`Self::with_bytes_mut`]
+    /// without marking the destination dirty — so a receiver reconstructs
+    /// identical state without itself appearing to have locally modified
+    /// anything.
+    ///
+    /// Returns [`ShadowError::ChecksumMismatch`] on the first record whose
+    /// CRC doesn't match, and [`ShadowError::OutOfBounds`] if `input` is
+    /// truncated mid-record. Already-applied records before the failure are
+    /// not rolled back.
+    pub(crate) fn decode_dirty_deltas(&mut self, input: &[u8]) -> Result<(), ShadowError> {
+        let mut pos = 0usize;
+        while pos < input.len() {
+            let header = input
+                .get(pos..pos + DELTA_HEADER_LEN)
+                .ok_or(ShadowError::OutOfBounds)?;
+            let addr = u16::from_le_bytes([header[0], header[1]]);
+            let len = u16::from_le_bytes([header[2], header[3]]) as usize;
+            let record_len = DELTA_HEADER_LEN + len + DELTA_CRC_LEN;
+
+            let record = input
+                .get(pos..pos + record_len)
+                .ok_or(ShadowError::OutOfBounds)?;
+            let payload = &record[DELTA_HEADER_LEN..DELTA_HEADER_LEN + len];
+            let expected_crc = u32::from_le_bytes(
+                record[DELTA_HEADER_LEN + len..record_len]
+                    .try_into()
+                    .expect("slice is exactly 4 bytes"),
+            );
+            if crc32(record[..DELTA_HEADER_LEN + len].iter().copied()) != expected_crc {
+                return Err(ShadowError::ChecksumMismatch);
+            }
+
+            self.with_bytes_mut(addr, len, |dst| {
+                dst.copy_from_slice(payload);
+                Ok(())
+            })?;
+
+            pos += record_len;
+        }
+        Ok(())
+    }
+
+    /// Hands up to `max` dirty blocks, starting from wherever the last call
+    /// left off, to `f`. Returns `true` if it stopped at `max` with dirty
+    /// blocks still ahead of the cursor, and `false` once the scan reaches
+    /// the last block, so a caller with a hard time budget (e.g. an ISR) can
+    /// flush a bounded number of blocks per invocation instead of an
+    /// unbounded scan, and keep calling back until it gets `false`.
+    ///
+    /// The cursor persists across calls in `self.cursor`; once a scan
+    /// reaches the last block without hitting `max`, the cursor wraps back
+    /// to the top so blocks dirtied earlier (behind the cursor's current
+    /// position) are picked up on the wrap. Marking a processed block clean
+    /// remains the caller's responsibility, same as [`Self::iter_dirty`].
+    pub(crate) fn for_each_dirty_block_bounded<F>(
+        &mut self,
+        max: usize,
+        mut f: F,
+    ) -> Result<bool, ShadowError>
+    where
+        F: FnMut(u16, &[u8]) -> Result<(), ShadowError>,
+    {
+        let mut idx = self.first_dirty_at_or_after(self.cursor);
+        let mut processed = 0usize;
+
+        while let Some(block) = idx {
+            if processed >= max {
+                self.cursor = block;
+                return Ok(true);
+            }
+
+            let off = block * BS;
+            self.backend
+                .with_bytes(off, off + BS, |buf| f(off as u16, buf))?;
+            processed += 1;
+            idx = self.dirty.next_index(block);
+        }
+
+        self.cursor = 0;
+        Ok(false)
+    }
+
+    /// Restarts [`Self::for_each_dirty_block_bounded`]'s cursor from the
+    /// top of the table.
+    pub(crate) fn reset_cursor(&mut self) {
+        self.cursor = 0;
+    }
+
+    fn first_dirty_at_or_after(&self, start: usize) -> Option<usize> {
+        if start == 0 {
+            return self.dirty.first_index();
+        }
+        if self.dirty.get(start) {
+            Some(start)
+        } else {
+            self.dirty.next_index(start - 1)
+        }
+    }
+
     pub(crate) fn is_dirty(&self, addr: u16, len: usize) -> Result<bool, ShadowError> {
         let (sb, eb) = block_span::<TS, BS, BC>(addr, len)?;
         for block in sb..=eb {
@@ -92,6 +323,367 @@ where
         let (sb, eb) = block_span::<TS, BS, BC>(addr, len)?;
         for block in sb..=eb {
             self.dirty.set(block, dirty);
+            if dirty {
+                self.generations[block] = self.generations[block].wrapping_add(1);
+            }
+        }
+        Ok(())
+    }
+
+    /// Hands each dirty, not-already-leased block to `f` as a [`DirtyLease`],
+    /// marking it in-flight so it isn't leased again until
+    /// [`Self::complete_lease`] is called.
+    pub(crate) fn lease_dirty_blocks<F>(&mut self, mut f: F)
+    where
+        F: FnMut(DirtyLease<'_>),
+    {
+        let mut idx = self.dirty.first_index();
+        while let Some(block) = idx {
+            if !self.in_flight.get(block) {
+                self.in_flight.set(block, true);
+                let off = block * BS;
+                let generation = self.generations[block];
+                let _ = self.backend.with_bytes(off, off + BS, |data| {
+                    f(DirtyLease::new(off as u16, data, generation));
+                    Ok(())
+                });
+            }
+            idx = self.dirty.next_index(block);
+        }
+    }
+
+    /// Completes a lease taken by [`Self::lease_dirty_blocks`].
+    ///
+    /// Clears the block's dirty bit only if `ok` and no write touched the
+    /// block (bumping its generation) since the lease was taken; otherwise
+    /// the block is left dirty so it gets re-flushed. Always clears the
+    /// in-flight marker so the block becomes leasable again.
+    pub(crate) fn complete_lease(
+        &mut self,
+        addr: u16,
+        generation: u32,
+        ok: bool,
+    ) -> Result<(), ShadowError> {
+        let (sb, eb) = block_span::<TS, BS, BC>(addr, 1)?;
+        debug_assert_eq!(sb, eb, "lease completion addr must be block-aligned");
+        let block = sb;
+
+        self.in_flight.set(block, false);
+        if ok && self.generations[block] == generation {
+            self.dirty.set(block, false);
+        }
+        Ok(())
+    }
+
+    /// Pins every block overlapping `addr..addr+len`, e.g. for the duration
+    /// of a DMA transfer. Returns [`ShadowError::Pinned`] if any block in
+    /// the range is already pinned, so two overlapping DMA regions can
+    /// never be leased at once.
+    pub(crate) fn pin_range(&mut self, addr: u16, len: usize) -> Result<(), ShadowError> {
+        let (sb, eb) = block_span::<TS, BS, BC>(addr, len)?;
+        for block in sb..=eb {
+            if self.pinned.get(block) {
+                return Err(ShadowError::Pinned);
+            }
+        }
+        for block in sb..=eb {
+            self.pinned.set(block, true);
+        }
+        Ok(())
+    }
+
+    /// Releases a pin taken by [`Self::pin_range`].
+    pub(crate) fn unpin_range(&mut self, addr: u16, len: usize) -> Result<(), ShadowError> {
+        let (sb, eb) = block_span::<TS, BS, BC>(addr, len)?;
+        for block in sb..=eb {
+            self.pinned.set(block, false);
+        }
+        Ok(())
+    }
+
+    /// Locks every block overlapping `addr..addr+len`, so a host write
+    /// touching any of them is later rejected by [`Self::is_locked`] — e.g.
+    /// once a configuration block has been committed to hardware and
+    /// verified, firmware can lock it so a buggy host path can't silently
+    /// overwrite it. Unlike [`Self::pin_range`], locking is not exclusive:
+    /// locking an already-locked block is a no-op rather than an error.
+    pub(crate) fn lock(&mut self, addr: u16, len: usize) -> Result<(), ShadowError> {
+        let (sb, eb) = block_span::<TS, BS, BC>(addr, len)?;
+        for block in sb..=eb {
+            self.locked.set(block, true);
+        }
+        Ok(())
+    }
+
+    /// Releases a lock taken by [`Self::lock`].
+    pub(crate) fn unlock(&mut self, addr: u16, len: usize) -> Result<(), ShadowError> {
+        let (sb, eb) = block_span::<TS, BS, BC>(addr, len)?;
+        for block in sb..=eb {
+            self.locked.set(block, false);
+        }
+        Ok(())
+    }
+
+    /// Returns true if any block overlapping `addr..addr+len` is locked.
+    pub(crate) fn is_locked(&self, addr: u16, len: usize) -> Result<bool, ShadowError> {
+        let (sb, eb) = block_span::<TS, BS, BC>(addr, len)?;
+        for block in sb..=eb {
+            if self.locked.get(block) {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Returns true if every block overlapping `addr..addr+len` has been
+    /// written at least once, whether by a host/kernel write or a prior
+    /// [`Self::mark_populated`] call. False as soon as one block in the
+    /// range hasn't, so a partially-populated range still falls through to
+    /// the backing store rather than reading a mix of real and fill bytes.
+    pub(crate) fn is_populated(&self, addr: u16, len: usize) -> Result<bool, ShadowError> {
+        let (sb, eb) = block_span::<TS, BS, BC>(addr, len)?;
+        for block in sb..=eb {
+            if !self.populated.get(block) {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Marks every block overlapping `addr..addr+len` as populated, without
+    /// touching dirty state. Called automatically from
+    /// [`Self::with_bytes_mut`], since every write path (including a
+    /// backing-store read-through fill, which writes through
+    /// `with_bytes_mut` without calling [`Self::mark_dirty`]) funnels
+    /// through it.
+    pub(crate) fn mark_populated(&mut self, addr: u16, len: usize) -> Result<(), ShadowError> {
+        let (sb, eb) = block_span::<TS, BS, BC>(addr, len)?;
+        for block in sb..=eb {
+            self.populated.set(block, true);
+        }
+        Ok(())
+    }
+
+    /// Moves `len` bytes from `src` to `dst`, with `memmove` semantics: safe
+    /// even when the two ranges overlap. Marks every block `dst..dst+len`
+    /// overlaps dirty if `mark_dirty` is true; see [`Self::copy_within`] and
+    /// [`Self::copy_within_quiet`] for the two callers of this.
+    ///
+    /// Copies through a `BS`-sized stack buffer rather than assuming the
+    /// backend exposes one contiguous slice for the whole range — true for
+    /// [`DenseBackend`](crate::shadow::backend::DenseBackend) but not for a
+    /// [`SparseBackend`](crate::shadow::backend::SparseBackend) range that
+    /// spans more than one page. Processes chunks from the high end down
+    /// when `dst > src` and from the low end up otherwise, which is what
+    /// keeps a chunked copy correct under overlap: the chunk just written
+    /// never lands on source bytes a later chunk still needs to read.
+    fn copy_within_impl(
+        &mut self,
+        src: u16,
+        dst: u16,
+        len: usize,
+        mark_dirty: bool,
+    ) -> Result<(), ShadowError> {
+        range_span::<TS>(src, len)?;
+        range_span::<TS>(dst, len)?;
+
+        let mut chunk = [0u8; BS];
+        if dst > src {
+            let mut remaining = len;
+            while remaining > 0 {
+                let n = remaining.min(BS);
+                remaining -= n;
+                let s = src + remaining as u16;
+                let d = dst + remaining as u16;
+                self.with_bytes(s, n, |buf| Ok(chunk[..n].copy_from_slice(buf)))?;
+                self.with_bytes_mut(d, n, |buf| Ok(buf.copy_from_slice(&chunk[..n])))?;
+            }
+        } else {
+            let mut offset = 0usize;
+            while offset < len {
+                let n = (len - offset).min(BS);
+                let s = src + offset as u16;
+                let d = dst + offset as u16;
+                self.with_bytes(s, n, |buf| Ok(chunk[..n].copy_from_slice(buf)))?;
+                self.with_bytes_mut(d, n, |buf| Ok(buf.copy_from_slice(&chunk[..n])))?;
+                offset += n;
+            }
+        }
+
+        if mark_dirty {
+            self.mark_dirty(dst, len)?;
+        }
+        Ok(())
+    }
+
+    /// Copies `len` bytes from `src` to `dst` (`memmove` semantics — safe
+    /// under overlap) and marks every block the destination range overlaps
+    /// dirty, e.g. promoting a freshly received buffer into an active slot.
+    /// Use [`Self::copy_within_quiet`] to leave dirty state untouched
+    /// instead.
+    pub(crate) fn copy_within(
+        &mut self,
+        src: u16,
+        dst: u16,
+        len: usize,
+    ) -> Result<(), ShadowError> {
+        self.copy_within_impl(src, dst, len, true)
+    }
+
+    /// Same as [`Self::copy_within`], but leaves dirty state untouched,
+    /// matching the usual kernel-side "reads and writes don't mark dirty"
+    /// convention — for a ping-pong copy the driver itself tracks, where the
+    /// destination shouldn't look host-modified.
+    pub(crate) fn copy_within_quiet(
+        &mut self,
+        src: u16,
+        dst: u16,
+        len: usize,
+    ) -> Result<(), ShadowError> {
+        self.copy_within_impl(src, dst, len, false)
+    }
+}
+
+/// `addr: u16 LE` + `orig_len: u16 LE` + `flag: u8` + `stored_len: u16 LE`,
+/// preceding each record's bytes in the
+/// [`ShadowTable::encode_dirty_deltas_compressed`] stream format.
+/// `stored_len` is carried even for an uncompressed (`flag == 0`) record, so
+/// the header shape doesn't depend on which way a given record went.
+#[cfg(feature = "lz4")]
+const COMPRESSED_DELTA_HEADER_LEN: usize = 2 + 2 + 1 + 2;
+
+/// `flag` byte value for a record whose bytes are the original payload,
+/// copied through unchanged because compressing it didn't shrink it.
+#[cfg(feature = "lz4")]
+const COMPRESSED_DELTA_FLAG_STORED: u8 = 0;
+
+/// `flag` byte value for a record whose bytes are an LZ4 block, decompressed
+/// back to `orig_len` bytes on the receiving end.
+#[cfg(feature = "lz4")]
+const COMPRESSED_DELTA_FLAG_COMPRESSED: u8 = 1;
+
+#[cfg(feature = "lz4")]
+impl<const TS: usize, const BS: usize, const BC: usize, TB> ShadowTable<TS, BS, BC, TB>
+where
+    bitmaps::BitsImpl<BC>: bitmaps::Bits,
+    TB: TableBackend<TS>,
+{
+    /// Like [`Self::encode_dirty_deltas`], but LZ4-compresses each dirty
+    /// run's payload before framing it, falling back to storing it
+    /// unchanged when compression doesn't shrink it (common for short
+    /// runs, where the LZ4 block overhead outweighs the savings). The
+    /// per-record `flag` byte records which happened, so
+    /// [`Self::decode_dirty_deltas_compressed`] knows whether to
+    /// decompress or copy straight through; the outer
+    /// `[addr][orig_len][flag][stored_len][...][crc32]` framing is
+    /// otherwise identical to the uncompressed stream.
+    ///
+    /// Returns [`ShadowError::OutOfBounds`] if a run's payload or its
+    /// compressed form doesn't fit a `u16` length field, or if `out` is
+    /// too small for the full stream; `out` may hold a partial stream in
+    /// that case and should be discarded.
+    pub(crate) fn encode_dirty_deltas_compressed(
+        &self,
+        out: &mut [u8],
+    ) -> Result<usize, ShadowError> {
+        let mut pos = 0usize;
+        self.iter_dirty_runs(|addr, payload| {
+            if payload.len() > u16::MAX as usize {
+                return Err(ShadowError::OutOfBounds);
+            }
+
+            let body_start = pos + COMPRESSED_DELTA_HEADER_LEN;
+            let body = out.get_mut(body_start..).ok_or(ShadowError::OutOfBounds)?;
+
+            let (flag, stored_len) = match lz4_flex::block::compress_into(payload, body) {
+                Ok(n) if n < payload.len() => (COMPRESSED_DELTA_FLAG_COMPRESSED, n),
+                _ => {
+                    let dst = out
+                        .get_mut(body_start..body_start + payload.len())
+                        .ok_or(ShadowError::OutOfBounds)?;
+                    dst.copy_from_slice(payload);
+                    (COMPRESSED_DELTA_FLAG_STORED, payload.len())
+                }
+            };
+            if stored_len > u16::MAX as usize {
+                return Err(ShadowError::OutOfBounds);
+            }
+
+            let record_len = COMPRESSED_DELTA_HEADER_LEN + stored_len + DELTA_CRC_LEN;
+            let record = out
+                .get_mut(pos..pos + record_len)
+                .ok_or(ShadowError::OutOfBounds)?;
+
+            record[0..2].copy_from_slice(&addr.to_le_bytes());
+            record[2..4].copy_from_slice(&(payload.len() as u16).to_le_bytes());
+            record[4] = flag;
+            record[5..7].copy_from_slice(&(stored_len as u16).to_le_bytes());
+
+            let crc = crc32(record[..COMPRESSED_DELTA_HEADER_LEN + stored_len].iter().copied());
+            record[COMPRESSED_DELTA_HEADER_LEN + stored_len..record_len]
+                .copy_from_slice(&crc.to_le_bytes());
+
+            pos += record_len;
+            Ok(())
+        })?;
+        Ok(pos)
+    }
+
+    /// Applies a stream produced by
+    /// [`Self::encode_dirty_deltas_compressed`], decompressing each
+    /// record directly into its destination via [`Self::with_bytes_mut`]
+    /// (or copying it through unchanged, per the record's `flag`) without
+    /// marking the destination dirty.
+    ///
+    /// Returns [`ShadowError::ChecksumMismatch`] on the first record whose
+    /// CRC doesn't match or whose decompressed length doesn't match
+    /// `orig_len`, and [`ShadowError::OutOfBounds`] if `input` is
+    /// truncated mid-record.
+    pub(crate) fn decode_dirty_deltas_compressed(
+        &mut self,
+        input: &[u8],
+    ) -> Result<(), ShadowError> {
+        let mut pos = 0usize;
+        while pos < input.len() {
+            let header = input
+                .get(pos..pos + COMPRESSED_DELTA_HEADER_LEN)
+                .ok_or(ShadowError::OutOfBounds)?;
+            let addr = u16::from_le_bytes([header[0], header[1]]);
+            let orig_len = u16::from_le_bytes([header[2], header[3]]) as usize;
+            let flag = header[4];
+            let stored_len = u16::from_le_bytes([header[5], header[6]]) as usize;
+            let record_len = COMPRESSED_DELTA_HEADER_LEN + stored_len + DELTA_CRC_LEN;
+
+            let record = input
+                .get(pos..pos + record_len)
+                .ok_or(ShadowError::OutOfBounds)?;
+            let body =
+                &record[COMPRESSED_DELTA_HEADER_LEN..COMPRESSED_DELTA_HEADER_LEN + stored_len];
+            let expected_crc = u32::from_le_bytes(
+                record[COMPRESSED_DELTA_HEADER_LEN + stored_len..record_len]
+                    .try_into()
+                    .expect("slice is exactly 4 bytes"),
+            );
+            if crc32(record[..COMPRESSED_DELTA_HEADER_LEN + stored_len].iter().copied()) != expected_crc {
+                return Err(ShadowError::ChecksumMismatch);
+            }
+
+            self.with_bytes_mut(addr, orig_len, |dst| match flag {
+                COMPRESSED_DELTA_FLAG_STORED => {
+                    dst.copy_from_slice(body);
+                    Ok(())
+                }
+                _ => {
+                    let n = lz4_flex::block::decompress_into(body, dst)
+                        .map_err(|_| ShadowError::ChecksumMismatch)?;
+                    if n != orig_len {
+                        return Err(ShadowError::ChecksumMismatch);
+                    }
+                    Ok(())
+                }
+            })?;
+
+            pos += record_len;
         }
         Ok(())
     }
@@ -100,6 +692,7 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::shadow::backend::SparseBackend;
 
     // 16-byte table, 4-byte blocks, 4 dirty blocks
     type TestTable = ShadowTable<16, 4, 4>;
@@ -210,4 +803,514 @@ mod tests {
             Err(ShadowError::OutOfBounds)
         );
     }
+
+    #[test]
+    fn lease_dirty_blocks_skips_in_flight_and_clean_blocks() {
+        let mut table: TestTable = ShadowTable::new();
+        table.mark_dirty(0, 4).unwrap();
+        table.mark_dirty(8, 4).unwrap();
+
+        let mut leased = [0u16; 4];
+        let mut count = 0;
+        table.lease_dirty_blocks(|lease| {
+            leased[count] = lease.addr();
+            count += 1;
+        });
+        assert_eq!(count, 2);
+        assert_eq!(leased[0], 0);
+        assert_eq!(leased[1], 8);
+
+        // Both blocks are now in-flight, so a second lease pass finds nothing.
+        let mut second_pass_count = 0;
+        table.lease_dirty_blocks(|_lease| second_pass_count += 1);
+        assert_eq!(second_pass_count, 0);
+    }
+
+    #[test]
+    fn complete_lease_clears_dirty_when_generation_unchanged() {
+        let mut table: TestTable = ShadowTable::new();
+        table.mark_dirty(0, 4).unwrap();
+
+        let mut generation = 0;
+        table.lease_dirty_blocks(|lease| generation = lease.generation());
+
+        table.complete_lease(0, generation, true).unwrap();
+
+        assert!(!table.is_dirty(0, 4).unwrap());
+    }
+
+    #[test]
+    fn complete_lease_leaves_dirty_when_write_lands_mid_flight() {
+        let mut table: TestTable = ShadowTable::new();
+        table.mark_dirty(0, 4).unwrap();
+
+        let mut generation = 0;
+        table.lease_dirty_blocks(|lease| generation = lease.generation());
+
+        // A host write lands on the same block mid-transfer, bumping its generation.
+        table.mark_dirty(0, 4).unwrap();
+
+        table.complete_lease(0, generation, true).unwrap();
+
+        // Still dirty, since the leased generation is now stale.
+        assert!(table.is_dirty(0, 4).unwrap());
+    }
+
+    #[test]
+    fn complete_lease_leaves_dirty_when_transfer_failed() {
+        let mut table: TestTable = ShadowTable::new();
+        table.mark_dirty(0, 4).unwrap();
+
+        let mut generation = 0;
+        table.lease_dirty_blocks(|lease| generation = lease.generation());
+
+        table.complete_lease(0, generation, false).unwrap();
+
+        assert!(table.is_dirty(0, 4).unwrap());
+    }
+
+    #[test]
+    fn complete_lease_unmarks_in_flight_so_block_can_be_leased_again() {
+        let mut table: TestTable = ShadowTable::new();
+        table.mark_dirty(0, 4).unwrap();
+
+        let mut generation = 0;
+        table.lease_dirty_blocks(|lease| generation = lease.generation());
+        // Transfer failed, block stays dirty but should be leasable again.
+        table.complete_lease(0, generation, false).unwrap();
+
+        let mut count = 0;
+        table.lease_dirty_blocks(|_lease| count += 1);
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn iter_dirty_runs_coalesces_adjacent_blocks() {
+        let mut table: TestTable = ShadowTable::new();
+        table.mark_dirty(0, 4).unwrap();
+        table.mark_dirty(4, 4).unwrap();
+        table.mark_dirty(12, 4).unwrap();
+
+        let mut runs = [(0u16, 0usize); 4];
+        let mut count = 0;
+        table
+            .iter_dirty_runs(|addr, data| {
+                runs[count] = (addr, data.len());
+                count += 1;
+                Ok(())
+            })
+            .unwrap();
+
+        assert_eq!(count, 2);
+        assert_eq!(runs[0], (0, 8)); // blocks 0-1 merged into one run
+        assert_eq!(runs[1], (12, 4)); // block 3 stays its own run
+    }
+
+    #[test]
+    fn iter_dirty_runs_yields_nothing_when_table_is_clean() {
+        let table: TestTable = ShadowTable::new();
+        let mut count = 0;
+        table.iter_dirty_runs(|_, _| Ok(count += 1)).unwrap();
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    fn iter_dirty_runs_merges_all_blocks_when_table_fully_dirty() {
+        let mut table: TestTable = ShadowTable::new();
+        table.mark_dirty(0, 16).unwrap();
+
+        let mut count = 0;
+        let mut run = (0u16, 0usize);
+        table
+            .iter_dirty_runs(|addr, data| {
+                run = (addr, data.len());
+                count += 1;
+                Ok(())
+            })
+            .unwrap();
+
+        assert_eq!(count, 1);
+        assert_eq!(run, (0, 16));
+    }
+
+    #[test]
+    fn for_each_dirty_block_bounded_resumes_across_calls() {
+        let mut table: TestTable = ShadowTable::new();
+        table.mark_dirty(0, 16).unwrap(); // blocks 0-3 all dirty
+
+        let mut seen = [0u16; 4];
+        let mut count = 0;
+        let more = table
+            .for_each_dirty_block_bounded(2, |addr, _data| {
+                seen[count] = addr;
+                count += 1;
+                Ok(())
+            })
+            .unwrap();
+
+        assert!(more);
+        assert_eq!(count, 2);
+        assert_eq!(&seen[..2], &[0, 4]);
+
+        // Second call resumes from block 2, not from the top.
+        let more = table
+            .for_each_dirty_block_bounded(2, |addr, _data| {
+                seen[count] = addr;
+                count += 1;
+                Ok(())
+            })
+            .unwrap();
+
+        assert!(!more);
+        assert_eq!(count, 4);
+        assert_eq!(&seen[2..4], &[8, 12]);
+    }
+
+    #[test]
+    fn for_each_dirty_block_bounded_wraps_and_picks_up_newly_dirtied_blocks() {
+        let mut table: TestTable = ShadowTable::new();
+        table.mark_dirty(8, 4).unwrap(); // block 2
+
+        let mut count = 0;
+        let more = table
+            .for_each_dirty_block_bounded(10, |_addr, _data| {
+                count += 1;
+                Ok(())
+            })
+            .unwrap();
+        assert!(!more);
+        assert_eq!(count, 1);
+        // The caller marks the processed block clean.
+        table.clear_dirty(8, 4).unwrap();
+
+        // A host write lands on an earlier block, behind where the cursor
+        // wrapped to (the top); the next bounded pass still finds it.
+        table.mark_dirty(0, 4).unwrap();
+
+        let more = table
+            .for_each_dirty_block_bounded(10, |addr, _data| {
+                assert_eq!(addr, 0);
+                count += 1;
+                Ok(())
+            })
+            .unwrap();
+        assert!(!more);
+        assert_eq!(count, 2);
+    }
+
+    #[test]
+    fn reset_cursor_restarts_bounded_scan_from_the_top() {
+        let mut table: TestTable = ShadowTable::new();
+        table.mark_dirty(0, 16).unwrap();
+
+        let mut count = 0;
+        table
+            .for_each_dirty_block_bounded(2, |_addr, _data| {
+                count += 1;
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(count, 2);
+
+        table.reset_cursor();
+
+        let mut first_addr = None;
+        table
+            .for_each_dirty_block_bounded(1, |addr, _data| {
+                first_addr = Some(addr);
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(first_addr, Some(0));
+    }
+
+    #[test]
+    fn pin_range_rejects_overlapping_pin() {
+        let mut table: TestTable = ShadowTable::new();
+        table.pin_range(0, 4).unwrap();
+
+        assert_eq!(table.pin_range(2, 4), Err(ShadowError::Pinned));
+        // Non-overlapping block is still pinnable.
+        table.pin_range(8, 4).unwrap();
+    }
+
+    #[test]
+    fn unpin_range_allows_re_pinning() {
+        let mut table: TestTable = ShadowTable::new();
+        table.pin_range(0, 4).unwrap();
+        table.unpin_range(0, 4).unwrap();
+
+        table.pin_range(0, 4).unwrap();
+    }
+
+    #[test]
+    fn lock_rejects_nothing_and_is_locked_reports_locked_blocks() {
+        let mut table: TestTable = ShadowTable::new();
+        assert!(!table.is_locked(0, 4).unwrap());
+
+        table.lock(0, 4).unwrap();
+        assert!(table.is_locked(0, 4).unwrap());
+        assert!(!table.is_locked(4, 4).unwrap());
+    }
+
+    #[test]
+    fn unlock_releases_a_previously_locked_block() {
+        let mut table: TestTable = ShadowTable::new();
+        table.lock(0, 4).unwrap();
+        table.unlock(0, 4).unwrap();
+
+        assert!(!table.is_locked(0, 4).unwrap());
+    }
+
+    #[test]
+    fn new_table_has_no_populated_blocks() {
+        let table: TestTable = ShadowTable::new();
+        assert!(!table.is_populated(0, 16).unwrap());
+    }
+
+    #[test]
+    fn with_bytes_mut_marks_only_the_written_blocks_populated() {
+        let mut table: TestTable = ShadowTable::new();
+        table.with_bytes_mut(4, 4, |buf| Ok(buf.fill(0x42))).unwrap();
+
+        assert!(table.is_populated(4, 4).unwrap());
+        assert!(!table.is_populated(0, 4).unwrap());
+        // A range spanning a populated and an unpopulated block is reported
+        // as not fully populated.
+        assert!(!table.is_populated(0, 16).unwrap());
+    }
+
+    #[test]
+    fn locking_an_already_locked_block_is_not_an_error() {
+        let mut table: TestTable = ShadowTable::new();
+        table.lock(0, 4).unwrap();
+        table.lock(0, 4).unwrap();
+
+        assert!(table.is_locked(0, 4).unwrap());
+    }
+
+    #[test]
+    fn encode_then_decode_dirty_deltas_reproduces_dirty_bytes() {
+        let mut src: TestTable = ShadowTable::new();
+        src.with_bytes_mut(0, 4, |buf| {
+            buf.copy_from_slice(&[0x01, 0x02, 0x03, 0x04]);
+            Ok(())
+        })
+        .unwrap();
+        src.mark_dirty(0, 4).unwrap();
+        src.with_bytes_mut(12, 4, |buf| {
+            buf.copy_from_slice(&[0xAA, 0xBB, 0xCC, 0xDD]);
+            Ok(())
+        })
+        .unwrap();
+        src.mark_dirty(12, 4).unwrap();
+
+        let mut stream = [0u8; 64];
+        let len = src.encode_dirty_deltas(&mut stream).unwrap();
+
+        let mut dst: TestTable = ShadowTable::new();
+        dst.decode_dirty_deltas(&stream[..len]).unwrap();
+
+        dst.with_bytes(0, 4, |buf| {
+            assert_eq!(buf, &[0x01, 0x02, 0x03, 0x04]);
+            Ok(())
+        })
+        .unwrap();
+        dst.with_bytes(12, 4, |buf| {
+            assert_eq!(buf, &[0xAA, 0xBB, 0xCC, 0xDD]);
+            Ok(())
+        })
+        .unwrap();
+        // Applying the deltas shouldn't mark the receiver dirty.
+        assert!(!dst.any_dirty());
+    }
+
+    #[test]
+    fn encode_dirty_deltas_reports_out_of_bounds_when_out_is_too_small() {
+        let mut table: TestTable = ShadowTable::new();
+        table.mark_dirty(0, 4).unwrap();
+
+        let mut out = [0u8; 2];
+        assert_eq!(
+            table.encode_dirty_deltas(&mut out),
+            Err(ShadowError::OutOfBounds)
+        );
+    }
+
+    #[test]
+    fn decode_dirty_deltas_rejects_a_corrupted_record() {
+        let mut src: TestTable = ShadowTable::new();
+        src.mark_dirty(0, 4).unwrap();
+
+        let mut stream = [0u8; 64];
+        let len = src.encode_dirty_deltas(&mut stream).unwrap();
+        stream[len - 1] ^= 0xFF; // Flip a CRC byte.
+
+        let mut dst: TestTable = ShadowTable::new();
+        assert_eq!(
+            dst.decode_dirty_deltas(&stream[..len]),
+            Err(ShadowError::ChecksumMismatch)
+        );
+    }
+
+    // 256-byte table, large enough for a repeated-byte run to actually
+    // compress.
+    #[cfg(feature = "lz4")]
+    type CompressibleTable = ShadowTable<256, 32, 8>;
+
+    #[cfg(feature = "lz4")]
+    #[test]
+    fn encode_then_decode_dirty_deltas_compressed_reproduces_dirty_bytes() {
+        let mut src: CompressibleTable = ShadowTable::new();
+        // A long run of repeated bytes compresses well...
+        src.with_bytes_mut(0, 64, |buf| {
+            buf.fill(0x00);
+            Ok(())
+        })
+        .unwrap();
+        src.mark_dirty(0, 64).unwrap();
+        // ...and a 2-byte write dirties its whole 32-byte block (192..224),
+        // which goes out as a second record.
+        src.with_bytes_mut(200, 2, |buf| {
+            buf.copy_from_slice(&[0xAA, 0xBB]);
+            Ok(())
+        })
+        .unwrap();
+        src.mark_dirty(200, 2).unwrap();
+
+        let mut stream = [0u8; 256];
+        let len = src.encode_dirty_deltas_compressed(&mut stream).unwrap();
+        assert!(
+            len < 64 + 2 + 2 * COMPRESSED_DELTA_HEADER_LEN + 2 * DELTA_CRC_LEN,
+            "compressed stream ({len} bytes) should be smaller than the raw payloads"
+        );
+
+        let mut dst: CompressibleTable = ShadowTable::new();
+        dst.decode_dirty_deltas_compressed(&stream[..len]).unwrap();
+
+        dst.with_bytes(0, 64, |buf| {
+            assert_eq!(buf, &[0x00; 64]);
+            Ok(())
+        })
+        .unwrap();
+        dst.with_bytes(200, 2, |buf| {
+            assert_eq!(buf, &[0xAA, 0xBB]);
+            Ok(())
+        })
+        .unwrap();
+        assert!(!dst.any_dirty());
+    }
+
+    #[cfg(feature = "lz4")]
+    #[test]
+    fn decode_dirty_deltas_compressed_rejects_a_corrupted_record() {
+        let mut src: CompressibleTable = ShadowTable::new();
+        src.with_bytes_mut(0, 64, |buf| {
+            buf.fill(0x00);
+            Ok(())
+        })
+        .unwrap();
+        src.mark_dirty(0, 64).unwrap();
+
+        let mut stream = [0u8; 256];
+        let len = src.encode_dirty_deltas_compressed(&mut stream).unwrap();
+        stream[len - 1] ^= 0xFF; // Flip a CRC byte.
+
+        let mut dst: CompressibleTable = ShadowTable::new();
+        assert_eq!(
+            dst.decode_dirty_deltas_compressed(&stream[..len]),
+            Err(ShadowError::ChecksumMismatch)
+        );
+    }
+
+    #[test]
+    fn with_backend_plugs_in_a_non_default_backend() {
+        let mut table: ShadowTable<16, 4, 4, SparseBackend<4, 2>> =
+            ShadowTable::with_backend(SparseBackend::with_fill(0xFF));
+
+        // Unwritten page reads back as the backend's fill byte.
+        table
+            .with_bytes(0, 4, |buf| {
+                assert_eq!(buf, &[0xFF; 4]);
+                Ok(())
+            })
+            .unwrap();
+
+        table
+            .with_bytes_mut(4, 4, |buf| {
+                buf.copy_from_slice(&[1, 2, 3, 4]);
+                Ok(())
+            })
+            .unwrap();
+        table.mark_dirty(4, 4).unwrap();
+
+        assert!(table.is_dirty(4, 4).unwrap());
+        assert!(!table.is_dirty(0, 4).unwrap());
+    }
+
+    #[test]
+    fn copy_within_moves_bytes_for_non_overlapping_ranges() {
+        let mut table = TestTable::new();
+        table
+            .with_bytes_mut(0, 4, |buf| Ok(buf.copy_from_slice(&[1, 2, 3, 4])))
+            .unwrap();
+
+        table.copy_within(0, 8, 4).unwrap();
+
+        table
+            .with_bytes(8, 4, |buf| {
+                assert_eq!(buf, &[1, 2, 3, 4]);
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn copy_within_handles_forward_overlap() {
+        // dst > src: without memmove semantics, a naive forward copy would
+        // clobber source bytes it hasn't read yet.
+        let mut table = TestTable::new();
+        table
+            .with_bytes_mut(0, 6, |buf| Ok(buf.copy_from_slice(&[1, 2, 3, 4, 5, 6])))
+            .unwrap();
+
+        table.copy_within(0, 2, 6).unwrap();
+
+        table
+            .with_bytes(2, 6, |buf| {
+                assert_eq!(buf, &[1, 2, 3, 4, 5, 6]);
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn copy_within_handles_backward_overlap() {
+        // dst < src: without memmove semantics, a naive backward copy would
+        // clobber source bytes it hasn't read yet.
+        let mut table = TestTable::new();
+        table
+            .with_bytes_mut(2, 6, |buf| Ok(buf.copy_from_slice(&[1, 2, 3, 4, 5, 6])))
+            .unwrap();
+
+        table.copy_within(2, 0, 6).unwrap();
+
+        table
+            .with_bytes(0, 6, |buf| {
+                assert_eq!(buf, &[1, 2, 3, 4, 5, 6]);
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn copy_within_marks_destination_dirty_but_copy_within_quiet_does_not() {
+        let mut table = TestTable::new();
+        table.copy_within(0, 8, 4).unwrap();
+        assert!(table.is_dirty(8, 4).unwrap());
+
+        table.clear_all_dirty();
+        table.copy_within_quiet(0, 4, 4).unwrap();
+        assert!(!table.is_dirty(4, 4).unwrap());
+    }
 }
@@ -1,7 +1,9 @@
 //! Test support utilities - only compiled in test builds.
 
 use crate::shadow::{
-    ShadowError, WriteResult,
+    backing::NoBackingStore,
+    cache::NoCache,
+    fault::NoFaultHandler,
     persist::{NoPersist, PersistTrigger},
     policy::{AccessPolicy, AllowAllPolicy, NoPersistPolicy, PersistPolicy},
     staged::PatchStagingBuffer,
@@ -9,6 +11,7 @@ use crate::shadow::{
     table::ShadowTable,
     types::StagingBuffer,
     view::{HostView, HostViewStaged},
+    ShadowError, WriteResult,
 };
 
 /// Standard test configuration: 64 bytes, 16-byte blocks, 4 blocks
@@ -74,6 +77,7 @@ pub struct TestHostViewFixture {
     pub policy: AllowAllPolicy,
     pub persist_policy: NoPersistPolicy,
     pub trigger: NoPersist,
+    pub fault_handler: NoFaultHandler,
 }
 
 impl TestHostViewFixture {
@@ -83,6 +87,7 @@ impl TestHostViewFixture {
             policy: AllowAllPolicy::default(),
             persist_policy: NoPersistPolicy::default(),
             trigger: NoPersist,
+            fault_handler: NoFaultHandler,
         }
     }
 
@@ -94,6 +99,8 @@ impl TestHostViewFixture {
             &self.policy,
             &self.persist_policy,
             &mut self.trigger,
+            &mut self.fault_handler,
+            &NoBackingStore,
         )
     }
 }
@@ -110,7 +117,9 @@ pub struct TestHostViewStagedFixture {
     pub policy: AllowAllPolicy,
     pub persist_policy: NoPersistPolicy,
     pub trigger: NoPersist,
+    pub fault_handler: NoFaultHandler,
     pub stage: TestStage,
+    pub cache: NoCache,
 }
 
 impl TestHostViewStagedFixture {
@@ -120,7 +129,9 @@ impl TestHostViewStagedFixture {
             policy: AllowAllPolicy::default(),
             persist_policy: NoPersistPolicy::default(),
             trigger: NoPersist,
+            fault_handler: NoFaultHandler,
             stage: TestStage::new(),
+            cache: NoCache,
         }
     }
 
@@ -133,8 +144,10 @@ impl TestHostViewStagedFixture {
             &self.policy,
             &self.persist_policy,
             &mut self.trigger,
+            &mut self.fault_handler,
+            &NoBackingStore,
         );
-        HostViewStaged::new(base, &mut self.stage)
+        HostViewStaged::new(base, &mut self.stage, &mut self.cache)
     }
 }
 
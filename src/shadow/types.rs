@@ -1,4 +1,4 @@
-use crate::shadow::ShadowError;
+use crate::shadow::{slice::ROSlice, ShadowError};
 
 /// Result of a write operation indicating whether to mark blocks dirty.
 ///
@@ -27,6 +27,76 @@ impl<R> WriteResult<R> {
     }
 }
 
+/// A leased dirty block handed to a DMA-driven flush.
+///
+/// Captures the block's address, data and dirty *generation* at lease
+/// time. Pass the lease to a DMA engine and call
+/// [`KernelView::complete_lease`](crate::shadow::KernelView::complete_lease)
+/// once the transfer finishes; completion only clears the dirty bit if the
+/// block's generation hasn't advanced since the lease was taken, so a host
+/// write that lands mid-transfer keeps the block dirty for the next flush.
+#[derive(Debug)]
+pub struct DirtyLease<'a> {
+    addr: u16,
+    data: &'a [u8],
+    generation: u32,
+}
+
+impl<'a> DirtyLease<'a> {
+    pub(crate) fn new(addr: u16, data: &'a [u8], generation: u32) -> Self {
+        Self {
+            addr,
+            data,
+            generation,
+        }
+    }
+
+    /// Address of the leased block.
+    pub fn addr(&self) -> u16 {
+        self.addr
+    }
+
+    /// Zero-copy read access to the leased block's bytes.
+    pub fn data(&self) -> ROSlice<'a> {
+        ROSlice::new(self.data)
+    }
+
+    /// Dirty generation captured when the lease was taken.
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+}
+
+/// Captured position in a [`StagingBuffer`]'s append-only storage, returned
+/// by [`StagingBuffer::savepoint`] and consumed by
+/// [`StagingBuffer::rollback_to`] to discard everything staged since.
+///
+/// Just two counters — capturing one allocates nothing — and savepoints
+/// nest naturally: rolling back to an earlier savepoint also discards
+/// writes staged after a later one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Savepoint {
+    data_len: u16,
+    entries_len: u16,
+}
+
+impl Savepoint {
+    pub(crate) fn new(data_len: u16, entries_len: u16) -> Self {
+        Self {
+            data_len,
+            entries_len,
+        }
+    }
+
+    pub(crate) fn data_len(&self) -> u16 {
+        self.data_len
+    }
+
+    pub(crate) fn entries_len(&self) -> u16 {
+        self.entries_len
+    }
+}
+
 /// Buffer for staging writes before committing to the shadow table.
 pub trait StagingBuffer {
     /// Returns true if any writes are staged.
@@ -51,4 +121,65 @@ pub trait StagingBuffer {
     fn iter_staged<F>(&self, f: F) -> Result<(), ShadowError>
     where
         F: FnMut(u16, &[u8]) -> Result<(), ShadowError>;
+
+    /// Merges adjacent/overlapping staged writes into a minimal
+    /// non-overlapping set, later writes winning over earlier ones
+    /// wherever they overlap. Shrinks both buffer usage and the number of
+    /// writes [`iter_staged`](Self::iter_staged) replays, without changing
+    /// the effective result.
+    ///
+    /// Implementations that can't (or don't need to) compact may leave
+    /// this as a no-op; it's always safe to skip.
+    fn compact(&mut self) -> Result<(), ShadowError> {
+        Ok(())
+    }
+
+    /// Captures the current staging position, so a later
+    /// [`Self::rollback_to`] can discard everything staged after it while
+    /// keeping everything staged before it intact.
+    ///
+    /// Implementations that don't support partial rollback may return a
+    /// placeholder `Savepoint` and make [`Self::rollback_to`] a no-op.
+    fn savepoint(&self) -> Savepoint {
+        Savepoint::new(0, 0)
+    }
+
+    /// Returns true if staging `[addr, addr+len)` right now would exceed
+    /// capacity, so a caller with an eviction policy should make room by
+    /// evicting (via [`Self::evict_oldest_staged`]) before calling
+    /// [`Self::alloc_staged`].
+    ///
+    /// Implementations without an eviction policy (or with it disabled)
+    /// always return `false` — there's nothing useful to evict, and
+    /// `alloc_staged` is trusted to report
+    /// [`ShadowError::StageFull`](crate::shadow::ShadowError::StageFull) on
+    /// its own.
+    fn would_overflow(&self, addr: u16, len: usize) -> bool {
+        let _ = (addr, len);
+        false
+    }
+
+    /// Evicts the least-recently-touched staged entry, if any, handing its
+    /// `(addr, bytes)` to `f` before dropping it from the buffer — e.g. so
+    /// a caller can force-commit it to the shadow table first. Returns
+    /// `true` if an entry was evicted, `false` if there was nothing to
+    /// evict (or eviction isn't supported/enabled), in which case `f` is
+    /// never called.
+    fn evict_oldest_staged<F>(&mut self, f: F) -> Result<bool, ShadowError>
+    where
+        F: FnOnce(u16, &[u8]) -> Result<(), ShadowError>,
+    {
+        let _ = f;
+        Ok(false)
+    }
+
+    /// Discards every write staged since `sp` was captured, keeping
+    /// earlier ones. Savepoints nest: rolling back to an older one also
+    /// undoes a newer one.
+    ///
+    /// Implementations that can't support partial rollback may leave this
+    /// as a no-op; it's always safe to skip.
+    fn rollback_to(&mut self, _sp: Savepoint) -> Result<(), ShadowError> {
+        Ok(())
+    }
 }
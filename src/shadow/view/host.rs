@@ -1,83 +1,142 @@
+#![allow(unsafe_code)]
+
 use core::marker::PhantomData;
+use core::ops::{Deref, DerefMut};
 
 use crate::shadow::{
-    AccessPolicy, PersistTrigger, ShadowError, WriteResult,
+    backend::{DenseBackend, TableBackend},
+    backing::BackingStore,
+    fault::AccessFaultHandler,
+    helpers::block_span,
     policy::PersistPolicy,
-    slice::{ROSlice, RWSlice, WOSlice},
+    slice::{FieldCursor, ROSlice, RWSlice, WOSlice},
     table::ShadowTable,
+    view::{DmaDirection, DmaRegion, Transaction},
+    AccessPolicy, PersistTrigger, ShadowError, WriteResult,
 };
 
 /// Application/host-side view of the shadow table.
 ///
 /// Writes through this view mark blocks dirty and may trigger persistence.
-/// Reads and writes are subject to the configured access policy.
-pub struct HostView<'a, const TS: usize, const BS: usize, const BC: usize, AP, PP, PT, PK>
-where
+/// Reads and writes are subject to the configured access policy, and a
+/// denial is reported to the configured [`AccessFaultHandler`].
+pub struct HostView<
+    'a,
+    const TS: usize,
+    const BS: usize,
+    const BC: usize,
+    AP,
+    PP,
+    PT,
+    PK,
+    TB = DenseBackend<TS>,
+> where
     bitmaps::BitsImpl<BC>: bitmaps::Bits,
     AP: AccessPolicy,
     PP: PersistPolicy<PK>,
     PT: PersistTrigger<PK>,
+    TB: TableBackend<TS>,
 {
-    pub(crate) table: &'a mut ShadowTable<TS, BS, BC>,
+    pub(crate) table: &'a mut ShadowTable<TS, BS, BC, TB>,
     pub(crate) access_policy: &'a AP,
     pub(crate) persist_policy: &'a PP,
     pub(crate) persist_trigger: &'a mut PT,
+    pub(crate) fault_handler: &'a mut dyn AccessFaultHandler,
+    pub(crate) backing_store: &'a dyn BackingStore,
     _phantom: PhantomData<PK>,
 }
 
-impl<'a, const TS: usize, const BS: usize, const BC: usize, AP, PP, PT, PK> core::fmt::Debug
-    for HostView<'a, TS, BS, BC, AP, PP, PT, PK>
+impl<'a, const TS: usize, const BS: usize, const BC: usize, AP, PP, PT, PK, TB> core::fmt::Debug
+    for HostView<'a, TS, BS, BC, AP, PP, PT, PK, TB>
 where
     bitmaps::BitsImpl<BC>: bitmaps::Bits,
     AP: AccessPolicy,
     PP: PersistPolicy<PK>,
     PT: PersistTrigger<PK>,
+    TB: TableBackend<TS>,
 {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.debug_struct("HostView").finish_non_exhaustive()
     }
 }
 
-impl<'a, const TS: usize, const BS: usize, const BC: usize, AP, PP, PT, PK>
-    HostView<'a, TS, BS, BC, AP, PP, PT, PK>
+impl<'a, const TS: usize, const BS: usize, const BC: usize, AP, PP, PT, PK, TB>
+    HostView<'a, TS, BS, BC, AP, PP, PT, PK, TB>
 where
     bitmaps::BitsImpl<BC>: bitmaps::Bits,
     AP: AccessPolicy,
     PP: PersistPolicy<PK>,
     PT: PersistTrigger<PK>,
+    TB: TableBackend<TS>,
 {
     pub(crate) fn new(
-        table: &'a mut ShadowTable<TS, BS, BC>,
+        table: &'a mut ShadowTable<TS, BS, BC, TB>,
         access_policy: &'a AP,
         persist_policy: &'a PP,
         persist_trigger: &'a mut PT,
+        fault_handler: &'a mut dyn AccessFaultHandler,
+        backing_store: &'a dyn BackingStore,
     ) -> Self {
         Self {
             table,
             access_policy,
             persist_policy,
             persist_trigger,
+            fault_handler,
+            backing_store,
             _phantom: PhantomData,
         }
     }
 
+    /// Locks every block overlapping `addr..addr+len` so subsequent host
+    /// writes touching any of them are rejected with
+    /// [`ShadowError::Denied`] until [`Self::unlock`] releases it — e.g.
+    /// once a configuration block has been committed to hardware and
+    /// verified, locking it stops a buggy host path from silently
+    /// overwriting it. Reads are unaffected. Kernel-side writes can bypass
+    /// or honor the lock via
+    /// [`KernelView::with_rw_slice_honoring_lock`](crate::shadow::view::KernelView::with_rw_slice_honoring_lock).
+    pub fn lock(&mut self, addr: u16, len: usize) -> Result<(), ShadowError> {
+        self.table.lock(addr, len)
+    }
+
+    /// Releases a lock taken by [`Self::lock`].
+    pub fn unlock(&mut self, addr: u16, len: usize) -> Result<(), ShadowError> {
+        self.table.unlock(addr, len)
+    }
+
+    /// Returns true if any block overlapping `addr..addr+len` is locked.
+    pub fn is_locked(&self, addr: u16, len: usize) -> Result<bool, ShadowError> {
+        self.table.is_locked(addr, len)
+    }
+
     /// Provides zero-copy read access via ROSlice.
     ///
-    /// Returns `Denied` if the access policy rejects the read.
-    pub fn with_ro_slice<F, R>(&self, addr: u16, len: usize, f: F) -> Result<R, ShadowError>
+    /// Returns `Denied` if the access policy rejects the read, after
+    /// notifying the [`AccessFaultHandler`]. If any block in `addr..addr+len`
+    /// has never been written, it's first warmed from the configured
+    /// [`BackingStore`] so `f` sees the backing image rather than the
+    /// backend's fill byte; subsequent reads of the same range hit the
+    /// table directly.
+    pub fn with_ro_slice<F, R>(&mut self, addr: u16, len: usize, f: F) -> Result<R, ShadowError>
     where
         F: FnOnce(ROSlice<'_>) -> R,
     {
         if !self.access_policy.can_read(addr, len) {
+            self.fault_handler.on_read_denied(addr, len);
             return Err(ShadowError::Denied);
         }
+
+        self.fill_from_backing_store(addr, len)?;
+
         self.table
             .with_bytes(addr, len, |data| Ok(f(ROSlice::new(data))))
     }
 
     /// Provides zero-copy write access via WOSlice.
     ///
-    /// Returns `Denied` if the access policy rejects the write.
+    /// Returns `Denied` if the access policy rejects the write, after
+    /// notifying the [`AccessFaultHandler`].
     /// Return `WriteResult::Dirty(result)` from your callback to mark the range as modified.
     /// Return `WriteResult::Clean(result)` to skip dirty marking.
     /// If dirty, triggers persistence based on configured policy.
@@ -91,6 +150,7 @@ where
         F: FnOnce(WOSlice<'_>) -> WriteResult<R>,
     {
         if !self.access_policy.can_write(addr, len) {
+            self.fault_handler.on_write_denied(addr, len);
             return Err(ShadowError::Denied);
         }
 
@@ -114,7 +174,10 @@ where
 
     /// Provides zero-copy read-write access via RWSlice.
     ///
-    /// Returns `Denied` if the access policy rejects either read or write.
+    /// Returns `Denied` if the access policy rejects either read or write,
+    /// after notifying the [`AccessFaultHandler`]. Like [`Self::with_ro_slice`],
+    /// any never-written block in range is warmed from the configured
+    /// [`BackingStore`] first, so `f`'s read side sees the backing image.
     /// Return `WriteResult::Dirty(result)` from your callback to mark the range as modified.
     /// Return `WriteResult::Clean(result)` to skip dirty marking.
     /// If dirty, triggers persistence based on configured policy.
@@ -127,10 +190,15 @@ where
     where
         F: FnOnce(RWSlice<'_>) -> WriteResult<R>,
     {
-        if !self.access_policy.can_read(addr, len) || !self.access_policy.can_write(addr, len) {
+        let can_read = self.access_policy.can_read(addr, len);
+        let can_write = self.access_policy.can_write(addr, len);
+        if !can_read || !can_write {
+            self.fault_handler.on_write_denied(addr, len);
             return Err(ShadowError::Denied);
         }
 
+        self.fill_from_backing_store(addr, len)?;
+
         let write_result =
             self.with_bytes_mut_no_persist(addr, len, |data| f(RWSlice::new(data)))?;
 
@@ -149,6 +217,175 @@ where
         Ok(write_result)
     }
 
+    /// Provides named-field read/write access to the block at `addr` via a
+    /// [`FieldCursor`] over `layout`.
+    ///
+    /// A thin wrapper over [`Self::with_rw_slice`]: same access-policy and
+    /// dirty/persist behavior, just with `layout`'s `Field<T>` constants in
+    /// hand instead of raw offsets. See [`FieldCursor`] for why `layout` is
+    /// handed to the callback as a field rather than kept on `self`.
+    pub fn with_fields<L, F, R>(
+        &mut self,
+        addr: u16,
+        len: usize,
+        layout: &L,
+        f: F,
+    ) -> Result<WriteResult<R>, ShadowError>
+    where
+        F: FnOnce(&mut FieldCursor<'_, '_, L>) -> WriteResult<R>,
+    {
+        self.with_rw_slice(addr, len, |slice| {
+            let mut cursor = FieldCursor::new(layout, slice);
+            f(&mut cursor)
+        })
+    }
+
+    /// Runs `f` as an all-or-nothing batch of writes via [`Transaction`].
+    ///
+    /// Every block `f` touches through [`Transaction::with_wo_slice`]/
+    /// [`Transaction::with_rw_slice`] is snapshotted (bounded by
+    /// `MAX_BLOCKS`) before being overwritten. If `f` returns `Err`, every
+    /// touched block is restored from its snapshot and nothing is marked
+    /// dirty. If `f` returns `Ok`, all touched blocks are marked dirty in
+    /// one step and persistence is triggered as configured.
+    ///
+    /// Returns [`ShadowError::TransactionFull`] if `f` touches more than
+    /// `MAX_BLOCKS` distinct blocks.
+    pub fn with_transaction<const MAX_BLOCKS: usize, F, R>(
+        &mut self,
+        f: F,
+    ) -> Result<R, ShadowError>
+    where
+        F: FnOnce(
+            &mut Transaction<'_, 'a, TS, BS, BC, AP, PP, PT, PK, TB, MAX_BLOCKS>,
+        ) -> Result<R, ShadowError>,
+    {
+        let mut txn = Transaction::new(self);
+
+        match f(&mut txn) {
+            Ok(result) => {
+                txn.commit()?;
+                Ok(result)
+            }
+            Err(err) => {
+                txn.rollback();
+                Err(err)
+            }
+        }
+    }
+
+    /// Leases `addr..addr+len` to a DMA engine for a transfer into or out
+    /// of the shadow table, pinning it for the duration and returning a
+    /// bounds-checked raw pointer to its bytes — the same zero-copy lease
+    /// [`KernelView::with_dma_region`](crate::shadow::view::KernelView::with_dma_region)
+    /// hands to a hardware driver, but checked against the configured
+    /// [`AccessPolicy`] first: [`DmaDirection::DeviceToMemory`] requires
+    /// `can_write`, [`DmaDirection::MemoryToDevice`] requires `can_read`. A
+    /// denial is reported to the configured [`AccessFaultHandler`].
+    ///
+    /// `addr` and `len` must be multiples of `BS`, since the lease is
+    /// resolved block-by-block the same way a normal write is, and a
+    /// partial block would leave the rest of it in limbo.
+    ///
+    /// Consume the returned region with
+    /// [`HostShadow::complete_dma`](crate::shadow::HostShadow::complete_dma)
+    /// once the transfer finishes.
+    pub fn with_dma_region(
+        &mut self,
+        addr: u16,
+        len: usize,
+        dir: DmaDirection,
+    ) -> Result<DmaRegion, ShadowError> {
+        if len == 0 {
+            return Err(ShadowError::ZeroLength);
+        }
+        if addr as usize % BS != 0 || len % BS != 0 {
+            return Err(ShadowError::Unaligned);
+        }
+
+        let allowed = match dir {
+            DmaDirection::DeviceToMemory => self.access_policy.can_write(addr, len),
+            DmaDirection::MemoryToDevice => self.access_policy.can_read(addr, len),
+        };
+        if !allowed {
+            match dir {
+                DmaDirection::DeviceToMemory => self.fault_handler.on_write_denied(addr, len),
+                DmaDirection::MemoryToDevice => self.fault_handler.on_read_denied(addr, len),
+            }
+            return Err(ShadowError::Denied);
+        }
+
+        self.table.pin_range(addr, len)?;
+
+        match self
+            .table
+            .with_bytes_mut(addr, len, |buf| Ok(buf.as_mut_ptr()))
+        {
+            Ok(ptr) => Ok(DmaRegion::new(addr, ptr, len, dir)),
+            Err(err) => {
+                let _ = self.table.unpin_range(addr, len);
+                Err(err)
+            }
+        }
+    }
+
+    /// Hands `f` a zero-copy [`DmaWindowGuard`] over `addr..addr+len`'s
+    /// bytes in the shadow table's backing storage, so a DMA engine (or
+    /// `f` itself) can fill the window without an intermediate copy.
+    ///
+    /// Unlike [`Self::with_dma_region`], whose [`DmaRegion`] is only
+    /// resolved by a later, separate
+    /// [`HostShadow::complete_dma`](crate::shadow::HostShadow::complete_dma)
+    /// call, the window here is scoped to `f`: checked against the
+    /// [`AccessPolicy`] exactly like [`Self::with_wo_slice`] up front, and
+    /// on drop the guard recomputes the blocks `addr..addr+len` spans,
+    /// marks them dirty, and runs the persist policy/trigger — so by the
+    /// time `with_dma_window` returns, dirty tracking and persistence
+    /// already reflect whatever `f` wrote, with no separate completion
+    /// call. Because the guard borrows this view for `'g`, it cannot
+    /// outlive the call and is always released before `with_view` returns.
+    pub fn with_dma_window<F, R>(&mut self, addr: u16, len: usize, f: F) -> Result<R, ShadowError>
+    where
+        F: FnOnce(DmaWindowGuard<'_, 'a, TS, BS, BC, AP, PP, PT, PK, TB>) -> R,
+    {
+        // Bounds-checked up front, before any bytes are handed out.
+        block_span::<TS, BS, BC>(addr, len)?;
+
+        if !self.access_policy.can_write(addr, len) {
+            self.fault_handler.on_write_denied(addr, len);
+            return Err(ShadowError::Denied);
+        }
+
+        let ptr = self
+            .table
+            .with_bytes_mut(addr, len, |buf| Ok(buf.as_mut_ptr()))?;
+
+        let guard = DmaWindowGuard {
+            view: self,
+            addr,
+            len,
+            ptr,
+        };
+
+        Ok(f(guard))
+    }
+
+    /// Warms any never-written block in `addr..addr+len` from the
+    /// configured [`BackingStore`], so a subsequent [`Self::table`] read
+    /// sees the backing image instead of the backend's fill byte. A no-op
+    /// once every block in range has been written or previously warmed, so
+    /// repeated reads of the same range only ever hit the backing store
+    /// once.
+    fn fill_from_backing_store(&mut self, addr: u16, len: usize) -> Result<(), ShadowError> {
+        if self.table.is_populated(addr, len)? {
+            return Ok(());
+        }
+
+        let backing_store = self.backing_store;
+        self.table
+            .with_bytes_mut(addr, len, |buf| backing_store.load(addr, buf))
+    }
+
     pub(crate) fn with_bytes_mut_no_persist<F, R>(
         &mut self,
         addr: u16,
@@ -158,6 +395,11 @@ where
     where
         F: FnOnce(&mut [u8]) -> WriteResult<R>,
     {
+        if self.table.is_locked(addr, len)? {
+            self.fault_handler.on_write_denied(addr, len);
+            return Err(ShadowError::Denied);
+        }
+
         let write_result = self.table.with_bytes_mut(addr, len, |data| Ok(f(data)))?;
 
         if write_result.is_dirty() {
@@ -166,15 +408,134 @@ where
 
         Ok(write_result)
     }
+
+    /// Like [`Self::with_bytes_mut_no_persist`], but leaves dirty-marking to
+    /// the caller entirely instead of marking `addr..addr+len` immediately.
+    ///
+    /// Used by [`HostViewStaged::commit_staged`](crate::shadow::HostViewStaged::commit_staged)
+    /// to apply every staged write's payload first and mark their union of
+    /// blocks dirty in one batched pass afterward, rather than walking each
+    /// entry's block span twice.
+    pub(crate) fn with_bytes_mut_unmarked<F, R>(
+        &mut self,
+        addr: u16,
+        len: usize,
+        f: F,
+    ) -> Result<R, ShadowError>
+    where
+        F: FnOnce(&mut [u8]) -> R,
+    {
+        if self.table.is_locked(addr, len)? {
+            self.fault_handler.on_write_denied(addr, len);
+            return Err(ShadowError::Denied);
+        }
+
+        self.table.with_bytes_mut(addr, len, |data| Ok(f(data)))
+    }
+}
+
+/// Zero-copy write window into a [`HostView`]'s backing bytes, handed to
+/// the closure passed to [`HostView::with_dma_window`].
+///
+/// Derefs to the requested `&mut [u8]`. Dropping it marks the window's
+/// blocks dirty and runs the persist policy/trigger, exactly as
+/// [`HostView::with_wo_slice`] would for the same range — the bounds check
+/// already happened in `with_dma_window`, so the drop's bookkeeping cannot
+/// fail.
+pub struct DmaWindowGuard<
+    'g,
+    'a,
+    const TS: usize,
+    const BS: usize,
+    const BC: usize,
+    AP,
+    PP,
+    PT,
+    PK,
+    TB,
+> where
+    bitmaps::BitsImpl<BC>: bitmaps::Bits,
+    AP: AccessPolicy,
+    PP: PersistPolicy<PK>,
+    PT: PersistTrigger<PK>,
+    TB: TableBackend<TS>,
+{
+    view: &'g mut HostView<'a, TS, BS, BC, AP, PP, PT, PK, TB>,
+    addr: u16,
+    len: usize,
+    ptr: *mut u8,
+}
+
+impl<'g, 'a, const TS: usize, const BS: usize, const BC: usize, AP, PP, PT, PK, TB> Deref
+    for DmaWindowGuard<'g, 'a, TS, BS, BC, AP, PP, PT, PK, TB>
+where
+    bitmaps::BitsImpl<BC>: bitmaps::Bits,
+    AP: AccessPolicy,
+    PP: PersistPolicy<PK>,
+    PT: PersistTrigger<PK>,
+    TB: TableBackend<TS>,
+{
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        // SAFETY: `ptr` was obtained from the table's backing storage for
+        // `addr..addr+len` by `with_dma_window`, which borrows `view`
+        // exclusively for the guard's lifetime `'g`, so no other access to
+        // these bytes can alias this one.
+        unsafe { core::slice::from_raw_parts(self.ptr, self.len) }
+    }
+}
+
+impl<'g, 'a, const TS: usize, const BS: usize, const BC: usize, AP, PP, PT, PK, TB> DerefMut
+    for DmaWindowGuard<'g, 'a, TS, BS, BC, AP, PP, PT, PK, TB>
+where
+    bitmaps::BitsImpl<BC>: bitmaps::Bits,
+    AP: AccessPolicy,
+    PP: PersistPolicy<PK>,
+    PT: PersistTrigger<PK>,
+    TB: TableBackend<TS>,
+{
+    fn deref_mut(&mut self) -> &mut [u8] {
+        // SAFETY: see `Deref::deref`.
+        unsafe { core::slice::from_raw_parts_mut(self.ptr, self.len) }
+    }
+}
+
+impl<'g, 'a, const TS: usize, const BS: usize, const BC: usize, AP, PP, PT, PK, TB> Drop
+    for DmaWindowGuard<'g, 'a, TS, BS, BC, AP, PP, PT, PK, TB>
+where
+    bitmaps::BitsImpl<BC>: bitmaps::Bits,
+    AP: AccessPolicy,
+    PP: PersistPolicy<PK>,
+    PT: PersistTrigger<PK>,
+    TB: TableBackend<TS>,
+{
+    fn drop(&mut self) {
+        let _ = self.view.table.mark_dirty(self.addr, self.len);
+
+        let should_persist =
+            self.view
+                .persist_policy
+                .push_persist_keys_for_range(self.addr, self.len, |key| {
+                    self.view.persist_trigger.push_key(key)
+                });
+
+        if should_persist {
+            self.view.persist_trigger.request_persist();
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::shadow::backing::NoBackingStore;
+    use crate::shadow::fault::NoFaultHandler;
     use crate::shadow::persist::NoPersist;
-    use crate::shadow::policy::NoPersistPolicy;
+    use crate::shadow::policy::{AllowAllPolicy, NoPersistPolicy};
     use crate::shadow::test_support::{
-        DenyAllPolicy, ReadOnlyBelow32, TestHostViewFixture, TestTable, assert_denied,
+        assert_denied, assert_table_bytes, AlwaysPersistPolicy, DenyAllPolicy, ReadOnlyBelow32,
+        TestHostViewFixture, TestTable, TrackingPersistTrigger,
     };
 
     #[test]
@@ -219,24 +580,89 @@ mod tests {
         let policy = DenyAllPolicy;
         let persist_policy = NoPersistPolicy::default();
         let mut trigger = NoPersist;
+        let mut fault_handler = NoFaultHandler;
 
         // Test RO denied
         {
-            let view = HostView::new(&mut table, &policy, &persist_policy, &mut trigger);
+            let mut view = HostView::new(
+                &mut table,
+                &policy,
+                &persist_policy,
+                &mut trigger,
+                &mut fault_handler,
+                &NoBackingStore,
+            );
             assert_denied(view.with_ro_slice(0, 4, |_slice| {}));
         }
 
         // Test WO denied
         {
-            let mut view = HostView::new(&mut table, &policy, &persist_policy, &mut trigger);
+            let mut view = HostView::new(
+                &mut table,
+                &policy,
+                &persist_policy,
+                &mut trigger,
+                &mut fault_handler,
+                &NoBackingStore,
+            );
             assert_denied(view.with_wo_slice(0, 4, |_| WriteResult::Clean(())));
         }
 
         // Test RW denied
         {
-            let mut view = HostView::new(&mut table, &policy, &persist_policy, &mut trigger);
+            let mut view = HostView::new(
+                &mut table,
+                &policy,
+                &persist_policy,
+                &mut trigger,
+                &mut fault_handler,
+                &NoBackingStore,
+            );
+            assert_denied(view.with_rw_slice(0, 4, |_| WriteResult::Clean(())));
+        }
+    }
+
+    #[test]
+    fn denied_access_notifies_fault_handler() {
+        use crate::shadow::fault::AccessFaultHandler;
+
+        #[derive(Default)]
+        struct CountingFaultHandler {
+            reads_denied: usize,
+            writes_denied: usize,
+        }
+
+        impl AccessFaultHandler for CountingFaultHandler {
+            fn on_read_denied(&mut self, _addr: u16, _len: usize) {
+                self.reads_denied += 1;
+            }
+            fn on_write_denied(&mut self, _addr: u16, _len: usize) {
+                self.writes_denied += 1;
+            }
+        }
+
+        let mut table = TestTable::new();
+        let policy = DenyAllPolicy;
+        let persist_policy = NoPersistPolicy::default();
+        let mut trigger = NoPersist;
+        let mut fault_handler = CountingFaultHandler::default();
+
+        {
+            let mut view = HostView::new(
+                &mut table,
+                &policy,
+                &persist_policy,
+                &mut trigger,
+                &mut fault_handler,
+                &NoBackingStore,
+            );
+            assert_denied(view.with_ro_slice(0, 4, |_slice| {}));
+            assert_denied(view.with_wo_slice(0, 4, |_| WriteResult::Clean(())));
             assert_denied(view.with_rw_slice(0, 4, |_| WriteResult::Clean(())));
         }
+
+        assert_eq!(fault_handler.reads_denied, 1);
+        assert_eq!(fault_handler.writes_denied, 2);
     }
 
     #[test]
@@ -245,8 +671,16 @@ mod tests {
         let policy = ReadOnlyBelow32; // Can read anywhere, write only >= 32
         let persist_policy = NoPersistPolicy::default();
         let mut trigger = NoPersist;
+        let mut fault_handler = NoFaultHandler;
 
-        let mut view = HostView::new(&mut table, &policy, &persist_policy, &mut trigger);
+        let mut view = HostView::new(
+            &mut table,
+            &policy,
+            &persist_policy,
+            &mut trigger,
+            &mut fault_handler,
+            &NoBackingStore,
+        );
 
         // Below 32: can read but not write, so rw_slice should fail
         assert_denied(view.with_rw_slice(0, 4, |_| WriteResult::Clean(())));
@@ -265,9 +699,17 @@ mod tests {
         let policy = AllowAllPolicy::default();
         let persist_policy = AlwaysPersistPolicy; // Would trigger persist if dirty
         let mut trigger = TrackingPersistTrigger::default();
+        let mut fault_handler = NoFaultHandler;
 
         {
-            let mut view = HostView::new(&mut table, &policy, &persist_policy, &mut trigger);
+            let mut view = HostView::new(
+                &mut table,
+                &policy,
+                &persist_policy,
+                &mut trigger,
+                &mut fault_handler,
+                &NoBackingStore,
+            );
 
             // Write data but return Clean to indicate not dirty
             view.with_wo_slice(0, 4, |mut slice| {
@@ -292,9 +734,17 @@ mod tests {
         let policy = AllowAllPolicy::default();
         let persist_policy = AlwaysPersistPolicy;
         let mut trigger = TrackingPersistTrigger::default();
+        let mut fault_handler = NoFaultHandler;
 
         {
-            let mut view = HostView::new(&mut table, &policy, &persist_policy, &mut trigger);
+            let mut view = HostView::new(
+                &mut table,
+                &policy,
+                &persist_policy,
+                &mut trigger,
+                &mut fault_handler,
+                &NoBackingStore,
+            );
             view.with_wo_slice(0, 4, |mut slice| {
                 slice.copy_from_slice(&[0x01, 0x02, 0x03, 0x04]);
                 WriteResult::Dirty(()) // Mark dirty - should trigger persist
@@ -315,9 +765,17 @@ mod tests {
         let policy = AllowAllPolicy::default();
         let persist_policy = AlwaysPersistPolicy;
         let mut trigger = TrackingPersistTrigger::default();
+        let mut fault_handler = NoFaultHandler;
 
         {
-            let mut view = HostView::new(&mut table, &policy, &persist_policy, &mut trigger);
+            let mut view = HostView::new(
+                &mut table,
+                &policy,
+                &persist_policy,
+                &mut trigger,
+                &mut fault_handler,
+                &NoBackingStore,
+            );
             let result = view
                 .with_rw_slice(0, 4, |mut slice| {
                     slice.copy_from_slice(&[0xAA, 0xBB, 0xCC, 0xDD]);
@@ -373,4 +831,275 @@ mod tests {
             .unwrap();
         assert!(fixture.table.is_dirty(0, 4).unwrap());
     }
+
+    #[test]
+    fn transaction_commits_all_touched_blocks_dirty() {
+        let mut fixture = TestHostViewFixture::new();
+
+        {
+            let mut view = fixture.view();
+            view.with_transaction::<4, _, ()>(|txn| {
+                txn.with_wo_slice(0, 4, |mut slice| {
+                    slice.copy_from_slice(&[0xAA, 0xBB, 0xCC, 0xDD]);
+                })?;
+                txn.with_wo_slice(32, 4, |mut slice| {
+                    slice.copy_from_slice(&[0x11, 0x22, 0x33, 0x44]);
+                })?;
+                Ok(())
+            })
+            .unwrap();
+        }
+
+        assert_table_bytes(&fixture.table, 0, &[0xAA, 0xBB, 0xCC, 0xDD]);
+        assert_table_bytes(&fixture.table, 32, &[0x11, 0x22, 0x33, 0x44]);
+        assert!(fixture.table.is_dirty(0, 4).unwrap());
+        assert!(fixture.table.is_dirty(32, 4).unwrap());
+    }
+
+    #[test]
+    fn transaction_rolls_back_all_writes_on_err() {
+        let mut fixture = TestHostViewFixture::new();
+
+        // Pre-existing data the transaction should not disturb on failure.
+        {
+            let mut view = fixture.view();
+            view.with_wo_slice(0, 4, |mut slice| {
+                slice.copy_from_slice(&[1, 2, 3, 4]);
+                WriteResult::Dirty(())
+            })
+            .unwrap();
+        }
+        fixture.table.clear_all_dirty();
+
+        {
+            let mut view = fixture.view();
+            let result = view.with_transaction::<4, _, ()>(|txn| {
+                txn.with_wo_slice(0, 4, |mut slice| {
+                    slice.copy_from_slice(&[0xAA, 0xBB, 0xCC, 0xDD]);
+                })?;
+                txn.with_wo_slice(32, 4, |mut slice| {
+                    slice.copy_from_slice(&[0x11, 0x22, 0x33, 0x44]);
+                })?;
+                Err(ShadowError::Denied)
+            });
+            assert_eq!(result, Err(ShadowError::Denied));
+        }
+
+        // Both blocks restored to their pre-transaction contents.
+        assert_table_bytes(&fixture.table, 0, &[1, 2, 3, 4]);
+        assert_table_bytes(&fixture.table, 32, &[0, 0, 0, 0]);
+        assert!(!fixture.table.any_dirty());
+    }
+
+    #[test]
+    fn with_dma_region_rejects_unaligned_requests() {
+        let mut fixture = TestHostViewFixture::new();
+        let mut view = fixture.view();
+
+        assert_eq!(
+            view.with_dma_region(1, 16, DmaDirection::DeviceToMemory),
+            Err(ShadowError::Unaligned)
+        );
+        assert_eq!(
+            view.with_dma_region(0, 15, DmaDirection::DeviceToMemory),
+            Err(ShadowError::Unaligned)
+        );
+    }
+
+    #[test]
+    fn with_dma_region_checks_access_policy_per_direction() {
+        let mut table = TestTable::new();
+        let policy = ReadOnlyBelow32; // Can read anywhere, write only >= 32
+        let persist_policy = NoPersistPolicy::default();
+        let mut trigger = NoPersist;
+        let mut fault_handler = NoFaultHandler;
+        let mut view = HostView::new(
+            &mut table,
+            &policy,
+            &persist_policy,
+            &mut trigger,
+            &mut fault_handler,
+            &NoBackingStore,
+        );
+
+        // Write lease below 32 is denied; read lease anywhere is allowed.
+        assert_denied(view.with_dma_region(0, 16, DmaDirection::DeviceToMemory));
+        assert!(view
+            .with_dma_region(0, 16, DmaDirection::MemoryToDevice)
+            .is_ok());
+    }
+
+    #[test]
+    fn with_dma_region_yields_writable_pointer_into_the_table() {
+        let mut fixture = TestHostViewFixture::new();
+        let mut view = fixture.view();
+
+        let mut region = view
+            .with_dma_region(0, 16, DmaDirection::DeviceToMemory)
+            .unwrap();
+        unsafe {
+            core::ptr::copy_nonoverlapping([0xAA; 16].as_ptr(), region.as_mut_ptr(), 16);
+        }
+
+        view.with_ro_slice(0, 16, |slice| {
+            let mut buf = [0u8; 16];
+            slice.copy_to_slice(&mut buf);
+            assert_eq!(buf, [0xAA; 16]);
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn with_dma_region_rejects_overlapping_lease() {
+        let mut fixture = TestHostViewFixture::new();
+        let mut view = fixture.view();
+
+        let _region = view
+            .with_dma_region(0, 16, DmaDirection::DeviceToMemory)
+            .unwrap();
+
+        assert_eq!(
+            view.with_dma_region(0, 16, DmaDirection::MemoryToDevice),
+            Err(ShadowError::Pinned)
+        );
+    }
+
+    #[test]
+    fn with_dma_window_applies_writes_and_marks_dirty_on_drop() {
+        let mut fixture = TestHostViewFixture::new();
+
+        {
+            let mut view = fixture.view();
+            view.with_dma_window(0, 4, |mut window| {
+                window.copy_from_slice(&[0xAA, 0xBB, 0xCC, 0xDD]);
+            })
+            .unwrap();
+        }
+
+        assert_table_bytes(&fixture.table, 0, &[0xAA, 0xBB, 0xCC, 0xDD]);
+        assert!(fixture.table.is_dirty(0, 4).unwrap());
+    }
+
+    #[test]
+    fn with_dma_window_rejects_out_of_bounds_windows() {
+        let mut fixture = TestHostViewFixture::new();
+        let mut view = fixture.view();
+
+        assert_eq!(
+            view.with_dma_window(u16::MAX, 4, |_window| {}),
+            Err(ShadowError::OutOfBounds)
+        );
+    }
+
+    #[test]
+    fn with_dma_window_checks_access_policy() {
+        let mut table = TestTable::new();
+        let policy = DenyAllPolicy;
+        let persist_policy = NoPersistPolicy::default();
+        let mut trigger = NoPersist;
+        let mut fault_handler = NoFaultHandler;
+        let mut view = HostView::new(
+            &mut table,
+            &policy,
+            &persist_policy,
+            &mut trigger,
+            &mut fault_handler,
+            &NoBackingStore,
+        );
+
+        assert_denied(view.with_dma_window(0, 4, |_window| {}));
+    }
+
+    #[test]
+    fn with_dma_window_triggers_persist_once_the_guard_drops() {
+        let mut table = TestTable::new();
+        let policy = AllowAllPolicy::default();
+        let persist_policy = AlwaysPersistPolicy;
+        let mut trigger = TrackingPersistTrigger::default();
+        let mut fault_handler = NoFaultHandler;
+        let mut view = HostView::new(
+            &mut table,
+            &policy,
+            &persist_policy,
+            &mut trigger,
+            &mut fault_handler,
+            &NoBackingStore,
+        );
+
+        view.with_dma_window(0, 4, |mut window| {
+            window.copy_from_slice(&[1, 2, 3, 4]);
+        })
+        .unwrap();
+
+        assert!(trigger.persist_requested);
+    }
+
+    #[test]
+    fn locked_block_rejects_host_writes_but_not_reads() {
+        let mut fixture = TestHostViewFixture::new();
+
+        {
+            let mut view = fixture.view();
+            view.lock(0, 4).unwrap();
+
+            assert_denied(view.with_wo_slice(0, 4, |_| WriteResult::Dirty(())));
+            assert_denied(view.with_rw_slice(0, 4, |_| WriteResult::Dirty(())));
+            view.with_ro_slice(0, 4, |_slice| {}).unwrap();
+        }
+
+        assert!(!fixture.table.any_dirty());
+    }
+
+    #[test]
+    fn unlock_restores_write_access() {
+        let mut fixture = TestHostViewFixture::new();
+
+        let mut view = fixture.view();
+        view.lock(0, 4).unwrap();
+        assert!(view.is_locked(0, 4).unwrap());
+
+        view.unlock(0, 4).unwrap();
+        assert!(!view.is_locked(0, 4).unwrap());
+
+        let result = view.with_wo_slice(0, 4, |mut slice| {
+            slice.copy_from_slice(&[1, 2, 3, 4]);
+            WriteResult::Dirty(())
+        });
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn transaction_rejects_writes_to_a_locked_block() {
+        let mut fixture = TestHostViewFixture::new();
+
+        let mut view = fixture.view();
+        view.lock(0, 4).unwrap();
+
+        let result = view.with_transaction::<4, _, ()>(|txn| {
+            txn.with_wo_slice(0, 4, |mut slice| {
+                slice.copy_from_slice(&[0xAA, 0xBB, 0xCC, 0xDD]);
+            })?;
+            Ok(())
+        });
+
+        assert_eq!(result, Err(ShadowError::Denied));
+    }
+
+    #[test]
+    fn transaction_full_when_more_blocks_touched_than_scratch_allows() {
+        let mut fixture = TestHostViewFixture::new();
+        let mut view = fixture.view();
+
+        let result = view.with_transaction::<1, _, ()>(|txn| {
+            txn.with_wo_slice(0, 4, |mut slice| {
+                slice.copy_from_slice(&[1, 2, 3, 4]);
+            })?;
+            txn.with_wo_slice(16, 4, |mut slice| {
+                slice.copy_from_slice(&[5, 6, 7, 8]);
+            })?;
+            Ok(())
+        });
+
+        assert_eq!(result, Err(ShadowError::TransactionFull));
+    }
 }
@@ -1,54 +1,130 @@
 use crate::shadow::{
-    AccessPolicy, HostView, PersistTrigger, ShadowError,
+    backend::{DenseBackend, TableBackend},
+    cache::CacheMaintenance,
+    fault::AccessFaultHandler,
+    helpers::block_span,
     policy::PersistPolicy,
     slice::{ROSlice, RWSlice, WOSlice},
-    types::StagingBuffer,
+    table::ShadowTable,
+    types::{Savepoint, StagingBuffer},
+    AccessPolicy, HostView, PersistTrigger, ShadowError,
 };
 
+/// Read-only view of the shadow table as it will read once the staging
+/// buffer's currently staged writes commit: a staged byte wins over the
+/// committed table wherever the two overlap.
+///
+/// Built by [`HostViewStaged::try_commit_staged`] so a validator can check
+/// the final, merged state — including the effect of staged writes
+/// overriding each other — before anything is written to the table.
+pub struct StagedOverlay<
+    'v,
+    const TS: usize,
+    const BS: usize,
+    const BC: usize,
+    SB,
+    TB = DenseBackend<TS>,
+> where
+    bitmaps::BitsImpl<BC>: bitmaps::Bits,
+    SB: StagingBuffer,
+    TB: TableBackend<TS>,
+{
+    table: &'v ShadowTable<TS, BS, BC, TB>,
+    sb: &'v SB,
+}
+
+impl<'v, const TS: usize, const BS: usize, const BC: usize, SB, TB>
+    StagedOverlay<'v, TS, BS, BC, SB, TB>
+where
+    bitmaps::BitsImpl<BC>: bitmaps::Bits,
+    SB: StagingBuffer,
+    TB: TableBackend<TS>,
+{
+    /// Fills `out` with the `out.len()` bytes starting at `addr`, as they
+    /// will read after commit: the committed table's bytes, overlaid with
+    /// whatever part of any staged write falls within `addr..addr+out.len()`.
+    pub fn read_range_overlay(&self, addr: u16, out: &mut [u8]) -> Result<(), ShadowError> {
+        self.table
+            .with_bytes(addr, out.len(), |data| Ok(out.copy_from_slice(data)))?;
+
+        let start = addr as usize;
+        let end = start + out.len();
+        self.sb.iter_staged(|staged_addr, data| {
+            let staged_start = staged_addr as usize;
+            let staged_end = staged_start + data.len();
+            let lo = start.max(staged_start);
+            let hi = end.min(staged_end);
+            if lo < hi {
+                out[lo - start..hi - start]
+                    .copy_from_slice(&data[lo - staged_start..hi - staged_start]);
+            }
+            Ok(())
+        })
+    }
+}
+
 /// Host view with transactional staging support.
 ///
 /// Allows writes to be staged and previewed before committing to the shadow table.
-pub struct HostViewStaged<'a, const TS: usize, const BS: usize, const BC: usize, AP, PP, PT, PK, SB>
-where
+pub struct HostViewStaged<
+    'a,
+    const TS: usize,
+    const BS: usize,
+    const BC: usize,
+    AP,
+    PP,
+    PT,
+    PK,
+    SB,
+    TB = DenseBackend<TS>,
+> where
     AP: AccessPolicy,
     PP: PersistPolicy<PK>,
     PT: PersistTrigger<PK>,
     bitmaps::BitsImpl<BC>: bitmaps::Bits,
     SB: StagingBuffer,
+    TB: TableBackend<TS>,
 {
-    base: HostView<'a, TS, BS, BC, AP, PP, PT, PK>,
+    base: HostView<'a, TS, BS, BC, AP, PP, PT, PK, TB>,
     sb: &'a mut SB,
+    cache: &'a mut dyn CacheMaintenance,
 }
 
-impl<'a, const TS: usize, const BS: usize, const BC: usize, AP, PP, PT, PK, SB> core::fmt::Debug
-    for HostViewStaged<'a, TS, BS, BC, AP, PP, PT, PK, SB>
+impl<'a, const TS: usize, const BS: usize, const BC: usize, AP, PP, PT, PK, SB, TB> core::fmt::Debug
+    for HostViewStaged<'a, TS, BS, BC, AP, PP, PT, PK, SB, TB>
 where
     AP: AccessPolicy,
     PP: PersistPolicy<PK>,
     PT: PersistTrigger<PK>,
     bitmaps::BitsImpl<BC>: bitmaps::Bits,
     SB: StagingBuffer,
+    TB: TableBackend<TS>,
 {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.debug_struct("HostViewStaged").finish_non_exhaustive()
     }
 }
 
-impl<'a, const TS: usize, const BS: usize, const BC: usize, AP, PP, PT, PK, SB>
-    HostViewStaged<'a, TS, BS, BC, AP, PP, PT, PK, SB>
+impl<'a, const TS: usize, const BS: usize, const BC: usize, AP, PP, PT, PK, SB, TB>
+    HostViewStaged<'a, TS, BS, BC, AP, PP, PT, PK, SB, TB>
 where
     AP: AccessPolicy,
     PP: PersistPolicy<PK>,
     PT: PersistTrigger<PK>,
     bitmaps::BitsImpl<BC>: bitmaps::Bits,
     SB: StagingBuffer,
+    TB: TableBackend<TS>,
 {
-    pub(crate) fn new(base: HostView<'a, TS, BS, BC, AP, PP, PT, PK>, sb: &'a mut SB) -> Self {
-        Self { base, sb }
+    pub(crate) fn new(
+        base: HostView<'a, TS, BS, BC, AP, PP, PT, PK, TB>,
+        sb: &'a mut SB,
+        cache: &'a mut dyn CacheMaintenance,
+    ) -> Self {
+        Self { base, sb, cache }
     }
 
     /// Provides zero-copy read access via ROSlice (ignores staged writes).
-    pub fn with_ro_slice<F, R>(&self, addr: u16, len: usize, f: F) -> Result<R, ShadowError>
+    pub fn with_ro_slice<F, R>(&mut self, addr: u16, len: usize, f: F) -> Result<R, ShadowError>
     where
         F: FnOnce(ROSlice<'_>) -> R,
     {
@@ -83,8 +159,18 @@ where
 
     /// Zero-copy staged write access via RWSlice.
     ///
+    /// Returns `Denied` if the access policy rejects the write, after
+    /// notifying the [`AccessFaultHandler`](crate::shadow::AccessFaultHandler).
     /// Return `(true, result)` from your callback to commit the staged write.
     /// If you return `false`, no data is staged and space is reclaimed.
+    ///
+    /// If the staging buffer was built with an LRU eviction policy (e.g.
+    /// `PatchStagingBuffer::stage_evict_lru`) and there isn't room for this
+    /// write, the least-recently-touched staged entries are force-committed
+    /// straight to the table — applied, marked dirty and pushed to the
+    /// [`PersistTrigger`] — one at a time until enough room is freed, rather
+    /// than failing with [`ShadowError::StageFull`]. A buffer without such a
+    /// policy behaves exactly as before.
     pub fn alloc_staged<F, R>(
         &mut self,
         addr: u16,
@@ -95,9 +181,41 @@ where
         F: FnOnce(RWSlice<'_>) -> (bool, R),
     {
         if !self.base.access_policy.can_write(addr, len) {
+            self.base.fault_handler.on_write_denied(addr, len);
             return Err(ShadowError::Denied);
         }
 
+        while self.sb.would_overflow(addr, len) {
+            let base = &mut self.base;
+            let cache = &mut self.cache;
+            let evicted = self.sb.evict_oldest_staged(|evicted_addr, evicted_data| {
+                base.with_bytes_mut_unmarked(evicted_addr, evicted_data.len(), |buf| {
+                    buf.copy_from_slice(evicted_data)
+                })?;
+                cache.clean_range(evicted_addr, evicted_data.len());
+                base.table.mark_dirty(evicted_addr, evicted_data.len())?;
+
+                let mut keys: heapless::Vec<PK, BC> = heapless::Vec::new();
+                base.persist_policy.push_persist_keys_for_range(
+                    evicted_addr,
+                    evicted_data.len(),
+                    |key| {
+                        let _ = keys.push(key);
+                    },
+                );
+                for key in keys {
+                    base.persist_trigger.push_key(key);
+                }
+                base.persist_trigger.request_persist();
+
+                Ok(())
+            })?;
+
+            if !evicted {
+                break;
+            }
+        }
+
         let mut result = None;
         let written = self.sb.alloc_staged(addr, len, |data| {
             let (written, r) = f(RWSlice::new(data));
@@ -110,39 +228,143 @@ where
 
     /// Commits all staged writes to the shadow table.
     ///
-    /// Staged writes are applied in order, marking blocks dirty and
-    /// triggering persistence as configured. The staging buffer is
-    /// cleared after successful commit.
-    pub fn commit_staged(&mut self) -> Result<(), ShadowError> {
+    /// First [`compacts`](StagingBuffer::compact) the staging buffer, so
+    /// repeatedly-staged overlapping ranges write each touched byte once
+    /// rather than once per staged write. Every staged entry's payload is
+    /// then applied to the table, and the union of blocks they touch is
+    /// computed as it goes; once all payloads are applied, that union is
+    /// marked dirty in one batched pass (one dirty-marking call per
+    /// contiguous run, rather than once per staged entry), and persist keys
+    /// collected across every entry are deduplicated through a
+    /// temporary `BC`-sized set so each one reaches
+    /// [`PersistTrigger::push_key`] at most once before a single
+    /// [`PersistTrigger::request_persist`]. The staging buffer is cleared
+    /// after successful commit; as before, an error leaves it intact.
+    pub fn commit_staged(&mut self) -> Result<(), ShadowError>
+    where
+        PK: PartialEq,
+    {
         if !self.sb.any_staged() {
             return Ok(());
         }
 
-        let mut should_persist = false;
+        self.sb.compact()?;
+
+        let mut dirty_blocks = bitmaps::Bitmap::<BC>::new();
+        let mut pending_keys: heapless::Vec<PK, BC> = heapless::Vec::new();
+        let cache = &mut self.cache;
         self.sb.iter_staged(|addr, data| {
             self.base
-                .with_bytes_mut_no_persist(addr, data.len(), |buf| {
-                    buf.copy_from_slice(data);
-                    (true, ())
-                })?;
-            should_persist |=
-                self.base
-                    .persist_policy
-                    .push_persist_keys_for_range(addr, data.len(), |key| {
-                        self.base.persist_trigger.push_key(key)
-                    });
+                .with_bytes_mut_unmarked(addr, data.len(), |buf| buf.copy_from_slice(data))?;
+            cache.clean_range(addr, data.len());
+
+            let (sb, eb) = block_span::<TS, BS, BC>(addr, data.len())?;
+            for block in sb..=eb {
+                dirty_blocks.set(block, true);
+            }
+
+            self.base
+                .persist_policy
+                .push_persist_keys_for_range(addr, data.len(), |key| {
+                    if !pending_keys.iter().any(|pending| pending == &key) {
+                        let _ = pending_keys.push(key);
+                    }
+                });
             Ok(())
         })?;
 
         self.sb.clear_staged()?;
 
-        if should_persist {
+        let mut idx = dirty_blocks.first_index();
+        while let Some(run_start) = idx {
+            let mut run_end = run_start;
+            let mut next = dirty_blocks.next_index(run_end);
+            while next == Some(run_end + 1) {
+                run_end += 1;
+                next = dirty_blocks.next_index(run_end);
+            }
+
+            let off = run_start * BS;
+            let len = (run_end - run_start + 1) * BS;
+            self.base.table.mark_dirty(off as u16, len)?;
+
+            idx = next;
+        }
+
+        if !pending_keys.is_empty() {
+            for key in pending_keys {
+                self.base.persist_trigger.push_key(key);
+            }
             self.base.persist_trigger.request_persist();
         }
 
         Ok(())
     }
 
+    /// Like [`Self::commit_staged`], but validates the merged post-commit
+    /// state before touching the table instead of applying staged writes
+    /// unconditionally.
+    ///
+    /// [`Compacts`](StagingBuffer::compact) the staging buffer so every
+    /// touched byte has one final value (later staged writes overriding
+    /// earlier ones where they overlap), then hands `validate` a
+    /// [`StagedOverlay`] that reads that merged state on top of the
+    /// committed table — e.g. checking `min_value < max_value` across two
+    /// overlapping sensor-cal overrides. If `validate` returns `true`, the
+    /// staged writes are applied exactly as [`Self::commit_staged`] does and
+    /// this returns `Ok(true)`; if `false`, the table is left untouched, the
+    /// writes stay staged, and this returns `Ok(false)`.
+    pub fn try_commit_staged(
+        &mut self,
+        validate: impl FnOnce(&StagedOverlay<'_, TS, BS, BC, SB, TB>) -> bool,
+    ) -> Result<bool, ShadowError>
+    where
+        PK: PartialEq,
+    {
+        if !self.sb.any_staged() {
+            return Ok(true);
+        }
+
+        self.sb.compact()?;
+
+        let overlay = StagedOverlay {
+            table: &*self.base.table,
+            sb: &*self.sb,
+        };
+        if !validate(&overlay) {
+            return Ok(false);
+        }
+
+        self.commit_staged()?;
+        Ok(true)
+    }
+
+    /// Like [`Self::commit_staged`], but first makes the whole staged patch
+    /// set durable via a write-ahead journal before applying anything to
+    /// the shadow table.
+    ///
+    /// Every staged write is serialized to the configured [`PersistTrigger`]'s
+    /// [`PersistTrigger::journal_append`] hook as a framed record, followed
+    /// by a commit marker covering the records with a CRC16. Only once that
+    /// marker has been appended does this apply the staged writes, exactly
+    /// as [`Self::commit_staged`] does. On boot,
+    /// [`replay_journal`](crate::shadow::staged::replay_journal) re-applies
+    /// a journal recovered with a valid marker, or discards it silently
+    /// otherwise — so a reset between two writes either replays the whole
+    /// transaction or none of it.
+    pub fn commit_journaled(&mut self) -> Result<(), ShadowError>
+    where
+        PK: PartialEq,
+    {
+        if !self.sb.any_staged() {
+            return Ok(());
+        }
+
+        crate::shadow::staged::write_records(&*self.sb, self.base.persist_trigger)?;
+
+        self.commit_staged()
+    }
+
     /// Returns true if there are any staged writes pending.
     pub fn is_staged(&self) -> bool {
         self.sb.any_staged()
@@ -158,9 +380,35 @@ where
     }
 
     /// Clears all staged writes without committing them.
+    ///
+    /// Invalidates any outstanding [`Savepoint`] taken before this call: its
+    /// captured lengths now exceed the (empty) buffer, so
+    /// [`Self::rollback_to`] is a no-op rather than restoring stale data,
+    /// as long as the buffer holds no more bytes than the savepoint
+    /// captured.
     pub fn clear_staged(&mut self) -> Result<(), ShadowError> {
         self.sb.clear_staged()
     }
+
+    /// Captures the current staging buffer state, for later
+    /// [`Self::rollback_to`]. See [`StagingBuffer::savepoint`] for what a
+    /// savepoint is valid against.
+    ///
+    /// [`Self::commit_staged`] and [`Self::clear_staged`] both invalidate
+    /// any savepoint taken before them: rolling back to one afterward is a
+    /// no-op rather than restoring the writes it discarded, as long as the
+    /// buffer has not since grown past the savepoint's captured length.
+    pub fn savepoint(&self) -> Savepoint {
+        self.sb.savepoint()
+    }
+
+    /// Discards staged writes made after `sp`, keeping everything staged
+    /// before it. A no-op if `sp` is stale — captured before a
+    /// [`Self::commit_staged`] or [`Self::clear_staged`] that has already
+    /// shrunk the buffer below it.
+    pub fn rollback_to(&mut self, sp: Savepoint) -> Result<(), ShadowError> {
+        self.sb.rollback_to(sp)
+    }
 }
 
 #[cfg(test)]
@@ -168,8 +416,8 @@ mod tests {
     use crate::shadow::persist::NoPersist;
     use crate::shadow::policy::NoPersistPolicy;
     use crate::shadow::test_support::{
-        DenyAllPolicy, TestHostViewStagedFixture, TestStage, TestTable, assert_denied,
-        assert_table_bytes,
+        assert_denied, assert_table_bytes, DenyAllPolicy, TestHostViewStagedFixture, TestStage,
+        TestTable,
     };
     use crate::shadow::view::HostView;
 
@@ -256,19 +504,76 @@ mod tests {
 
     #[test]
     fn alloc_staged_checks_access_policy() {
+        use crate::shadow::cache::NoCache;
+        use crate::shadow::fault::NoFaultHandler;
+
         let mut table = TestTable::new();
         let policy = DenyAllPolicy;
         let persist_policy = NoPersistPolicy::default();
         let mut trigger = NoPersist;
+        let mut fault_handler = NoFaultHandler;
         let mut stage = TestStage::new();
-
-        let base = HostView::new(&mut table, &policy, &persist_policy, &mut trigger);
-        let mut view = HostViewStaged::new(base, &mut stage);
+        let mut cache = NoCache;
+
+        let base = HostView::new(
+            &mut table,
+            &policy,
+            &persist_policy,
+            &mut trigger,
+            &mut fault_handler,
+            &crate::shadow::backing::NoBackingStore,
+        );
+        let mut view = HostViewStaged::new(base, &mut stage, &mut cache);
 
         assert_denied(view.alloc_staged(0, 4, |_| (false, ())));
         assert!(!stage.any_staged());
     }
 
+    #[test]
+    fn alloc_staged_evicts_the_oldest_entry_to_the_table_when_the_buffer_is_full() {
+        let mut fixture = TestHostViewStagedFixture::new();
+        fixture.stage = TestStage::new().stage_evict_lru();
+
+        {
+            let mut view = fixture.view();
+            // Fill all 8 entry slots with disjoint single-byte writes.
+            for i in 0..8u16 {
+                view.alloc_staged(i * 2, 1, |mut slice| {
+                    slice.copy_from_slice(&[i as u8]);
+                    (true, ())
+                })
+                .unwrap();
+            }
+        }
+
+        {
+            let mut view = fixture.view();
+            // A 9th disjoint write has nowhere to go without evicting one
+            // of the previous 8 first.
+            view.alloc_staged(20, 1, |mut slice| {
+                slice.copy_from_slice(&[0xFF]);
+                (true, ())
+            })
+            .unwrap();
+        }
+
+        // The oldest staged entry (addr 0) was force-committed and marked
+        // dirty rather than causing `StageFull`.
+        assert_table_bytes(&fixture.table, 0, &[0x00]);
+        assert!(fixture.table.is_dirty(0, 1).unwrap());
+
+        let mut still_staged = heapless::Vec::<u16, 8>::new();
+        fixture
+            .stage
+            .iter_staged(|addr, _| {
+                let _ = still_staged.push(addr);
+                Ok(())
+            })
+            .unwrap();
+        assert!(!still_staged.contains(&0));
+        assert!(still_staged.contains(&20));
+    }
+
     #[test]
     fn commit_error_leaves_staging_intact() {
         use crate::shadow::test_support::stage_write;
@@ -293,6 +598,8 @@ mod tests {
 
     #[test]
     fn commit_staged_triggers_persist() {
+        use crate::shadow::cache::NoCache;
+        use crate::shadow::fault::NoFaultHandler;
         use crate::shadow::policy::AllowAllPolicy;
         use crate::shadow::staged::PatchStagingBuffer;
         use crate::shadow::table::ShadowTable;
@@ -303,11 +610,20 @@ mod tests {
         let policy = AllowAllPolicy::default();
         let persist_policy = AlwaysPersistPolicy;
         let mut trigger = TrackingPersistTrigger::default();
+        let mut fault_handler = NoFaultHandler;
         let mut stage: PatchStagingBuffer<64, 8> = PatchStagingBuffer::new();
+        let mut cache = NoCache;
 
         {
-            let base = HostView::new(&mut table, &policy, &persist_policy, &mut trigger);
-            let mut view = HostViewStaged::new(base, &mut stage);
+            let base = HostView::new(
+                &mut table,
+                &policy,
+                &persist_policy,
+                &mut trigger,
+                &mut fault_handler,
+                &crate::shadow::backing::NoBackingStore,
+            );
+            let mut view = HostViewStaged::new(base, &mut stage, &mut cache);
 
             // Stage a write
             view.alloc_staged(0, 4, |mut slice| {
@@ -325,4 +641,148 @@ mod tests {
         // Table should be dirty
         assert!(table.is_dirty(0, 4).unwrap());
     }
+
+    #[test]
+    fn rollback_to_discards_writes_staged_after_the_savepoint() {
+        let mut fixture = TestHostViewStagedFixture::new();
+        let mut view = fixture.view();
+
+        view.alloc_staged(0, 2, |mut slice| {
+            slice.copy_from_slice(&[0x01, 0x02]);
+            (true, ())
+        })
+        .unwrap();
+        let sp = view.savepoint();
+        view.alloc_staged(10, 2, |mut slice| {
+            slice.copy_from_slice(&[0x03, 0x04]);
+            (true, ())
+        })
+        .unwrap();
+
+        view.rollback_to(sp).unwrap();
+
+        let mut count = 0;
+        view.iter_staged(|addr, data| {
+            count += 1;
+            assert_eq!(addr, 0);
+            let mut seen = [0u8; 2];
+            data.copy_to_slice(&mut seen);
+            assert_eq!(seen, [0x01, 0x02]);
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn rollback_to_a_savepoint_from_before_clear_staged_is_a_no_op() {
+        let mut fixture = TestHostViewStagedFixture::new();
+        let mut view = fixture.view();
+
+        view.alloc_staged(0, 2, |mut slice| {
+            slice.copy_from_slice(&[0x01, 0x02]);
+            (true, ())
+        })
+        .unwrap();
+        let sp = view.savepoint();
+
+        view.clear_staged().unwrap();
+        view.alloc_staged(10, 1, |mut slice| {
+            slice.copy_from_slice(&[0xAA]);
+            (true, ())
+        })
+        .unwrap();
+
+        view.rollback_to(sp).unwrap();
+
+        let mut count = 0;
+        view.iter_staged(|addr, data| {
+            count += 1;
+            assert_eq!(addr, 10);
+            let mut seen = [0u8; 1];
+            data.copy_to_slice(&mut seen);
+            assert_eq!(seen, [0xAA]);
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn try_commit_staged_applies_writes_when_validator_accepts() {
+        let mut fixture = TestHostViewStagedFixture::new();
+        let mut view = fixture.view();
+
+        view.alloc_staged(0, 2, |mut slice| {
+            slice.copy_from_slice(&[0x01, 0x02]);
+            (true, ())
+        })
+        .unwrap();
+
+        let committed = view
+            .try_commit_staged(|overlay| {
+                let mut seen = [0u8; 2];
+                overlay.read_range_overlay(0, &mut seen).unwrap();
+                seen == [0x01, 0x02]
+            })
+            .unwrap();
+
+        assert!(committed);
+        assert!(!view.is_staged());
+        assert_table_bytes(&fixture.table, 0, &[0x01, 0x02]);
+    }
+
+    #[test]
+    fn try_commit_staged_leaves_table_untouched_when_validator_rejects() {
+        let mut fixture = TestHostViewStagedFixture::new();
+        let mut view = fixture.view();
+
+        view.alloc_staged(0, 2, |mut slice| {
+            slice.copy_from_slice(&[0x01, 0x02]);
+            (true, ())
+        })
+        .unwrap();
+
+        let committed = view.try_commit_staged(|_overlay| false).unwrap();
+
+        assert!(!committed);
+        assert!(view.is_staged());
+        assert_table_bytes(&fixture.table, 0, &[0x00, 0x00]);
+    }
+
+    #[test]
+    fn try_commit_staged_validator_sees_later_staged_writes_override_earlier_overlapping_ones() {
+        let mut fixture = TestHostViewStagedFixture::new();
+        let mut view = fixture.view();
+
+        view.alloc_staged(0, 4, |mut slice| {
+            slice.copy_from_slice(&[0x01, 0x02, 0x03, 0x04]);
+            (true, ())
+        })
+        .unwrap();
+        view.alloc_staged(2, 2, |mut slice| {
+            slice.copy_from_slice(&[0xAA, 0xBB]);
+            (true, ())
+        })
+        .unwrap();
+
+        let mut seen = [0u8; 4];
+        view.try_commit_staged(|overlay| {
+            overlay.read_range_overlay(0, &mut seen).unwrap();
+            true
+        })
+        .unwrap();
+
+        assert_eq!(seen, [0x01, 0x02, 0xAA, 0xBB]);
+    }
+
+    #[test]
+    fn try_commit_staged_with_nothing_staged_is_a_no_op_that_reports_committed() {
+        let mut fixture = TestHostViewStagedFixture::new();
+        let mut view = fixture.view();
+
+        let committed = view.try_commit_staged(|_overlay| false).unwrap();
+
+        assert!(committed);
+    }
 }
@@ -1,43 +1,118 @@
+#![allow(unsafe_code)]
+
 use crate::shadow::{
-    ShadowError,
+    backend::{DenseBackend, TableBackend},
     slice::{ROSlice, RWSlice},
     table::ShadowTable,
+    types::DirtyLease,
+    ShadowError,
 };
 
+/// Direction of a DMA transfer leased via [`KernelView::with_dma_region`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DmaDirection {
+    /// Peripheral writes into the region. [`KernelShadow::complete_dma`](crate::shadow::KernelShadow::complete_dma)
+    /// marks it dirty and re-runs the [`PersistPolicy`](crate::shadow::PersistPolicy).
+    DeviceToMemory,
+    /// Peripheral reads from the region. `complete_dma` marks it clean.
+    MemoryToDevice,
+}
+
+/// A bounds-checked, pinned raw pointer into a shadow region, handed to a
+/// DMA engine so it can fill or drain the region without an intermediate
+/// copy.
+///
+/// Obtained from [`KernelView::with_dma_region`]; consume it with
+/// [`KernelShadow::complete_dma`](crate::shadow::KernelShadow::complete_dma)
+/// once the transfer finishes. While leased, the region is pinned — no
+/// other `with_dma_region` call can lease an overlapping block (see
+/// [`ShadowError::Pinned`]).
+pub struct DmaRegion {
+    addr: u16,
+    ptr: *mut u8,
+    len: usize,
+    dir: DmaDirection,
+}
+
+impl DmaRegion {
+    pub(crate) fn new(addr: u16, ptr: *mut u8, len: usize, dir: DmaDirection) -> Self {
+        Self {
+            addr,
+            ptr,
+            len,
+            dir,
+        }
+    }
+
+    /// Address this region was leased at.
+    pub fn addr(&self) -> u16 {
+        self.addr
+    }
+
+    /// Read-only pointer to the region's bytes.
+    pub fn as_ptr(&self) -> *const u8 {
+        self.ptr
+    }
+
+    /// Read-write pointer to the region's bytes.
+    pub fn as_mut_ptr(&mut self) -> *mut u8 {
+        self.ptr
+    }
+
+    /// Length of the region in bytes.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the region is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Direction this region was leased for.
+    pub fn direction(&self) -> DmaDirection {
+        self.dir
+    }
+}
+
 /// Hardware/kernel-side view of the shadow table.
 ///
 /// Provides read/write access without marking blocks dirty, plus
 /// methods to query and clear dirty state. Used by hardware drivers
 /// to sync shadow data to/from actual hardware registers.
-pub struct KernelView<'a, const TS: usize, const BS: usize, const BC: usize>
+pub struct KernelView<'a, const TS: usize, const BS: usize, const BC: usize, TB = DenseBackend<TS>>
 where
     bitmaps::BitsImpl<BC>: bitmaps::Bits,
+    TB: TableBackend<TS>,
 {
-    table: &'a mut ShadowTable<TS, BS, BC>,
+    table: &'a mut ShadowTable<TS, BS, BC, TB>,
 }
 
-impl<'a, const TS: usize, const BS: usize, const BC: usize> core::fmt::Debug
-    for KernelView<'a, TS, BS, BC>
+impl<'a, const TS: usize, const BS: usize, const BC: usize, TB> core::fmt::Debug
+    for KernelView<'a, TS, BS, BC, TB>
 where
     bitmaps::BitsImpl<BC>: bitmaps::Bits,
+    TB: TableBackend<TS>,
 {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         f.debug_struct("KernelView").finish_non_exhaustive()
     }
 }
 
-impl<'a, const TS: usize, const BS: usize, const BC: usize> KernelView<'a, TS, BS, BC>
+impl<'a, const TS: usize, const BS: usize, const BC: usize, TB> KernelView<'a, TS, BS, BC, TB>
 where
     bitmaps::BitsImpl<BC>: bitmaps::Bits,
+    TB: TableBackend<TS>,
 {
-    pub(crate) fn new(table: &'a mut ShadowTable<TS, BS, BC>) -> Self {
+    pub(crate) fn new(table: &'a mut ShadowTable<TS, BS, BC, TB>) -> Self {
         Self { table }
     }
 }
 
-impl<'a, const TS: usize, const BS: usize, const BC: usize> KernelView<'a, TS, BS, BC>
+impl<'a, const TS: usize, const BS: usize, const BC: usize, TB> KernelView<'a, TS, BS, BC, TB>
 where
     bitmaps::BitsImpl<BC>: bitmaps::Bits,
+    TB: TableBackend<TS>,
 {
     /// Provides zero-copy read access via ROSlice without marking clean.
     pub fn with_ro_slice<F, R>(&self, addr: u16, len: usize, f: F) -> Result<R, ShadowError>
@@ -49,6 +124,10 @@ where
     }
 
     /// Provides zero-copy read-write access via RWSlice without marking dirty.
+    ///
+    /// Bypasses any lock taken via [`HostView::lock`](crate::shadow::view::HostView::lock);
+    /// use [`Self::with_rw_slice_honoring_lock`] if the write should be
+    /// rejected instead.
     pub fn with_rw_slice<F, R>(&mut self, addr: u16, len: usize, f: F) -> Result<R, ShadowError>
     where
         F: FnOnce(RWSlice<'_>) -> R,
@@ -57,6 +136,47 @@ where
             .with_bytes_mut(addr, len, |data| Ok(f(RWSlice::new(data))))
     }
 
+    /// Same as [`Self::with_rw_slice`], but when `honor_lock` is true, a
+    /// range overlapping a block locked via
+    /// [`HostView::lock`](crate::shadow::view::HostView::lock) is rejected
+    /// with [`ShadowError::Denied`] instead of being written — e.g. a
+    /// hardware-readback path that must not clobber a value the host has
+    /// already committed and locked, while other kernel paths keep calling
+    /// [`Self::with_rw_slice`] to bypass the lock unconditionally.
+    pub fn with_rw_slice_honoring_lock<F, R>(
+        &mut self,
+        addr: u16,
+        len: usize,
+        honor_lock: bool,
+        f: F,
+    ) -> Result<R, ShadowError>
+    where
+        F: FnOnce(RWSlice<'_>) -> R,
+    {
+        if honor_lock && self.table.is_locked(addr, len)? {
+            return Err(ShadowError::Denied);
+        }
+        self.with_rw_slice(addr, len, f)
+    }
+
+    /// Copies `len` bytes from `src` to `dst` within the table, with
+    /// `memmove` semantics — safe even when the ranges overlap, like the
+    /// block copier in the holey-bytes VM that handles overlapping
+    /// source/destination. Marks every block `dst..dst+len` overlaps dirty,
+    /// e.g. promoting a freshly received buffer into an active slot. Use
+    /// [`Self::copy_within_quiet`] for a ping-pong copy the driver tracks
+    /// itself, where the destination shouldn't look host-modified.
+    pub fn copy_within(&mut self, src: u16, dst: u16, len: usize) -> Result<(), ShadowError> {
+        self.table.copy_within(src, dst, len)
+    }
+
+    /// Same as [`Self::copy_within`], but leaves dirty state untouched,
+    /// matching the usual kernel-side "reads and writes don't mark dirty"
+    /// convention.
+    pub fn copy_within_quiet(&mut self, src: u16, dst: u16, len: usize) -> Result<(), ShadowError> {
+        self.table.copy_within_quiet(src, dst, len)
+    }
+
     /// Iterates over each dirty block, providing its address and data as ROSlice.
     pub fn iter_dirty<F>(&self, mut f: F) -> Result<(), ShadowError>
     where
@@ -66,9 +186,87 @@ where
             .iter_dirty(|addr, data| f(addr, ROSlice::new(data)))
     }
 
+    /// Iterates over each maximal run of contiguous dirty blocks, coalescing
+    /// adjacent dirty blocks into a single `(addr, data)` region instead of
+    /// handing them to `f` one block at a time — useful when syncing to a
+    /// peripheral where each transaction has fixed overhead, so fewer,
+    /// larger bursts beat one per block.
+    pub fn iter_dirty_runs<F>(&self, mut f: F) -> Result<(), ShadowError>
+    where
+        F: FnMut(u16, ROSlice<'_>) -> Result<(), ShadowError>,
+    {
+        self.table
+            .iter_dirty_runs(|addr, data| f(addr, ROSlice::new(data)))
+    }
+
+    /// Serializes every dirty run into `out` as a compact, CRC-protected
+    /// byte stream — the `[addr_le][len_le][payload][crc32]` patch-stream
+    /// format for shipping changed shadow state over a link (UART, SPI) to
+    /// a companion chip or host running [`Self::apply_dirty_deltas`],
+    /// instead of transferring the whole table. Returns the number of
+    /// bytes written, or [`ShadowError::OutOfBounds`] if `out` is too
+    /// small.
+    pub fn encode_dirty_deltas(&self, out: &mut [u8]) -> Result<usize, ShadowError> {
+        self.table.encode_dirty_deltas(out)
+    }
+
+    /// Applies a patch stream produced by [`Self::encode_dirty_deltas`]
+    /// (from this or another `ShadowTable` of matching layout), writing
+    /// each record's payload back without marking anything dirty, so the
+    /// receiver reconstructs identical state. Returns
+    /// [`ShadowError::ChecksumMismatch`] on the first corrupted record.
+    pub fn apply_dirty_deltas(&mut self, input: &[u8]) -> Result<(), ShadowError> {
+        self.table.decode_dirty_deltas(input)
+    }
+
+    /// Like [`Self::encode_dirty_deltas`], but LZ4-compresses each record's
+    /// payload when doing so shrinks it, for constrained links where
+    /// transmit size matters more than CPU time. See
+    /// [`ShadowTable::encode_dirty_deltas_compressed`] for the wire format.
+    #[cfg(feature = "lz4")]
+    pub fn encode_dirty_deltas_compressed(&self, out: &mut [u8]) -> Result<usize, ShadowError> {
+        self.table.encode_dirty_deltas_compressed(out)
+    }
+
+    /// Applies a stream produced by [`Self::encode_dirty_deltas_compressed`].
+    #[cfg(feature = "lz4")]
+    pub fn apply_dirty_deltas_compressed(&mut self, input: &[u8]) -> Result<(), ShadowError> {
+        self.table.decode_dirty_deltas_compressed(input)
+    }
+
+    /// Hands up to `max` dirty blocks, resuming from wherever the last call
+    /// left off, to `f`. Returns `true` if it stopped at `max` with dirty
+    /// blocks still ahead of the cursor, and `false` once the scan reaches
+    /// the last block — keep calling until it returns `false` — so a caller with
+    /// a hard per-invocation time budget (e.g. an ISR) gets deterministic
+    /// worst-case latency instead of an unbounded [`Self::iter_dirty`] pass,
+    /// while still reaching every dirty block eventually.
+    ///
+    /// Marking a processed block clean remains the caller's responsibility,
+    /// same as [`Self::iter_dirty`]. A host write landing on a block behind
+    /// the cursor is picked up once the scan wraps back to the top; use
+    /// [`Self::reset_cursor`] to force a restart from the top early.
+    pub fn for_each_dirty_block_bounded<F>(
+        &mut self,
+        max: usize,
+        mut f: F,
+    ) -> Result<bool, ShadowError>
+    where
+        F: FnMut(u16, ROSlice<'_>) -> Result<(), ShadowError>,
+    {
+        self.table
+            .for_each_dirty_block_bounded(max, |addr, data| f(addr, ROSlice::new(data)))
+    }
+
+    /// Restarts [`Self::for_each_dirty_block_bounded`]'s cursor from the top
+    /// of the table.
+    pub fn reset_cursor(&mut self) {
+        self.table.reset_cursor()
+    }
+
     /// Marks all blocks overlapping the given range as clean.
     pub fn mark_clean(&mut self, addr: u16, len: usize) -> Result<(), ShadowError> {
-        self.table.mark_clean(addr, len)
+        self.table.clear_dirty(addr, len)
     }
 
     /// Returns true if any block overlapping the given range is dirty.
@@ -83,7 +281,65 @@ where
 
     /// Clears all dirty flags in the table.
     pub fn clear_dirty(&mut self) {
-        self.table.clear_dirty()
+        self.table.clear_all_dirty()
+    }
+
+    /// Leases each dirty, not-already-in-flight block to `f` as a
+    /// [`DirtyLease`] for a zero-copy DMA flush, without blocking on the
+    /// transfer.
+    ///
+    /// `f` should hand the lease's bytes to the DMA engine and return
+    /// immediately; call [`Self::complete_lease`] once the transfer
+    /// finishes so the block's dirty bit is cleared or, if a write landed
+    /// mid-transfer, left dirty for the next flush.
+    pub fn lease_dirty_blocks<F>(&mut self, f: F)
+    where
+        F: FnMut(DirtyLease<'_>),
+    {
+        self.table.lease_dirty_blocks(f)
+    }
+
+    /// Completes a lease taken by [`Self::lease_dirty_blocks`].
+    ///
+    /// Clears the block's dirty bit only if `ok` is true and the block's
+    /// generation still matches the one captured at lease time; otherwise
+    /// the block stays dirty so it is re-flushed on the next pass.
+    pub fn complete_lease(
+        &mut self,
+        addr: u16,
+        generation: u32,
+        ok: bool,
+    ) -> Result<(), ShadowError> {
+        self.table.complete_lease(addr, generation, ok)
+    }
+
+    /// Leases `addr..addr+len` to a DMA engine, pinning it for the
+    /// duration of the transfer and returning a bounds-checked raw pointer
+    /// to its bytes.
+    ///
+    /// The region stays pinned — no other `with_dma_region` call can lease
+    /// an overlapping block — until the transfer finishes and
+    /// [`KernelShadow::complete_dma`](crate::shadow::KernelShadow::complete_dma)
+    /// releases it. This call itself doesn't block on the transfer; it
+    /// only hands out the pointer, mirroring [`Self::lease_dirty_blocks`].
+    pub fn with_dma_region(
+        &mut self,
+        addr: u16,
+        len: usize,
+        dir: DmaDirection,
+    ) -> Result<DmaRegion, ShadowError> {
+        self.table.pin_range(addr, len)?;
+
+        match self
+            .table
+            .with_bytes_mut(addr, len, |buf| Ok(buf.as_mut_ptr()))
+        {
+            Ok(ptr) => Ok(DmaRegion::new(addr, ptr, len, dir)),
+            Err(err) => {
+                let _ = self.table.unpin_range(addr, len);
+                Err(err)
+            }
+        }
     }
 }
 
@@ -123,6 +379,75 @@ mod tests {
         .unwrap();
     }
 
+    #[test]
+    fn copy_within_copies_non_overlapping_ranges_and_marks_dirty() {
+        let mut table = TestTable::new();
+        let mut view = KernelView::new(&mut table);
+
+        view.with_rw_slice(0, 4, |mut slice| slice.copy_from_slice(&[1, 2, 3, 4]))
+            .unwrap();
+
+        view.copy_within(0, 32, 4).unwrap();
+
+        view.with_ro_slice(32, 4, |slice| {
+            let mut buf = [0u8; 4];
+            slice.copy_to_slice(&mut buf);
+            assert_eq!(buf, [1, 2, 3, 4]);
+        })
+        .unwrap();
+        assert!(view.is_dirty(32, 4).unwrap());
+    }
+
+    #[test]
+    fn copy_within_handles_forward_overlap() {
+        let mut table = TestTable::new();
+        let mut view = KernelView::new(&mut table);
+
+        view.with_rw_slice(0, 6, |mut slice| slice.copy_from_slice(&[1, 2, 3, 4, 5, 6]))
+            .unwrap();
+
+        // dst > src: a naive forward byte copy would clobber source bytes
+        // it hasn't read yet.
+        view.copy_within(0, 2, 6).unwrap();
+
+        view.with_ro_slice(2, 6, |slice| {
+            let mut buf = [0u8; 6];
+            slice.copy_to_slice(&mut buf);
+            assert_eq!(buf, [1, 2, 3, 4, 5, 6]);
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn copy_within_handles_backward_overlap() {
+        let mut table = TestTable::new();
+        let mut view = KernelView::new(&mut table);
+
+        view.with_rw_slice(2, 6, |mut slice| slice.copy_from_slice(&[1, 2, 3, 4, 5, 6]))
+            .unwrap();
+
+        // dst < src: a naive backward byte copy would clobber source bytes
+        // it hasn't read yet.
+        view.copy_within(2, 0, 6).unwrap();
+
+        view.with_ro_slice(0, 6, |slice| {
+            let mut buf = [0u8; 6];
+            slice.copy_to_slice(&mut buf);
+            assert_eq!(buf, [1, 2, 3, 4, 5, 6]);
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn copy_within_quiet_leaves_dirty_state_untouched() {
+        let mut table = TestTable::new();
+        let mut view = KernelView::new(&mut table);
+
+        view.copy_within_quiet(0, 32, 4).unwrap();
+
+        assert!(!view.any_dirty());
+    }
+
     #[test]
     fn kernel_clear_dirty_clears_all_blocks() {
         let mut table = TestTable::new();
@@ -186,6 +511,175 @@ mod tests {
         .unwrap();
     }
 
+    #[test]
+    fn iter_dirty_runs_coalesces_adjacent_dirty_blocks() {
+        let mut table = TestTable::new();
+        // Blocks 0 and 1 (bytes 0-15, 16-31) are adjacent; block 3 (48-63) is not.
+        table.mark_dirty(0, 16).unwrap();
+        table.mark_dirty(16, 16).unwrap();
+        table.mark_dirty(48, 16).unwrap();
+
+        let view = KernelView::new(&mut table);
+
+        let mut count = 0;
+        let mut runs = [(0u16, 0usize); 4];
+        view.iter_dirty_runs(|addr, data| {
+            runs[count] = (addr, data.len());
+            count += 1;
+            Ok(())
+        })
+        .unwrap();
+
+        assert_eq!(count, 2);
+        assert_eq!(runs[0], (0, 32));
+        assert_eq!(runs[1], (48, 16));
+    }
+
+    #[test]
+    fn for_each_dirty_block_bounded_resumes_across_calls() {
+        let mut table = TestTable::new();
+        table.mark_dirty(0, 16).unwrap();
+        table.mark_dirty(16, 16).unwrap();
+        table.mark_dirty(32, 16).unwrap();
+
+        let mut view = KernelView::new(&mut table);
+
+        let mut count = 0;
+        let more = view
+            .for_each_dirty_block_bounded(2, |_addr, _data| {
+                count += 1;
+                Ok(())
+            })
+            .unwrap();
+        assert!(more);
+        assert_eq!(count, 2);
+
+        let more = view
+            .for_each_dirty_block_bounded(2, |_addr, _data| {
+                count += 1;
+                Ok(())
+            })
+            .unwrap();
+        assert!(!more);
+        assert_eq!(count, 3);
+    }
+
+    #[test]
+    fn reset_cursor_restarts_bounded_scan() {
+        let mut table = TestTable::new();
+        table.mark_dirty(0, 16).unwrap();
+        table.mark_dirty(16, 16).unwrap();
+
+        let mut view = KernelView::new(&mut table);
+        view.for_each_dirty_block_bounded(1, |_addr, _data| Ok(()))
+            .unwrap();
+
+        view.reset_cursor();
+
+        let mut first_addr = None;
+        view.for_each_dirty_block_bounded(1, |addr, _data| {
+            first_addr = Some(addr);
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(first_addr, Some(0));
+    }
+
+    #[test]
+    fn encode_then_apply_dirty_deltas_mirrors_state_without_marking_dirty() {
+        let mut src_table = TestTable::new();
+        let mut src = KernelView::new(&mut src_table);
+        src.with_rw_slice(0, 16, |mut slice| slice.copy_from_slice(&[0x42; 16]))
+            .unwrap();
+        src_table.mark_dirty(0, 16).unwrap();
+
+        let mut stream = [0u8; 64];
+        let len = KernelView::new(&mut src_table)
+            .encode_dirty_deltas(&mut stream)
+            .unwrap();
+
+        let mut dst_table = TestTable::new();
+        let mut dst = KernelView::new(&mut dst_table);
+        dst.apply_dirty_deltas(&stream[..len]).unwrap();
+
+        dst.with_ro_slice(0, 16, |slice| {
+            let mut buf = [0u8; 16];
+            slice.copy_to_slice(&mut buf);
+            assert_eq!(buf, [0x42; 16]);
+        })
+        .unwrap();
+        assert!(!dst.any_dirty());
+    }
+
+    #[cfg(feature = "lz4")]
+    #[test]
+    fn encode_then_apply_dirty_deltas_compressed_mirrors_state() {
+        let mut src_table = TestTable::new();
+        let mut src = KernelView::new(&mut src_table);
+        src.with_rw_slice(0, 16, |mut slice| slice.copy_from_slice(&[0x42; 16]))
+            .unwrap();
+        src_table.mark_dirty(0, 16).unwrap();
+
+        let mut stream = [0u8; 64];
+        let len = KernelView::new(&mut src_table)
+            .encode_dirty_deltas_compressed(&mut stream)
+            .unwrap();
+
+        let mut dst_table = TestTable::new();
+        let mut dst = KernelView::new(&mut dst_table);
+        dst.apply_dirty_deltas_compressed(&stream[..len]).unwrap();
+
+        dst.with_ro_slice(0, 16, |slice| {
+            let mut buf = [0u8; 16];
+            slice.copy_to_slice(&mut buf);
+            assert_eq!(buf, [0x42; 16]);
+        })
+        .unwrap();
+        assert!(!dst.any_dirty());
+    }
+
+    #[test]
+    fn with_rw_slice_honoring_lock_rejects_write_to_a_locked_block() {
+        let mut table = TestTable::new();
+        table.lock(0, 16).unwrap();
+
+        let mut view = KernelView::new(&mut table);
+        let result = view.with_rw_slice_honoring_lock(0, 16, true, |mut slice| {
+            slice.copy_from_slice(&[0xAA; 16])
+        });
+
+        assert_eq!(result, Err(ShadowError::Denied));
+    }
+
+    #[test]
+    fn with_rw_slice_honoring_lock_allows_bypass_when_not_honoring() {
+        let mut table = TestTable::new();
+        table.lock(0, 16).unwrap();
+
+        let mut view = KernelView::new(&mut table);
+        view.with_rw_slice_honoring_lock(0, 16, false, |mut slice| {
+            slice.copy_from_slice(&[0xAA; 16])
+        })
+        .unwrap();
+
+        view.with_ro_slice(0, 16, |slice| {
+            let mut buf = [0u8; 16];
+            slice.copy_to_slice(&mut buf);
+            assert_eq!(buf, [0xAA; 16]);
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn with_rw_slice_bypasses_lock_unconditionally() {
+        let mut table = TestTable::new();
+        table.lock(0, 16).unwrap();
+
+        let mut view = KernelView::new(&mut table);
+        view.with_rw_slice(0, 16, |mut slice| slice.copy_from_slice(&[0x11; 16]))
+            .unwrap();
+    }
+
     #[test]
     fn is_dirty_partial_overlap_returns_true() {
         let mut table = TestTable::new();
@@ -219,6 +713,48 @@ mod tests {
         assert!(!view.is_dirty(0, 16).unwrap());
     }
 
+    #[test]
+    fn with_dma_region_yields_writable_pointer_into_the_table() {
+        let mut table = TestTable::new();
+        let mut view = KernelView::new(&mut table);
+
+        let mut region = view
+            .with_dma_region(0, 4, DmaDirection::DeviceToMemory)
+            .unwrap();
+        assert_eq!(region.len(), 4);
+        assert_eq!(region.addr(), 0);
+        unsafe {
+            core::ptr::copy_nonoverlapping(
+                [0x11u8, 0x22, 0x33, 0x44].as_ptr(),
+                region.as_mut_ptr(),
+                4,
+            );
+        }
+
+        view.with_ro_slice(0, 4, |slice| {
+            let mut buf = [0u8; 4];
+            slice.copy_to_slice(&mut buf);
+            assert_eq!(buf, [0x11, 0x22, 0x33, 0x44]);
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn with_dma_region_rejects_overlapping_lease() {
+        let mut table = TestTable::new();
+        let mut view = KernelView::new(&mut table);
+
+        let _region = view
+            .with_dma_region(0, 4, DmaDirection::DeviceToMemory)
+            .unwrap();
+
+        assert_eq!(
+            view.with_dma_region(2, 4, DmaDirection::DeviceToMemory)
+                .unwrap_err(),
+            ShadowError::Pinned
+        );
+    }
+
     #[test]
     fn iter_dirty_stops_on_first_error() {
         let mut table = TestTable::new();
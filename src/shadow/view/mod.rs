@@ -1,7 +1,9 @@
 mod host;
 mod host_staged;
 mod kernel;
+mod transaction;
 
-pub use host::HostView;
-pub use host_staged::HostViewStaged;
-pub use kernel::KernelView;
+pub use host::{DmaWindowGuard, HostView};
+pub use host_staged::{HostViewStaged, StagedOverlay};
+pub use kernel::{DmaDirection, DmaRegion, KernelView};
+pub use transaction::Transaction;
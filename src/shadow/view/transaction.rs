@@ -0,0 +1,182 @@
+use crate::shadow::{
+    backend::TableBackend,
+    fault::AccessFaultHandler,
+    helpers::block_span,
+    policy::PersistPolicy,
+    slice::{RWSlice, WOSlice},
+    view::HostView,
+    AccessPolicy, PersistTrigger, ShadowError,
+};
+
+/// All-or-nothing batch of writes, started by
+/// [`HostView::with_transaction`].
+///
+/// Each distinct block written through [`Self::with_wo_slice`]/
+/// [`Self::with_rw_slice`] is snapshotted into a fixed-size scratch buffer
+/// the first time it's touched, bounded by the `MAX_BLOCKS` const generic
+/// chosen when the transaction is opened. If the closure returns `Err`,
+/// every touched block is restored from its snapshot and nothing is marked
+/// dirty; if it returns `Ok`, all touched blocks are marked dirty in one
+/// step and persistence is triggered as configured.
+pub struct Transaction<
+    'v,
+    'a,
+    const TS: usize,
+    const BS: usize,
+    const BC: usize,
+    AP,
+    PP,
+    PT,
+    PK,
+    TB,
+    const MAX_BLOCKS: usize,
+> where
+    bitmaps::BitsImpl<BC>: bitmaps::Bits,
+    AP: AccessPolicy,
+    PP: PersistPolicy<PK>,
+    PT: PersistTrigger<PK>,
+    TB: TableBackend<TS>,
+{
+    view: &'v mut HostView<'a, TS, BS, BC, AP, PP, PT, PK, TB>,
+    snapshots: [(usize, [u8; BS]); MAX_BLOCKS],
+    count: usize,
+}
+
+impl<
+        'v,
+        'a,
+        const TS: usize,
+        const BS: usize,
+        const BC: usize,
+        AP,
+        PP,
+        PT,
+        PK,
+        TB,
+        const MAX_BLOCKS: usize,
+    > Transaction<'v, 'a, TS, BS, BC, AP, PP, PT, PK, TB, MAX_BLOCKS>
+where
+    bitmaps::BitsImpl<BC>: bitmaps::Bits,
+    AP: AccessPolicy,
+    PP: PersistPolicy<PK>,
+    PT: PersistTrigger<PK>,
+    TB: TableBackend<TS>,
+{
+    pub(crate) fn new(view: &'v mut HostView<'a, TS, BS, BC, AP, PP, PT, PK, TB>) -> Self {
+        Self {
+            view,
+            snapshots: [(0usize, [0u8; BS]); MAX_BLOCKS],
+            count: 0,
+        }
+    }
+
+    fn snapshot_block(&mut self, block: usize) -> Result<(), ShadowError> {
+        if self.snapshots[..self.count]
+            .iter()
+            .any(|(b, _)| *b == block)
+        {
+            return Ok(());
+        }
+        if self.count >= MAX_BLOCKS {
+            return Err(ShadowError::TransactionFull);
+        }
+
+        let off = (block * BS) as u16;
+        let mut snapshot = [0u8; BS];
+        self.view.table.with_bytes(off, BS, |data| {
+            snapshot.copy_from_slice(data);
+            Ok(())
+        })?;
+
+        self.snapshots[self.count] = (block, snapshot);
+        self.count += 1;
+        Ok(())
+    }
+
+    fn snapshot_range(&mut self, addr: u16, len: usize) -> Result<(), ShadowError> {
+        let (sb, eb) = block_span::<TS, BS, BC>(addr, len)?;
+        for block in sb..=eb {
+            self.snapshot_block(block)?;
+        }
+        Ok(())
+    }
+
+    /// Zero-copy write access via WOSlice, staged within the transaction.
+    ///
+    /// Returns `Denied` if the access policy rejects the write, after
+    /// notifying the [`AccessFaultHandler`](crate::shadow::AccessFaultHandler).
+    pub fn with_wo_slice<F, R>(&mut self, addr: u16, len: usize, f: F) -> Result<R, ShadowError>
+    where
+        F: FnOnce(WOSlice<'_>) -> R,
+    {
+        if !self.view.access_policy.can_write(addr, len) {
+            self.view.fault_handler.on_write_denied(addr, len);
+            return Err(ShadowError::Denied);
+        }
+        if self.view.table.is_locked(addr, len)? {
+            self.view.fault_handler.on_write_denied(addr, len);
+            return Err(ShadowError::Denied);
+        }
+
+        self.snapshot_range(addr, len)?;
+        self.view
+            .table
+            .with_bytes_mut(addr, len, |data| Ok(f(WOSlice::new(data))))
+    }
+
+    /// Zero-copy read-write access via RWSlice, staged within the transaction.
+    ///
+    /// Returns `Denied` if the access policy rejects either read or write,
+    /// after notifying the [`AccessFaultHandler`](crate::shadow::AccessFaultHandler).
+    pub fn with_rw_slice<F, R>(&mut self, addr: u16, len: usize, f: F) -> Result<R, ShadowError>
+    where
+        F: FnOnce(RWSlice<'_>) -> R,
+    {
+        let can_read = self.view.access_policy.can_read(addr, len);
+        let can_write = self.view.access_policy.can_write(addr, len);
+        if !can_read || !can_write {
+            self.view.fault_handler.on_write_denied(addr, len);
+            return Err(ShadowError::Denied);
+        }
+        if self.view.table.is_locked(addr, len)? {
+            self.view.fault_handler.on_write_denied(addr, len);
+            return Err(ShadowError::Denied);
+        }
+
+        self.snapshot_range(addr, len)?;
+        self.view
+            .table
+            .with_bytes_mut(addr, len, |data| Ok(f(RWSlice::new(data))))
+    }
+
+    pub(crate) fn rollback(&mut self) {
+        for i in 0..self.count {
+            let (block, snapshot) = &self.snapshots[i];
+            let off = (*block * BS) as u16;
+            let _ = self.view.table.with_bytes_mut(off, BS, |data| {
+                data.copy_from_slice(snapshot);
+                Ok(())
+            });
+        }
+    }
+
+    pub(crate) fn commit(&mut self) -> Result<(), ShadowError> {
+        let mut should_persist = false;
+        for i in 0..self.count {
+            let (block, _) = self.snapshots[i];
+            let addr = (block * BS) as u16;
+            self.view.table.mark_dirty(addr, BS)?;
+            should_persist |=
+                self.view
+                    .persist_policy
+                    .push_persist_keys_for_range(addr, BS, |key| {
+                        self.view.persist_trigger.push_key(key)
+                    });
+        }
+
+        if should_persist {
+            self.view.persist_trigger.request_persist();
+        }
+        Ok(())
+    }
+}